@@ -0,0 +1,188 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::input::Action;
+use crate::{ServerConfig, Theme};
+
+/// Votes to cast without asking, for trying out how a set of themes ends up distributed.
+/// Read from a JSON file such as
+/// `{"rules": [{"contains": "horror", "vote": "yes"}], "default": "skip"}`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    /// Tried in order; the first one matching a theme decides its vote
+    rules: Vec<Rule>,
+    /// Vote for themes no rule matches
+    #[serde(default = "default_vote")]
+    default: Action,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rule {
+    /// Case-insensitive keyword looked for in the theme and its description
+    contains: String,
+    vote: Action,
+}
+
+fn default_vote() -> Action {
+    Action::Skip
+}
+
+impl Rules {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Couldn't read auto-vote rules from {}: {e}", path.display())
+        })?;
+        let mut rules: Self = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid auto-vote rules in {}: {e}", path.display()))?;
+
+        for rule in &mut rules.rules {
+            rule.contains = rule.contains.trim().to_lowercase();
+            if rule.contains.is_empty() {
+                anyhow::bail!("Empty keyword in auto-vote rules in {}", path.display());
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Fails if a rule asks for something that isn't a vote the server takes.
+    pub fn check(&self, config: &ServerConfig) -> anyhow::Result<()> {
+        let votes = self.rules.iter().map(|rule| rule.vote);
+        for action in votes.chain([self.default]) {
+            match action.vote_type() {
+                Some(vote_type) if config.allows(vote_type) => {}
+                Some(_) => anyhow::bail!(
+                    "Auto-vote rules use \"{}\", which the server isn't accepting",
+                    action.name().to_lowercase()
+                ),
+                None => anyhow::bail!(
+                    "Auto-vote rules can only vote yes, no, maybe or skip, not \"{}\"",
+                    action.name().to_lowercase()
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// The vote type for `theme`, and the keyword that picked it (`None` for the default).
+    pub fn decide(&self, theme: &Theme) -> (&'static str, Option<&str>) {
+        let content = theme.content.to_lowercase();
+        let description = theme
+            .description
+            .as_deref()
+            .unwrap_or_default()
+            .to_lowercase();
+        let (action, keyword) = self
+            .rules
+            .iter()
+            .find(|rule| content.contains(&rule.contains) || description.contains(&rule.contains))
+            .map_or((self.default, None), |rule| {
+                (rule.vote, Some(rule.contains.as_str()))
+            });
+        // `check` has made sure every action here is a vote
+        (action.vote_type().unwrap_or("skip"), keyword)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Loads rules from `json` through a file, as `--auto-vote` would.
+    fn load_rules(name: &str, json: &str) -> anyhow::Result<Rules> {
+        let path = env::temp_dir().join(format!(
+            "slaughter_vote_autovote_{}_{name}.json",
+            std::process::id()
+        ));
+        fs::write(&path, json).unwrap();
+        let rules = Rules::load(&path);
+        fs::remove_file(path).unwrap();
+        rules
+    }
+
+    fn theme(content: &str, description: Option<&str>) -> Theme {
+        Theme {
+            id: 1,
+            content: content.to_string(),
+            description: description.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn the_first_matching_rule_decides_the_vote() {
+        let rules = load_rules(
+            "matching",
+            r#"{"rules": [{"contains": " Horror ", "vote": "yes"},
+                          {"contains": "space", "vote": "no"}],
+                "default": "skip"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rules.decide(&theme("Cosmic HORROR in space", None)),
+            ("yes", Some("horror"))
+        );
+        // The description is looked at too
+        assert_eq!(
+            rules.decide(&theme("Alone", Some("Lost in space"))),
+            ("no", Some("space"))
+        );
+    }
+
+    #[test]
+    fn themes_no_rule_matches_get_the_default() {
+        let rules = load_rules(
+            "default",
+            r#"{"rules": [{"contains": "horror", "vote": "yes"}], "default": "no"}"#,
+        )
+        .unwrap();
+        assert_eq!(rules.decide(&theme("Gardening", None)), ("no", None));
+
+        // Skipping when no default is given
+        let skipping = load_rules(
+            "no_default",
+            r#"{"rules": [{"contains": "horror", "vote": "yes"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(skipping.decide(&theme("Gardening", None)), ("skip", None));
+    }
+
+    #[test]
+    fn rules_may_only_cast_votes_the_server_takes() {
+        let config = ServerConfig::default();
+        let valid = load_rules(
+            "valid",
+            r#"{"rules": [{"contains": "horror", "vote": "yes"}], "default": "no"}"#,
+        )
+        .unwrap();
+        assert!(valid.check(&config).is_ok());
+
+        let quits = load_rules(
+            "quit",
+            r#"{"rules": [{"contains": "horror", "vote": "quit"}]}"#,
+        )
+        .unwrap();
+        let error = quits.check(&config).unwrap_err().to_string();
+        assert!(error.contains("not \"quit\""), "{error}");
+
+        // The server isn't taking maybe votes
+        let maybe = load_rules(
+            "maybe",
+            r#"{"rules": [{"contains": "horror", "vote": "yes"}], "default": "maybe"}"#,
+        )
+        .unwrap();
+        let error = maybe.check(&config).unwrap_err().to_string();
+        assert!(error.contains("isn't accepting"), "{error}");
+    }
+
+    #[test]
+    fn an_empty_keyword_is_an_error() {
+        let error = load_rules("empty", r#"{"rules": [{"contains": "  ", "vote": "yes"}]}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Empty keyword"), "{error}");
+    }
+}
@@ -0,0 +1,158 @@
+use std::env;
+
+/// Width of the separator lines framing each theme.
+const FRAME_WIDTH: usize = 60;
+/// Continuation lines of a wrapped theme are indented by this much.
+const WRAP_INDENT: &str = "  ";
+
+/// Makes server-provided text safe to print: control characters such as stray
+/// newlines or escape codes become spaces, and runs of whitespace collapse to one.
+pub fn sanitize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits `text` into lines of at most `width` characters, breaking between words
+/// where possible and inside words longer than a whole line.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_len = 0;
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if line_len > 0 && line_len + 1 + word.len() <= width {
+            line.push(' ');
+            line.extend(&word);
+            line_len += 1 + word.len();
+            continue;
+        }
+        if line_len > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            lines.push(word.drain(..width).collect());
+        }
+        line_len = word.len();
+        line = word.into_iter().collect();
+    }
+    if line_len > 0 || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Theme text ready to print under a header: sanitized, wrapped to the terminal
+/// (but no wider than the frame), with continuation lines indented.
+pub fn theme_lines(content: &str) -> Vec<String> {
+    let width = env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .unwrap_or(FRAME_WIDTH)
+        .min(FRAME_WIDTH);
+    theme_lines_within(content, width)
+}
+
+fn theme_lines_within(content: &str, width: usize) -> Vec<String> {
+    let content = sanitize(content);
+    if content.chars().count() <= width {
+        return vec![content];
+    }
+
+    let mut lines = wrap(&content, width.saturating_sub(WRAP_INDENT.len())).into_iter();
+    let first = lines.next().unwrap_or_default();
+    std::iter::once(first)
+        .chain(lines.map(|line| format!("{WRAP_INDENT}{line}")))
+        .collect()
+}
+
+/// Splits a bar of `width` cells between `counts` in proportion, rounding so the
+/// segments always fill the whole bar (an all-zero bar has empty segments).
+pub fn bar_segments(counts: &[i64], width: usize) -> Vec<usize> {
+    let total: i64 = counts.iter().map(|&count| count.max(0)).sum();
+    if total == 0 {
+        return vec![0; counts.len()];
+    }
+
+    let exact: Vec<f64> = counts
+        .iter()
+        .map(|&count| count.max(0) as f64 * width as f64 / total as f64)
+        .collect();
+    let mut segments: Vec<usize> = exact.iter().map(|share| share.floor() as usize).collect();
+
+    // Hand the cells lost to rounding down to the largest remainders
+    let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+    by_remainder
+        .sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    let missing = width - segments.iter().sum::<usize>();
+    for &i in by_remainder.iter().take(missing) {
+        segments[i] += 1;
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_breaks_between_words() {
+        assert_eq!(wrap("the quick brown fox", 10), ["the quick", "brown fox"]);
+        assert_eq!(wrap("fits", 10), ["fits"]);
+        assert_eq!(wrap("exactly ten", 11), ["exactly ten"]);
+    }
+
+    #[test]
+    fn wrap_splits_words_longer_than_a_line() {
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("hi abcdefgh yo", 4), ["hi", "abcd", "efgh", "yo"]);
+        // Counted in characters, not bytes
+        assert_eq!(wrap("ééééé", 2), ["éé", "éé", "é"]);
+    }
+
+    #[test]
+    fn wrap_handles_tiny_widths() {
+        assert_eq!(wrap("ab c", 1), ["a", "b", "c"]);
+        // Zero is treated as one, rather than looping forever
+        assert_eq!(wrap("ab c", 0), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn wrap_of_nothing_is_one_empty_line() {
+        assert_eq!(wrap("", 10), [""]);
+        assert_eq!(wrap("   ", 10), [""]);
+        assert_eq!(wrap("", 0), [""]);
+    }
+
+    #[test]
+    fn theme_lines_indents_continuations() {
+        assert_eq!(theme_lines_within("short theme", 20), ["short theme"]);
+        assert_eq!(
+            theme_lines_within("a theme far too long for one line", 12),
+            ["a theme", "  far too", "  long for", "  one line"]
+        );
+    }
+
+    #[test]
+    fn theme_lines_sanitizes_first() {
+        assert_eq!(
+            theme_lines_within("two\nlines\x1b[31m", 60),
+            ["two lines [31m"]
+        );
+        assert_eq!(theme_lines_within("", 60), [""]);
+    }
+
+    #[test]
+    fn theme_lines_survives_tiny_widths() {
+        for width in [0, 1, 2] {
+            let lines = theme_lines_within("abc de", width);
+            assert!(!lines.is_empty());
+            assert_eq!(lines.concat().replace(' ', ""), "abcde", "width {width}");
+        }
+    }
+}
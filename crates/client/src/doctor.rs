@@ -0,0 +1,317 @@
+use colored::*;
+use std::env;
+
+use crate::{BACKEND_URL, Cli, ServerConfig, Unauthorized, authenticate, fetch_config};
+
+/// What the checks ask of the outside world: the server, or a stand-in in tests.
+trait Backend {
+    async fn health(&self) -> anyhow::Result<serde_json::Value>;
+    async fn config(&self) -> anyhow::Result<ServerConfig>;
+    /// Logs in through the browser, returning the token.
+    async fn log_in(&self, supabase_url: String) -> anyhow::Result<String>;
+    /// The user id the server reads from `token`.
+    async fn current_user(&self, token: &str) -> anyhow::Result<String>;
+}
+
+/// The real server, logging in as the command line says.
+struct Live<'a> {
+    cli: &'a Cli,
+}
+
+impl Backend for Live<'_> {
+    async fn health(&self) -> anyhow::Result<serde_json::Value> {
+        fetch_health().await
+    }
+
+    async fn config(&self) -> anyhow::Result<ServerConfig> {
+        fetch_config().await
+    }
+
+    async fn log_in(&self, supabase_url: String) -> anyhow::Result<String> {
+        let timeout = tokio::time::Duration::from_secs(self.cli.auth_timeout);
+        authenticate(supabase_url, self.cli.provider, timeout).await
+    }
+
+    async fn current_user(&self, token: &str) -> anyhow::Result<String> {
+        fetch_current_user(token).await
+    }
+}
+
+/// Checks each piece a voting session needs, printing a pass or fail for each with a hint
+/// on how to fix failures, and fails if any did. Logging in opens the browser, so it's
+/// only checked when asked.
+pub async fn run(cli: &Cli, login: bool) -> anyhow::Result<()> {
+    println!("{}", "🩺 CHECKING YOUR SETUP".bright_yellow().bold());
+    println!();
+    let supabase_url = env::var("SUPABASE_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty());
+    let healthy = check(&Live { cli }, supabase_url, login).await;
+
+    println!();
+    anyhow::ensure!(healthy, "Some checks failed, see the hints above.");
+    println!("{}", "✅ Everything looks good!".green().bold());
+    Ok(())
+}
+
+/// Runs every check against `backend`, returning whether they all passed.
+async fn check(backend: &impl Backend, supabase_url: Option<String>, login: bool) -> bool {
+    let mut healthy = true;
+
+    let reachable = match backend.health().await {
+        Ok(health) => {
+            healthy &= report("Server", Ok(format!("reachable at {BACKEND_URL}")));
+            let database = health["database"].as_str().unwrap_or("unknown");
+            healthy &= report(
+                "Database",
+                if database == "connected" {
+                    Ok("connected".into())
+                } else {
+                    Err((
+                        format!("the server reports it as {database}"),
+                        "The server can't reach its database; check its DATABASE_URL.",
+                    ))
+                },
+            );
+            true
+        }
+        Err(e) => {
+            healthy &= report(
+                "Server",
+                Err((
+                    format!("no answer from {BACKEND_URL} ({e})"),
+                    "Start the server, or check that it's listening on that address.",
+                )),
+            );
+            false
+        }
+    };
+
+    if reachable {
+        healthy &= report(
+            "Config",
+            match backend.config().await {
+                Ok(config) => Ok(format!(
+                    "fetched, accepting {} votes",
+                    config.vote_types.join("/")
+                )),
+                Err(e) => Err((
+                    e.to_string(),
+                    "The server answered but /config failed; it may be out of date.",
+                )),
+            },
+        );
+    } else {
+        skipped("Config", "needs the server");
+    }
+
+    healthy &= report(
+        "Supabase URL",
+        match &supabase_url {
+            Some(url) => Ok(url.clone()),
+            None => Err((
+                "SUPABASE_URL isn't set".into(),
+                "Set SUPABASE_URL in your environment or in a .env file.",
+            )),
+        },
+    );
+
+    match supabase_url {
+        Some(url) if login => {
+            let result = match backend.log_in(url).await {
+                Ok(token) => match backend.current_user(&token).await {
+                    Ok(user_id) => Ok(format!("logged in as {user_id}")),
+                    Err(e) if e.is::<Unauthorized>() => Err((
+                        "the server rejected the login token".into(),
+                        "The client and server may be set up for different Supabase projects.",
+                    )),
+                    Err(e) => Err((
+                        e.to_string(),
+                        "Logged in, but the server couldn't check the token; it may be out of date.",
+                    )),
+                },
+                Err(e) => Err((
+                    e.to_string(),
+                    "Finish logging in within --auth-timeout, or try another --provider.",
+                )),
+            };
+            println!();
+            healthy &= report("Login", result);
+        }
+        _ => skipped("Login", "run `doctor --login` to check"),
+    }
+
+    healthy
+}
+
+/// Prints one check's outcome: what was found, or what went wrong and how to fix it.
+fn report(name: &str, result: Result<String, (String, &str)>) -> bool {
+    match result {
+        Ok(detail) => {
+            println!("{} {:<14} {}", "✓".green(), name, detail.bright_black());
+            true
+        }
+        Err((problem, hint)) => {
+            println!("{} {:<14} {}", "✗".red(), name, problem.red());
+            println!("  {:<14} {}", "", format!("→ {hint}").yellow());
+            false
+        }
+    }
+}
+
+fn skipped(name: &str, why: &str) {
+    println!(
+        "{} {:<14} {}",
+        "–".bright_black(),
+        name,
+        format!("skipped ({why})").bright_black()
+    );
+}
+
+async fn fetch_health() -> anyhow::Result<serde_json::Value> {
+    let response = reqwest::get(format!("{}/health", BACKEND_URL)).await?;
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP {}", response.status());
+    }
+    Ok(response.json().await?)
+}
+
+/// `GET /auth/me`: whose token this is, as far as the server is concerned.
+async fn fetch_current_user(token: &str) -> anyhow::Result<String> {
+    let response = reqwest::Client::new()
+        .get(format!("{}/auth/me", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    let me: serde_json::Value = response.json().await?;
+    me["user_id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("/auth/me didn't say who the token belongs to"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A backend whose every answer can be made to fail, healthy by default.
+    struct FakeBackend {
+        database: &'static str,
+        down: bool,
+        config_fails: bool,
+        login_fails: bool,
+        token_rejected: bool,
+        logged_in: Cell<bool>,
+    }
+
+    impl Default for FakeBackend {
+        fn default() -> Self {
+            Self {
+                database: "connected",
+                down: false,
+                config_fails: false,
+                login_fails: false,
+                token_rejected: false,
+                logged_in: Cell::new(false),
+            }
+        }
+    }
+
+    impl Backend for FakeBackend {
+        async fn health(&self) -> anyhow::Result<serde_json::Value> {
+            anyhow::ensure!(!self.down, "connection refused");
+            Ok(serde_json::json!({ "database": self.database }))
+        }
+
+        async fn config(&self) -> anyhow::Result<ServerConfig> {
+            assert!(!self.down, "config was fetched from a server that's down");
+            anyhow::ensure!(!self.config_fails, "API error (404)");
+            Ok(ServerConfig::default())
+        }
+
+        async fn log_in(&self, supabase_url: String) -> anyhow::Result<String> {
+            assert_eq!(supabase_url, SUPABASE_URL);
+            self.logged_in.set(true);
+            anyhow::ensure!(!self.login_fails, "timed out");
+            Ok("token".into())
+        }
+
+        async fn current_user(&self, token: &str) -> anyhow::Result<String> {
+            assert_eq!(token, "token");
+            if self.token_rejected {
+                return Err(Unauthorized.into());
+            }
+            Ok("user-1".into())
+        }
+    }
+
+    const SUPABASE_URL: &str = "https://example.supabase.co";
+
+    async fn passes(backend: &FakeBackend) -> bool {
+        check(backend, Some(SUPABASE_URL.into()), true).await
+    }
+
+    #[tokio::test]
+    async fn a_healthy_setup_passes_every_check() {
+        let backend = FakeBackend::default();
+        assert!(passes(&backend).await);
+        assert!(backend.logged_in.get());
+    }
+
+    #[tokio::test]
+    async fn logging_in_is_only_checked_when_asked() {
+        let backend = FakeBackend {
+            login_fails: true,
+            ..FakeBackend::default()
+        };
+        assert!(check(&backend, Some(SUPABASE_URL.into()), false).await);
+        assert!(!backend.logged_in.get());
+    }
+
+    #[tokio::test]
+    async fn each_failure_fails_the_checks() {
+        let unhealthy = [
+            FakeBackend {
+                down: true,
+                ..FakeBackend::default()
+            },
+            FakeBackend {
+                database: "disconnected",
+                ..FakeBackend::default()
+            },
+            FakeBackend {
+                config_fails: true,
+                ..FakeBackend::default()
+            },
+            FakeBackend {
+                login_fails: true,
+                ..FakeBackend::default()
+            },
+            FakeBackend {
+                token_rejected: true,
+                ..FakeBackend::default()
+            },
+        ];
+        for backend in &unhealthy {
+            assert!(!passes(backend).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_missing_supabase_url_fails_and_skips_the_login() {
+        let backend = FakeBackend::default();
+        assert!(!check(&backend, None, true).await);
+        assert!(!backend.logged_in.get());
+    }
+}
@@ -0,0 +1,615 @@
+use crossterm::terminal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What the user asked for at the voting prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Yes,
+    No,
+    Skip,
+    Maybe,
+    Quit,
+    Results,
+    Undo,
+}
+
+impl Action {
+    /// The server-side vote type, for actions that are votes.
+    pub fn vote_type(self) -> Option<&'static str> {
+        match self {
+            Action::Yes => Some("yes"),
+            Action::No => Some("no"),
+            Action::Skip => Some("skip"),
+            Action::Maybe => Some("maybe"),
+            Action::Quit | Action::Results | Action::Undo => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Yes => "Yes",
+            Action::No => "No",
+            Action::Skip => "Skip",
+            Action::Maybe => "Maybe",
+            Action::Quit => "Quit",
+            Action::Results => "Results",
+            Action::Undo => "Undo",
+        }
+    }
+}
+
+/// Every word accepted at the prompt. Any unambiguous prefix of these works too.
+const WORDS: &[(&str, Action)] = &[
+    ("y", Action::Yes),
+    ("yes", Action::Yes),
+    ("yeah", Action::Yes),
+    ("yep", Action::Yes),
+    ("sure", Action::Yes),
+    ("+", Action::Yes),
+    ("n", Action::No),
+    ("no", Action::No),
+    ("nope", Action::No),
+    ("nah", Action::No),
+    ("-", Action::No),
+    ("s", Action::Skip),
+    ("skip", Action::Skip),
+    ("pass", Action::Skip),
+    ("next", Action::Skip),
+    ("m", Action::Maybe),
+    ("maybe", Action::Maybe),
+    ("unsure", Action::Maybe),
+    ("?", Action::Maybe),
+    ("q", Action::Quit),
+    ("quit", Action::Quit),
+    ("exit", Action::Quit),
+    ("r", Action::Results),
+    ("results", Action::Results),
+    ("stats", Action::Results),
+    ("u", Action::Undo),
+    ("undo", Action::Undo),
+];
+
+const HINT: &str = "Try y(es), n(o), m(aybe), s(kip), q(uit) or r(esults).";
+
+/// Extra keys for prompt actions, loaded from a JSON file such as
+/// `{"yes": ["w"], "no": ["s"], "skip": ["d"]}`. They take precedence over the built-in words.
+#[derive(Debug, Default)]
+pub struct Keybindings {
+    custom: Vec<(String, Action)>,
+}
+
+impl Keybindings {
+    /// Where the bindings are looked for when no path is given.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("slaughter_vote").join("keys.json"))
+    }
+
+    /// Reads bindings from `path`, or the default location. A missing default file
+    /// means the built-in keys; a broken or conflicting file is an error.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Self::default());
+            }
+            Err(e) => anyhow::bail!("Couldn't read key bindings from {}: {e}", path.display()),
+        };
+        let file: HashMap<Action, Vec<String>> = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid key bindings in {}: {e}", path.display()))?;
+
+        let mut custom: Vec<(String, Action)> = Vec::new();
+        for (action, keys) in file {
+            for key in keys {
+                let key = key.trim().to_lowercase();
+                if key.is_empty() {
+                    anyhow::bail!("Empty key bound to {} in {}", action.name(), path.display());
+                }
+                match custom.iter().find(|(bound, _)| *bound == key) {
+                    Some((_, other)) if *other != action => anyhow::bail!(
+                        "\"{key}\" is bound to both {} and {} in {}",
+                        other.name(),
+                        action.name(),
+                        path.display()
+                    ),
+                    Some(_) => {}
+                    None => custom.push((key, action)),
+                }
+            }
+        }
+        Ok(Self { custom })
+    }
+
+    /// The key shown for an action: its first custom binding, or the built-in one.
+    pub fn key(&self, action: Action) -> &str {
+        self.custom
+            .iter()
+            .find(|(_, bound)| *bound == action)
+            .map(|(key, _)| key.as_str())
+            .or_else(|| {
+                WORDS
+                    .iter()
+                    .find(|(_, bound)| *bound == action)
+                    .map(|(word, _)| *word)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Prompt label for an action: "[Y]es", or "Yes [w]" when the key isn't its initial.
+    pub fn label(&self, action: Action) -> String {
+        let name = action.name();
+        let key = self.key(action);
+        if key.chars().count() == 1 && name.to_lowercase().starts_with(key) {
+            let (first, rest) = name.split_at(key.len());
+            format!("[{first}]{rest}")
+        } else {
+            format!("{name} [{key}]")
+        }
+    }
+
+    fn hint(&self) -> String {
+        if self.custom.is_empty() {
+            return HINT.to_string();
+        }
+        let options: Vec<String> = [
+            Action::Yes,
+            Action::No,
+            Action::Maybe,
+            Action::Skip,
+            Action::Quit,
+            Action::Results,
+        ]
+        .into_iter()
+        .map(|action| format!("{} ({})", self.key(action), action.name().to_lowercase()))
+        .collect();
+        format!("Try {}.", options.join(", "))
+    }
+
+    /// Turns raw prompt input into an action, or a message explaining why it couldn't.
+    pub fn parse(&self, input: &str) -> Result<Action, String> {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return Err(format!("Please choose an option. {}", self.hint()));
+        }
+
+        if let Some((_, action)) = self.custom.iter().find(|(key, _)| *key == input) {
+            return Ok(*action);
+        }
+        if let Some((_, action)) = WORDS.iter().find(|(word, _)| *word == input) {
+            return Ok(*action);
+        }
+
+        let mut candidates: Vec<(&str, Action)> = WORDS
+            .iter()
+            .filter(|(word, _)| word.starts_with(&input))
+            .copied()
+            .collect();
+        candidates.dedup_by_key(|(_, action)| *action);
+
+        match candidates.as_slice() {
+            [] => Err(format!("Didn't understand \"{input}\". {}", self.hint())),
+            [(_, action)] => Ok(*action),
+            _ => {
+                let words: Vec<&str> = candidates.iter().map(|(word, _)| *word).collect();
+                Err(format!(
+                    "\"{input}\" is ambiguous (could be {}). {}",
+                    words.join(" or "),
+                    self.hint()
+                ))
+            }
+        }
+    }
+}
+
+/// What's been typed on stdin, read by a background thread as it arrives, so waiting on
+/// it can time out without a stray read swallowing the next answer. It's read as it comes
+/// rather than by line, so a single key can be taken in raw mode too.
+struct Typed {
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    /// Received but not handed out yet
+    buffered: Vec<u8>,
+    /// Input has ended, so nothing more will arrive
+    ended: bool,
+}
+
+impl Typed {
+    fn new(chunks: Receiver<io::Result<Vec<u8>>>) -> Self {
+        Self {
+            chunks,
+            buffered: Vec::new(),
+            ended: false,
+        }
+    }
+
+    /// Waits up to `timeout` (forever with `None`) for more input, returning whether
+    /// any came or the input ended.
+    fn receive(&mut self, timeout: Option<Duration>) -> io::Result<bool> {
+        let received = match timeout {
+            Some(timeout) => self.chunks.recv_timeout(timeout),
+            None => self
+                .chunks
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(Ok(chunk)) if !chunk.is_empty() => self.buffered.extend(chunk),
+            Ok(Err(e)) => {
+                self.ended = true;
+                return Err(e);
+            }
+            // The reader stops at end of input
+            Ok(Ok(_)) | Err(RecvTimeoutError::Disconnected) => self.ended = true,
+            Err(RecvTimeoutError::Timeout) => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The next line, newline included, or `None` if `timeout` passes first. At end of
+    /// input, whatever is left, which is empty once everything has been read.
+    fn line(&mut self, timeout: Option<Duration>) -> io::Result<Option<String>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let end = match self.buffered.iter().position(|&b| b == b'\n') {
+                Some(newline) => newline + 1,
+                None if self.ended => self.buffered.len(),
+                None => {
+                    let left = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+                    if !self.receive(left)? {
+                        return Ok(None);
+                    }
+                    continue;
+                }
+            };
+            let line: Vec<u8> = self.buffered.drain(..end).collect();
+            return Ok(Some(String::from_utf8_lossy(&line).into_owned()));
+        }
+    }
+
+    /// The next character typed, or `None` at end of input.
+    fn key(&mut self) -> io::Result<Option<char>> {
+        loop {
+            let valid = match std::str::from_utf8(&self.buffered) {
+                Ok(text) => text,
+                Err(e) if e.valid_up_to() > 0 || e.error_len().is_none() => {
+                    std::str::from_utf8(&self.buffered[..e.valid_up_to()]).unwrap()
+                }
+                // Not a character at all
+                Err(_) => {
+                    self.buffered.remove(0);
+                    return Ok(Some(char::REPLACEMENT_CHARACTER));
+                }
+            };
+            // Otherwise the rest of a character hasn't arrived yet
+            if let Some(key) = valid.chars().next() {
+                self.buffered.drain(..key.len_utf8());
+                return Ok(Some(key));
+            }
+            if self.ended {
+                return Ok(None);
+            }
+            self.receive(None)?;
+        }
+    }
+
+    /// Drops the whole lines already typed, keeping a line still being typed. Returns how
+    /// many were dropped.
+    fn discard_waiting(&mut self) -> usize {
+        while !self.ended && matches!(self.receive(Some(Duration::ZERO)), Ok(true)) {}
+        let Some(last) = self.buffered.iter().rposition(|&b| b == b'\n') else {
+            return 0;
+        };
+        self.buffered.drain(..=last).filter(|&b| b == b'\n').count()
+    }
+}
+
+fn typed() -> &'static Mutex<Typed> {
+    static TYPED: OnceLock<Mutex<Typed>> = OnceLock::new();
+    TYPED.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut stdin = io::stdin();
+            let mut buf = [0; 1024];
+            loop {
+                let result = stdin.read(&mut buf).map(|n| buf[..n].to_vec());
+                let done = matches!(&result, Ok(chunk) if chunk.is_empty()) || result.is_err();
+                if tx.send(result).is_err() || done {
+                    break;
+                }
+            }
+        });
+        Mutex::new(Typed::new(rx))
+    })
+}
+
+/// Drops lines typed while the client was busy, so a repeated key press can't answer
+/// the next prompt. Returns how many were dropped.
+pub fn discard_typed_ahead() -> usize {
+    typed().lock().unwrap().discard_waiting()
+}
+
+/// Reads one line from stdin. Returns an empty string at end of input.
+pub fn read_line() -> io::Result<String> {
+    Ok(read_line_timeout(None)?.unwrap_or_default())
+}
+
+/// Like [`read_line`], but gives up with `None` once `timeout` has passed.
+pub fn read_line_timeout(timeout: Option<Duration>) -> io::Result<Option<String>> {
+    typed().lock().unwrap().line(timeout)
+}
+
+/// Reads a single key press, without waiting for Enter when stdin is a terminal (which
+/// then won't echo it). Otherwise it's the first character of the next line, or Enter
+/// for an empty one. Returns `None` at end of input.
+pub fn read_key() -> io::Result<Option<char>> {
+    let mut typed = typed().lock().unwrap();
+    if !io::stdin().is_terminal() {
+        let line = typed.line(None)?.unwrap_or_default();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(line.trim().chars().next().unwrap_or('\n')));
+    }
+
+    terminal::enable_raw_mode()?;
+    let key = typed.key();
+    terminal::disable_raw_mode()?;
+    key
+}
+
+/// Like [`read_line_timeout`], but calls `tick` with the whole seconds left at the start
+/// and then once a second, so a countdown can be shown while waiting.
+pub fn read_line_countdown(limit: Duration, tick: impl FnMut(u64)) -> io::Result<Option<String>> {
+    countdown(limit, tick, Instant::now, read_line_timeout)
+}
+
+/// [`read_line_countdown`] reading through `read` and telling the time by `now`, so it can
+/// be run against scripted input and a clock that doesn't have to really pass.
+fn countdown(
+    limit: Duration,
+    mut tick: impl FnMut(u64),
+    now: impl Fn() -> Instant,
+    mut read: impl FnMut(Option<Duration>) -> io::Result<Option<String>>,
+) -> io::Result<Option<String>> {
+    let deadline = now() + limit;
+    loop {
+        let left = deadline.saturating_duration_since(now());
+        if left.is_zero() {
+            return Ok(None);
+        }
+        let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+        tick(seconds);
+
+        // Wake up again as the count reaches the next whole second
+        let wait = left - Duration::from_secs(seconds - 1);
+        if let Some(line) = read(Some(wait))? {
+            return Ok(Some(line));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(bindings: &[(&str, Action)]) -> Keybindings {
+        Keybindings {
+            custom: bindings
+                .iter()
+                .map(|(key, action)| (key.to_string(), *action))
+                .collect(),
+        }
+    }
+
+    /// Writes `content` to a fresh file and loads bindings from it.
+    fn load(name: &str, content: &str) -> anyhow::Result<Keybindings> {
+        let path = std::env::temp_dir().join(format!(
+            "slaughter_vote_keys_{}_{name}.json",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+        let keys = Keybindings::load(Some(&path));
+        fs::remove_file(&path).unwrap();
+        keys
+    }
+
+    #[test]
+    fn every_word_parses_to_its_action() {
+        let keys = Keybindings::default();
+        for (word, action) in WORDS {
+            assert_eq!(keys.parse(word), Ok(*action), "{word}");
+            assert_eq!(
+                keys.parse(&format!("  {} \n", word.to_uppercase())),
+                Ok(*action)
+            );
+        }
+    }
+
+    #[test]
+    fn words_are_unique() {
+        for (i, (word, _)) in WORDS.iter().enumerate() {
+            assert!(
+                WORDS[i + 1..].iter().all(|(other, _)| other != word),
+                "{word} is listed twice"
+            );
+        }
+    }
+
+    #[test]
+    fn unique_prefixes_are_accepted() {
+        let keys = Keybindings::default();
+        assert_eq!(keys.parse("ye"), Ok(Action::Yes));
+        assert_eq!(keys.parse("nop"), Ok(Action::No));
+        assert_eq!(keys.parse("ma"), Ok(Action::Maybe));
+        assert_eq!(keys.parse("pa"), Ok(Action::Skip));
+        assert_eq!(keys.parse("ex"), Ok(Action::Quit));
+        assert_eq!(keys.parse("st"), Ok(Action::Results));
+    }
+
+    #[test]
+    fn ambiguous_prefixes_name_the_candidates() {
+        let err = Keybindings::default().parse("un").unwrap_err();
+        assert!(err.contains("ambiguous"), "{err}");
+        assert!(err.contains("unsure") && err.contains("undo"), "{err}");
+    }
+
+    #[test]
+    fn unknown_and_empty_input_are_rejected_with_a_hint() {
+        let keys = Keybindings::default();
+        for input in ["", "   ", "yess", "xyz", "!"] {
+            let err = keys.parse(input).unwrap_err();
+            assert!(err.ends_with(HINT), "{input:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn custom_bindings_win_over_built_in_words() {
+        let keys = custom(&[("w", Action::Yes), ("n", Action::Skip)]);
+        assert_eq!(keys.parse("W"), Ok(Action::Yes));
+        assert_eq!(keys.parse("n"), Ok(Action::Skip));
+        // Built-in words still work
+        assert_eq!(keys.parse("no"), Ok(Action::No));
+        assert_eq!(keys.key(Action::Yes), "w");
+        assert_eq!(keys.label(Action::Yes), "Yes [w]");
+        assert_eq!(keys.label(Action::No), "[N]o");
+        assert!(keys.parse("xyz").unwrap_err().contains("w (yes)"));
+    }
+
+    #[test]
+    fn a_custom_undo_key_undoes() {
+        let keys = custom(&[("z", Action::Undo)]);
+        assert_eq!(keys.parse("Z"), Ok(Action::Undo));
+        assert_eq!(keys.parse("undo"), Ok(Action::Undo));
+        assert_eq!(keys.key(Action::Undo), "z");
+    }
+
+    #[test]
+    fn bindings_load_from_a_file() {
+        let keys = load("ok", r#"{"yes": ["W"], "no": [" s "], "skip": ["d", "d"]}"#).unwrap();
+        assert_eq!(keys.parse("w"), Ok(Action::Yes));
+        assert_eq!(keys.parse("s"), Ok(Action::No));
+        assert_eq!(keys.parse("d"), Ok(Action::Skip));
+    }
+
+    #[test]
+    fn broken_binding_files_are_errors() {
+        let conflict = load("conflict", r#"{"yes": ["x"], "no": ["x"]}"#).unwrap_err();
+        assert!(conflict.to_string().contains("bound to both"), "{conflict}");
+        let empty = load("empty", r#"{"yes": [" "]}"#).unwrap_err();
+        assert!(empty.to_string().contains("Empty key"), "{empty}");
+        let unknown = load("unknown", r#"{"jump": ["j"]}"#).unwrap_err();
+        assert!(
+            unknown.to_string().contains("Invalid key bindings"),
+            "{unknown}"
+        );
+        let missing = Keybindings::load(Some(Path::new("/nonexistent/keys.json"))).unwrap_err();
+        assert!(missing.to_string().contains("Couldn't read"), "{missing}");
+    }
+
+    #[test]
+    fn repeated_presses_while_submitting_are_dropped() {
+        let (tx, rx) = mpsc::channel();
+        let mut typed = Typed::new(rx);
+        for _ in 0..3 {
+            tx.send(Ok(b"y\n".to_vec())).unwrap();
+        }
+
+        // The first press answers the prompt...
+        assert_eq!(typed.line(None).unwrap().unwrap(), "y\n");
+        // ...and the ones that came in while its vote was sent go, so only one vote is cast
+        assert_eq!(typed.discard_waiting(), 2);
+        assert_eq!(typed.line(Some(Duration::ZERO)).unwrap(), None);
+
+        // Input typed after that is kept for the next prompt
+        tx.send(Ok(b"n\n".to_vec())).unwrap();
+        assert_eq!(typed.line(None).unwrap().unwrap(), "n\n");
+    }
+
+    #[test]
+    fn lines_are_put_together_from_what_arrives() {
+        let (tx, rx) = mpsc::channel();
+        let mut typed = Typed::new(rx);
+        tx.send(Ok(b"ye".to_vec())).unwrap();
+        assert_eq!(typed.line(Some(Duration::ZERO)).unwrap(), None);
+        tx.send(Ok(b"s\nno\nmay".to_vec())).unwrap();
+        assert_eq!(typed.line(None).unwrap().unwrap(), "yes\n");
+        assert_eq!(typed.line(None).unwrap().unwrap(), "no\n");
+
+        // A line cut off by the end of input still counts, then there's nothing
+        drop(tx);
+        assert_eq!(typed.line(None).unwrap().unwrap(), "may");
+        assert_eq!(typed.line(None).unwrap().unwrap(), "");
+    }
+
+    #[test]
+    fn keys_are_taken_one_character_at_a_time() {
+        let (tx, rx) = mpsc::channel();
+        let mut typed = Typed::new(rx);
+        tx.send(Ok("2é".as_bytes()[..2].to_vec())).unwrap();
+        tx.send(Ok("é".as_bytes()[1..].to_vec())).unwrap();
+        drop(tx);
+        assert_eq!(typed.key().unwrap(), Some('2'));
+        // Split across two reads
+        assert_eq!(typed.key().unwrap(), Some('é'));
+        assert_eq!(typed.key().unwrap(), None);
+    }
+
+    /// Runs a countdown where each read waits out its whole timeout, unless `answers`
+    /// has a line for it. Returns the answer and the counts shown.
+    fn run_countdown(limit: Duration, answers: &[Option<&str>]) -> (Option<String>, Vec<u64>) {
+        let start = Instant::now();
+        let clock = std::cell::Cell::new(start);
+        let mut answers = answers.iter();
+        let mut ticks = Vec::new();
+        let answer = countdown(
+            limit,
+            |left| ticks.push(left),
+            || clock.get(),
+            |timeout| {
+                if let Some(Some(line)) = answers.next() {
+                    return Ok(Some(line.to_string()));
+                }
+                clock.set(clock.get() + timeout.unwrap());
+                Ok(None)
+            },
+        )
+        .unwrap();
+        assert!(clock.get() - start <= limit, "waited past the limit");
+        (answer, ticks)
+    }
+
+    #[test]
+    fn the_countdown_gives_up_when_time_runs_out() {
+        // No answer, so the caller skips the theme
+        let (answer, ticks) = run_countdown(Duration::from_secs(3), &[]);
+        assert_eq!(answer, None);
+        assert_eq!(ticks, [3, 2, 1]);
+
+        // A part second counts as a whole one, then the count goes by whole seconds
+        let (answer, ticks) = run_countdown(Duration::from_millis(2500), &[]);
+        assert_eq!(answer, None);
+        assert_eq!(ticks, [3, 2, 1]);
+    }
+
+    #[test]
+    fn the_countdown_stops_at_an_answer() {
+        let (answer, ticks) = run_countdown(Duration::from_secs(5), &[None, Some("y\n")]);
+        assert_eq!(answer.as_deref(), Some("y\n"));
+        assert_eq!(ticks, [5, 4]);
+    }
+}
@@ -1,19 +1,209 @@
+mod autovote;
+mod display;
+mod doctor;
+mod input;
+mod session;
+mod tutorial;
+
 use axum::{Router, extract::Query, response::Html, routing::get};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::*;
+use input::{Action, Keybindings};
 use serde::{Deserialize, Serialize};
+use session::{PendingVote, Session};
+use std::collections::HashMap;
 use std::env;
-use std::io::{self, Write};
+use std::fmt;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 const BACKEND_URL: &str = "http://localhost:3000";
 const CALLBACK_PORT: u16 = 8080;
 
+// ===== CLI =====
+
+#[derive(Debug, Parser)]
+#[command(about = "Vote on Bevy Jam theme suggestions")]
+struct Cli {
+    /// Ring the terminal bell when you've voted on every theme
+    #[arg(long)]
+    notify: bool,
+
+    /// Seconds to wait for the browser login to complete
+    #[arg(
+        long,
+        env = "AUTH_TIMEOUT",
+        value_name = "SECS",
+        default_value_t = 120,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    auth_timeout: u64,
+
+    /// Account to log in with, as enabled in the Supabase project
+    #[arg(long, env = "AUTH_PROVIDER", value_enum, default_value_t = Provider::Discord)]
+    provider: Provider,
+
+    /// Ask why you're skipping a theme (answering is optional)
+    #[arg(long)]
+    skip_reasons: bool,
+
+    /// Show how others voted on each theme before you vote (off by default, as it can bias you)
+    #[arg(long)]
+    show_tally: bool,
+
+    /// Explain the voting keys before starting, even if you've seen it before
+    /// (it's shown automatically the first time)
+    #[arg(long)]
+    tutorial: bool,
+
+    /// Seconds to answer each theme before it's skipped for you, with a countdown (0 = off)
+    #[arg(long, env = "THEME_TIMER", value_name = "SECS", default_value_t = 0)]
+    theme_timer: u64,
+
+    /// Seconds after each vote during which `u` takes it back (0 = off)
+    #[arg(long, env = "UNDO_WINDOW", value_name = "SECS", default_value_t = 0)]
+    undo_window: u64,
+
+    /// How the server picks the next theme, e.g. "weighted" (defaults to the server's setting)
+    #[arg(long, env = "VOTE_STRATEGY", value_name = "NAME")]
+    strategy: Option<String>,
+
+    /// Results to show per page (defaults to the server's setting)
+    #[arg(
+        long,
+        value_name = "N",
+        global = true,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    top: Option<u64>,
+
+    /// Vote on every remaining theme without asking, following a JSON rules file, e.g.
+    /// {"rules": [{"contains": "horror", "vote": "yes"}], "default": "skip"}.
+    /// Meant for testing how results come out, not for real voting
+    #[arg(long, value_name = "PATH")]
+    auto_vote: Option<PathBuf>,
+
+    /// JSON file mapping prompt actions to keys, e.g. {"yes": ["w"]}
+    /// (defaults to keys.json in the config directory, if present)
+    #[arg(long, env = "KEYBINDINGS", value_name = "PATH")]
+    keys: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// OAuth providers Supabase can log in through.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Provider {
+    Discord,
+    Github,
+    Gitlab,
+    Google,
+}
+
+impl Provider {
+    fn as_str(self) -> &'static str {
+        match self {
+            Provider::Discord => "discord",
+            Provider::Github => "github",
+            Provider::Gitlab => "gitlab",
+            Provider::Google => "google",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Provider::Discord => "Discord",
+            Provider::Github => "GitHub",
+            Provider::Gitlab => "GitLab",
+            Provider::Google => "Google",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum VoteMode {
+    /// Yes or no on one theme at a time
+    Single,
+    /// Pick the better of two themes
+    Bracket,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ResultsFormat {
+    Text,
+    Markdown,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Log in and vote on themes (the default)
+    Vote {
+        /// "bracket" shows two themes at a time and asks which is better
+        #[arg(long, value_enum, default_value_t = VoteMode::Single)]
+        mode: VoteMode,
+        /// Vote on as many themes as you can in this many seconds, then see how it went
+        #[arg(
+            long,
+            value_name = "SECS",
+            conflicts_with = "mode",
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        speed: Option<u64>,
+    },
+    /// Find themes containing a term and show how they're doing
+    Search { term: String },
+    /// Summarize how you have voted so far
+    Mystats,
+    /// Download every vote to a JSON Lines file (admins only)
+    Export { path: PathBuf },
+    /// Download everything the server stores about you to a JSON file
+    ExportMine { path: PathBuf },
+    /// Save the results as an HTML page to share with organizers (admins only)
+    Report { path: PathBuf },
+    /// Go back through the themes you skipped and vote on them
+    ReviewSkips,
+    /// Check that the server, its database and your login all work
+    Doctor {
+        /// Also log in, to check that the server accepts the token
+        #[arg(long)]
+        login: bool,
+    },
+    /// Show the current results without logging in
+    Results {
+        /// Keep the results on screen, redrawing them when they change
+        #[arg(long)]
+        watch: bool,
+        /// Seconds between refreshes in watch mode
+        #[arg(
+            long,
+            value_name = "SECS",
+            default_value_t = 10,
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        interval: u64,
+        /// Output style; markdown prints every result as a table ready to paste
+        #[arg(long, value_enum, default_value_t = ResultsFormat::Text, conflicts_with = "watch")]
+        format: ResultsFormat,
+    },
+    /// Vote on one theme, given its id or part of its text
+    VoteOn { target: String },
+    /// Put two themes side by side, with how voters who saw both split between them (admins only)
+    Compare { a: i64, b: i64 },
+}
+
 // ===== Models =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Theme {
     id: i64,
     content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,12 +211,178 @@ struct ThemeResponse {
     theme: Option<Theme>,
     total: i64,
     seen: i64,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Eligibility {
+    can_vote: bool,
+    reason: Option<String>,
+}
+
+/// Two themes to pick between, or `None` once there are no pairs left to compare.
+#[derive(Debug, Deserialize)]
+struct PairResponse {
+    pair: Option<[Theme; 2]>,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct PairVoteRequest {
+    winner_id: i64,
+    loser_id: i64,
 }
 
 #[derive(Debug, Serialize)]
 struct VoteRequest {
     theme_id: i64,
     vote_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+/// Public settings fetched from the server's `/config` at startup.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct ServerConfig {
+    vote_types: Vec<String>,
+    page_size: usize,
+    allow_revote: bool,
+    results_hidden: bool,
+    strategies: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            vote_types: vec!["yes".into(), "no".into(), "skip".into()],
+            page_size: 10,
+            allow_revote: true,
+            results_hidden: false,
+            strategies: vec!["random".into()],
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Page size for results, letting `--top` override the server default.
+    fn results_page_size(&self, cli: &Cli) -> usize {
+        cli.top.map_or(self.page_size, |top| top as usize).max(1)
+    }
+
+    /// The `--strategy` to ask for, as long as the server offers it.
+    fn strategy<'a>(&self, cli: &'a Cli) -> anyhow::Result<Option<&'a str>> {
+        match cli.strategy.as_deref() {
+            Some(strategy) if !self.strategies.iter().any(|s| s == strategy) => anyhow::bail!(
+                "Unknown strategy \"{}\" (available: {})",
+                strategy,
+                self.strategies.join(", ")
+            ),
+            strategy => Ok(strategy),
+        }
+    }
+
+    fn allows(&self, vote_type: &str) -> bool {
+        self.vote_types.iter().any(|t| t == vote_type)
+    }
+
+    /// Prompt line listing only the options the server accepts.
+    fn vote_prompt(&self, keys: &Keybindings) -> String {
+        let mut options: Vec<String> = [Action::Yes, Action::No, Action::Maybe, Action::Skip]
+            .into_iter()
+            .filter(|action| action.vote_type().is_some_and(|t| self.allows(t)))
+            .map(|action| keys.label(action))
+            .collect();
+        options.extend([Action::Quit, Action::Results].map(|action| keys.label(action)));
+        format!("Vote: {}", options.join("  "))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FeaturedTheme {
+    theme_id: i64,
+    content: String,
+    /// Left out while results are hidden
+    yes_votes: Option<i64>,
+    no_votes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MyVote {
+    theme_id: i64,
+    content: String,
+    vote_type: String,
+}
+
+/// How many of each kind of vote the user has cast.
+#[derive(Debug, Default, PartialEq)]
+struct VoteCounts {
+    yes: usize,
+    maybe: usize,
+    no: usize,
+    skip: usize,
+}
+
+impl VoteCounts {
+    fn of(votes: &[MyVote]) -> Self {
+        let mut counts = Self::default();
+        for vote in votes {
+            match vote.vote_type.as_str() {
+                "yes" => counts.yes += 1,
+                "maybe" => counts.maybe += 1,
+                "no" => counts.no += 1,
+                "skip" => counts.skip += 1,
+                _ => {}
+            }
+        }
+        counts
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ThemeTally {
+    content: String,
+    yes_votes: i64,
+    no_votes: i64,
+    skip_votes: i64,
+    #[serde(default)]
+    maybe_votes: i64,
+    total_votes: i64,
+}
+
+/// Two themes' tallies, plus how users who voted on both split between them.
+#[derive(Debug, Deserialize)]
+struct Comparison {
+    a: ThemeTally,
+    b: ThemeTally,
+    voted_both: i64,
+    preferred_a: i64,
+    preferred_b: i64,
+    same: i64,
+    /// Skipped one of the two; older servers don't send it
+    #[serde(default)]
+    no_opinion: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct VoterRank {
+    voters: i64,
+    ahead_of_percent: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemainingByTag {
+    tag: Option<String>,
+    remaining: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Progress {
+    seen: i64,
+    total: i64,
+    percent: f64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,17 +396,69 @@ struct CallbackParams {
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
-    let supabase_url = env::var("SUPABASE_URL").expect("DATABASE_URL must be set");
-    println!("{}", "=".repeat(60).bright_cyan());
-    println!(
-        "{}",
-        "    🎮 BEVY JAM THEME VOTING 🎮".bright_yellow().bold()
+    let cli = Cli::parse();
+
+    // Markdown output is meant to be piped or pasted as is
+    let markdown = matches!(
+        cli.command,
+        Some(Command::Results {
+            format: ResultsFormat::Markdown,
+            ..
+        })
     );
-    println!("{}", "=".repeat(60).bright_cyan());
-    println!();
+    if !markdown {
+        println!("{}", "=".repeat(60).bright_cyan());
+        println!(
+            "{}",
+            "    🎮 BEVY JAM THEME VOTING 🎮".bright_yellow().bold()
+        );
+        println!("{}", "=".repeat(60).bright_cyan());
+        println!();
+    }
+
+    let keys = Keybindings::load(cli.keys.as_deref())?;
+    let auto_vote = cli
+        .auto_vote
+        .as_deref()
+        .map(autovote::Rules::load)
+        .transpose()?;
+
+    // Resolved before logging in so a bad id or search fails fast
+    let picked = match &cli.command {
+        Some(Command::Search { term }) => return search_themes(term).await,
+        Some(Command::Doctor { login }) => return doctor::run(&cli, *login).await,
+        Some(Command::Results {
+            watch,
+            interval,
+            format,
+        }) => {
+            let config = load_config().await;
+            let page_size = config.results_page_size(&cli);
+            if config.results_hidden {
+                print_results_hidden();
+                return Ok(());
+            }
+            if let ResultsFormat::Markdown = format {
+                return print_markdown_results(page_size).await;
+            }
+            if *watch {
+                return watch_results(page_size, Duration::from_secs(*interval)).await;
+            }
+            return show_results(page_size).await;
+        }
+        Some(Command::VoteOn { target }) => match pick_theme(target).await? {
+            Some(theme) => Some(theme),
+            None => return Ok(()),
+        },
+        _ => None,
+    };
 
     // Get auth token
-    let token = match authenticate(supabase_url).await {
+    let Ok(supabase_url) = env::var("SUPABASE_URL") else {
+        anyhow::bail!("SUPABASE_URL must be set (run `doctor`)");
+    };
+    let auth_timeout = tokio::time::Duration::from_secs(cli.auth_timeout);
+    let token = match authenticate(supabase_url.clone(), cli.provider, auth_timeout).await {
         Ok(t) => t,
         Err(e) => {
             eprintln!("{} {}", "❌ Authentication failed:".red().bold(), e);
@@ -61,15 +469,212 @@ async fn main() -> anyhow::Result<()> {
     println!("{}", "✅ Authentication successful!".green().bold());
     println!();
 
-    // Start voting loop
-    voting_loop(&token).await?;
+    if let Some(theme) = picked {
+        let config = load_config().await;
+        return vote_on(&theme, &token, &cli, &config, &keys).await;
+    }
+
+    let mut auth = Auth {
+        supabase_url,
+        provider: cli.provider,
+        timeout: auth_timeout,
+        token,
+    };
+    match cli.command {
+        Some(Command::Mystats) => show_my_stats(&mut auth).await?,
+        Some(Command::Export { path }) => export_votes(&mut auth, &path).await?,
+        Some(Command::ExportMine { path }) => export_my_data(&mut auth, &path).await?,
+        Some(Command::Report { path }) => save_report(&auth.token, &path).await?,
+        Some(Command::Compare { a, b }) => compare_themes(a, b, &auth.token).await?,
+        Some(Command::ReviewSkips) => {
+            let config = load_config().await;
+            review_skips(&mut auth, &config, &keys).await?
+        }
+        // Start voting loop
+        _ => {
+            let config = load_config().await;
+            // Older servers don't offer this check, so any failure just carries on
+            if let Ok(eligibility) = auth
+                .retry(|token| async move { fetch_eligibility(&token).await })
+                .await
+                && !eligibility.can_vote
+            {
+                let reason = eligibility
+                    .reason
+                    .unwrap_or_else(|| "You can't vote right now.".to_string());
+                println!("{}", format!("🔒 {reason}").yellow().bold());
+                return Ok(());
+            }
+            if let Some(Command::Vote {
+                mode: VoteMode::Bracket,
+                ..
+            }) = cli.command
+            {
+                return bracket_loop(&mut auth).await;
+            }
+            let (mut session, resume_theme) = resume_session(&mut auth).await?;
+            if let Some(Command::Vote {
+                speed: Some(seconds),
+                ..
+            }) = cli.command
+            {
+                let round = Duration::from_secs(seconds);
+                return speed_round(&mut auth, &cli, &config, &keys, &mut session, round).await;
+            }
+            if let Some(rules) = auto_vote {
+                rules.check(&config)?;
+                return auto_vote_all(&mut auth, &cli, &config, &mut session, &rules).await;
+            }
+            if tutorial::should_show(cli.tutorial) {
+                tutorial::show(&config, &keys)?;
+            }
+            offer_featured_theme(&auth.token, &config).await?;
+            voting_loop(&mut auth, &cli, &config, &keys, &mut session, resume_theme).await?
+        }
+    }
 
     Ok(())
 }
 
 // ===== Authentication =====
 
-async fn authenticate(supabase_url: String) -> anyhow::Result<String> {
+/// Logins attempted in a row for one request before giving up on it.
+const MAX_REAUTH_ATTEMPTS: u32 = 2;
+
+/// The server rejected our token, most likely because it expired.
+#[derive(Debug)]
+struct Unauthorized;
+
+impl fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the server rejected the login token")
+    }
+}
+
+impl std::error::Error for Unauthorized {}
+
+/// The server already has a vote from us on this theme and won't take another.
+#[derive(Debug)]
+struct AlreadyVoted;
+
+impl fmt::Display for AlreadyVoted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "you've already voted on this theme, and votes can't be changed"
+        )
+    }
+}
+
+impl std::error::Error for AlreadyVoted {}
+
+/// The server won't take another change to this vote just yet.
+#[derive(Debug)]
+struct ChangedTooSoon;
+
+impl fmt::Display for ChangedTooSoon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "this vote was changed moments ago, try again in a few seconds"
+        )
+    }
+}
+
+impl std::error::Error for ChangedTooSoon {}
+
+/// The server turned a vote down for good, e.g. because the theme is gone or voting closed.
+#[derive(Debug)]
+struct VoteRejected(String);
+
+impl fmt::Display for VoteRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for VoteRejected {}
+
+/// The current access token, plus what's needed to log in again once it expires.
+struct Auth {
+    supabase_url: String,
+    provider: Provider,
+    timeout: tokio::time::Duration,
+    token: String,
+}
+
+impl Auth {
+    /// The signed-in user's id, read from the token's `sub` claim. The server checks the
+    /// signature; this only tells accounts apart.
+    fn user_id(&self) -> Option<String> {
+        use base64::Engine;
+        let payload = self.token.split('.').nth(1)?;
+        let claims = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(payload.trim_end_matches('='))
+            .ok()?;
+        let claims: serde_json::Value = serde_json::from_slice(&claims).ok()?;
+        claims.get("sub")?.as_str().map(String::from)
+    }
+
+    /// Runs `request` with the current token, logging in again and retrying if it's rejected.
+    async fn retry<T, F, Fut>(&mut self, request: F) -> anyhow::Result<T>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let (supabase_url, provider, timeout) =
+            (self.supabase_url.clone(), self.provider, self.timeout);
+        retry_with_login(&mut self.token, request, || {
+            authenticate(supabase_url.clone(), provider, timeout)
+        })
+        .await
+    }
+}
+
+/// Runs `request` with `token`, replacing the token through `login` and retrying while
+/// it's rejected, up to `MAX_REAUTH_ATTEMPTS` times.
+async fn retry_with_login<T, F, Fut, L, LFut>(
+    token: &mut String,
+    mut request: F,
+    mut login: L,
+) -> anyhow::Result<T>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+    L: FnMut() -> LFut,
+    LFut: Future<Output = anyhow::Result<String>>,
+{
+    let mut attempts = 0;
+    loop {
+        match request(token.clone()).await {
+            Err(e) if e.is::<Unauthorized>() && attempts < MAX_REAUTH_ATTEMPTS => {
+                attempts += 1;
+                println!();
+                println!("{}", "🔑 Your login expired, please log in again.".yellow());
+                *token = login().await?;
+                println!("{}", "✅ Logged back in, carrying on.".green());
+                println!();
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Supabase's login page for `provider`, sending the user back to our callback server.
+fn auth_url(supabase_url: &str, provider: Provider) -> String {
+    format!(
+        "{}/auth/v1/authorize?provider={}&redirect_to=http://localhost:{}/callback",
+        supabase_url,
+        provider.as_str(),
+        CALLBACK_PORT
+    )
+}
+
+async fn authenticate(
+    supabase_url: String,
+    provider: Provider,
+    timeout: tokio::time::Duration,
+) -> anyhow::Result<String> {
     println!("Starting authentication...");
     println!();
 
@@ -92,14 +697,13 @@ async fn authenticate(supabase_url: String) -> anyhow::Result<String> {
 
     let server_handle = tokio::spawn(async move { axum::serve(listener, app).await });
 
-    // Build auth URL
-    let auth_url = format!(
-        "{}/auth/v1/authorize?provider=discord&redirect_to=http://localhost:{}/callback",
-        supabase_url, CALLBACK_PORT
-    );
+    let auth_url = auth_url(&supabase_url, provider);
 
     println!();
-    println!("{}", "Opening browser for Discord login...".yellow());
+    println!(
+        "{}",
+        format!("Opening browser for {} login...", provider.name()).yellow()
+    );
     println!();
 
     // Open browser
@@ -115,22 +719,39 @@ async fn authenticate(supabase_url: String) -> anyhow::Result<String> {
         println!();
     }
 
-    // Wait for token (with timeout)
-    let timeout = tokio::time::Duration::from_secs(120);
+    let token = wait_for_token(&token_store, timeout).await;
+    server_handle.abort();
+    token
+}
+
+/// Waits for the login callback to leave a token in `token_store`, giving up after `timeout`.
+async fn wait_for_token(
+    token_store: &Mutex<Option<String>>,
+    timeout: tokio::time::Duration,
+) -> anyhow::Result<String> {
     let start = tokio::time::Instant::now();
+    let mut last_announced = None;
 
     loop {
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
         if let Some(token) = token_store.lock().unwrap().clone() {
-            // Got token! Abort server
-            server_handle.abort();
             return Ok(token);
         }
 
         if start.elapsed() > timeout {
-            server_handle.abort();
-            anyhow::bail!("Authentication timeout (2 minutes)");
+            anyhow::bail!("Authentication timeout ({} seconds)", timeout.as_secs());
+        }
+
+        // Remind every 30s, then every 10s when close to expiry
+        let remaining = timeout.saturating_sub(start.elapsed()).as_secs();
+        let due = remaining.is_multiple_of(30) || (remaining <= 30 && remaining.is_multiple_of(10));
+        if due && remaining > 0 && last_announced != Some(remaining) {
+            last_announced = Some(remaining);
+            println!(
+                "{}",
+                format!("⏳ Waiting for login ({}s left)...", remaining).bright_black()
+            );
         }
     }
 }
@@ -260,164 +881,3028 @@ async fn callback_handler(
         "#
         .to_string(),
     )
-    .into()
 }
 
 // ===== Voting Loop =====
 
-async fn voting_loop(token: &str) -> anyhow::Result<()> {
-    loop {
-        // Fetch next theme
-        println!("Fetching next theme...");
-        let response = fetch_next_theme(token).await?;
-
-        if let Some(theme) = response.theme {
-            println!("{}", "━".repeat(60).bright_black());
-            println!();
-            println!(
-                "{} {}/{}",
-                "Progress:".bright_black(),
-                response.seen.to_string().bright_cyan(),
-                response.total.to_string().bright_cyan()
-            );
-            println!();
-            println!("{}", "THEME:".bright_yellow().bold());
-            println!("{}", theme.content.bright_white().bold());
-            println!();
-            println!(
-                "{}",
-                "Vote: [Y]es  [N]o  [S]kip  [Q]uit  [R]esults".bright_black()
-            );
-            print!("{}", "> ".bright_green().bold());
-            io::stdout().flush()?;
-
-            // Get user input
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
-            let choice = input.trim().to_lowercase();
-
-            match choice.as_str() {
-                "y" | "yes" => {
-                    submit_vote(theme.id, "yes", token).await?;
-                    println!("{}", "✓ Voted YES".green());
-                }
-                "n" | "no" => {
-                    submit_vote(theme.id, "no", token).await?;
-                    println!("{}", "✓ Voted NO".red());
-                }
-                "s" | "skip" => {
-                    submit_vote(theme.id, "skip", token).await?;
-                    println!("{}", "→ Skipped".yellow());
-                }
-                "q" | "quit" => {
-                    println!();
-                    println!("{}", "Thanks for voting! 👋".bright_cyan().bold());
-                    return Ok(());
-                }
-                "r" | "results" => {
-                    show_results().await?;
-                    continue;
-                }
-                _ => {
-                    println!("{}", "Invalid choice. Please try again.".red());
-                    continue;
-                }
-            }
-        } else {
-            println!();
-            println!("{}", "🎉 You've voted on all themes!".green().bold());
-            println!();
-            println!("View results? [Y/n]");
-            print!("> ");
-            io::stdout().flush()?;
+async fn offer_featured_theme(token: &str, config: &ServerConfig) -> anyhow::Result<()> {
+    let Some(featured) = fetch_featured_theme().await? else {
+        return Ok(());
+    };
+    let can_vote = config.allow_revote || !has_voted_on(featured.theme_id, token).await?;
 
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
+    println!("{}", "⭐ TODAY'S FEATURED THEME".bright_yellow().bold());
+    println!("{}", featured.content.bright_white().bold());
+    if let (Some(yes), Some(no)) = (featured.yes_votes, featured.no_votes) {
+        println!("{}", format!("{yes} yes, {no} no so far").bright_black());
+    }
+    println!();
+    if !can_vote {
+        return Ok(());
+    }
+    println!(
+        "{}",
+        "Vote on it now? [Y]es  [N]o  (Enter to continue)".bright_black()
+    );
+    print!("{}", "> ".bright_green().bold());
+    io::stdout().flush()?;
 
-            if !input.trim().to_lowercase().starts_with('n') {
-                show_results().await?;
-            }
+    let input = input::read_line()?;
 
-            break;
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" if config.allows("yes") => {
+            submit_vote(featured.theme_id, "yes", None, token).await?;
+            println!("{}", "✓ Voted YES".green());
         }
+        "n" | "no" if config.allows("no") => {
+            submit_vote(featured.theme_id, "no", None, token).await?;
+            println!("{}", "✓ Voted NO".red());
+        }
+        _ => {}
     }
-
+    println!();
     Ok(())
 }
 
-// ===== API Calls =====
+/// Offers to pick up where a previous session left off, sending its unconfirmed votes first.
+/// Returns the session to keep recording into, and the theme to show first if resuming.
+async fn resume_session(auth: &mut Auth) -> anyhow::Result<(Session, Option<Theme>)> {
+    let mut session = load_own_session(auth)?;
+    if session.pending_votes.is_empty() && session.last_theme.is_none() {
+        return Ok((session, None));
+    }
 
-async fn fetch_next_theme(token: &str) -> anyhow::Result<ThemeResponse> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get(format!("{}/themes/next", BACKEND_URL))
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .await?;
+    println!(
+        "{}",
+        format!(
+            "Found an unfinished session ({} unsent vote(s)). Resume it? [Y/n]",
+            session.pending_votes.len()
+        )
+        .yellow()
+    );
+    print!("> ");
+    io::stdout().flush()?;
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await?;
-        anyhow::bail!("API error ({}): {}", status, text);
+    let input = input::read_line()?;
+    if input.trim().to_lowercase().starts_with('n') {
+        session.clear()?;
+        return Ok((session, None));
     }
 
-    Ok(response.json().await?)
+    let flushed = flush_pending(&mut session, auth).await?;
+    if !flushed.sent.is_empty() {
+        println!(
+            "{}",
+            format!("✓ Sent {} pending vote(s)", flushed.sent.len()).green()
+        );
+    }
+    if !session.pending_votes.is_empty() {
+        let why = if flushed.throttled {
+            "The server asked us to slow down"
+        } else {
+            "Still offline"
+        };
+        println!(
+            "{}",
+            format!(
+                "⚠️  {why}, {} vote(s) will be sent later",
+                session.pending_votes.len()
+            )
+            .yellow()
+        );
+    }
+    println!();
+
+    // No need to show the last theme again if its vote just went through
+    let resume_theme = session
+        .last_theme
+        .take()
+        .filter(|theme| !flushed.sent.contains(&theme.id));
+    Ok((session, resume_theme))
 }
 
-async fn submit_vote(theme_id: i64, vote_type: &str, token: &str) -> anyhow::Result<()> {
-    let client = reqwest::Client::new();
-    let vote_req = VoteRequest {
-        theme_id,
-        vote_type: vote_type.to_string(),
+/// The saved session, minus any votes another account queued on this machine.
+fn load_own_session(auth: &Auth) -> io::Result<Session> {
+    let mut session = Session::load().unwrap_or_default();
+    let Some(user_id) = auth.user_id() else {
+        return Ok(session);
     };
+    let dropped = session.claim(&user_id);
+    if dropped > 0 {
+        println!(
+            "{}",
+            format!("⚠️  Dropped {dropped} unsent vote(s) left by another account").yellow()
+        );
+        session.save()?;
+    }
+    Ok(session)
+}
 
+/// Whether a request failed because the server couldn't be reached at all.
+fn is_offline(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+}
+
+/// What came of sending the queued votes.
+#[derive(Default)]
+struct Flushed {
+    /// Themes whose votes the server now has
+    sent: Vec<i64>,
+    /// Whether sending stopped because the server asked us to slow down, rather than
+    /// because it couldn't be reached
+    throttled: bool,
+}
+
+/// Whatever gets queued votes to the server: the signed-in user, or a stand-in in tests.
+trait SendVote {
+    async fn send_vote(&mut self, vote: PendingVote) -> anyhow::Result<()>;
+}
+
+impl SendVote for Auth {
+    async fn send_vote(&mut self, vote: PendingVote) -> anyhow::Result<()> {
+        self.retry(|token| {
+            let vote = vote.clone();
+            async move { submit_vote(vote.theme_id, &vote.vote_type, vote.reason, &token).await }
+        })
+        .await
+    }
+}
+
+/// Sends the session's unconfirmed votes oldest first, dropping each once the server has it.
+/// Votes the server turns down for good are dropped too, with a warning. Stops at the first
+/// network failure or 429, leaving the rest queued.
+async fn flush_pending(
+    session: &mut Session,
+    sender: &mut impl SendVote,
+) -> anyhow::Result<Flushed> {
+    let mut flushed = Flushed::default();
+    while let Some(vote) = session.pending_votes.first().cloned() {
+        let result = sender.send_vote(vote.clone()).await;
+        match result {
+            // Resending is harmless: a changed vote is simply set again...
+            Ok(()) => {}
+            // ...and a final one did reach the server before
+            Err(e) if e.is::<AlreadyVoted>() => {}
+            Err(e) if is_offline(&e) => break,
+            Err(e) if e.is::<ChangedTooSoon>() => {
+                flushed.throttled = true;
+                break;
+            }
+            // Resending would only be turned down again, so the vote goes
+            Err(e) if e.is::<VoteRejected>() => {
+                session.pending_votes.remove(0);
+                session.save()?;
+                println!(
+                    "{}",
+                    format!(
+                        "⚠️  Dropped your {} vote on theme #{}: {}",
+                        vote.vote_type.to_uppercase(),
+                        vote.theme_id,
+                        e
+                    )
+                    .yellow()
+                );
+                continue;
+            }
+            Err(e) => return Err(e),
+        }
+        session.pending_votes.remove(0);
+        session.save()?;
+        flushed.sent.push(vote.theme_id);
+    }
+    Ok(flushed)
+}
+
+/// Submits a vote, keeping it in the saved session until the server has it.
+/// Without a connection the vote stays queued, to be sent with the next one that gets through.
+async fn cast_vote(
+    session: &mut Session,
+    theme_id: i64,
+    vote_type: &str,
+    reason: Option<String>,
+    auth: &mut Auth,
+) -> anyhow::Result<()> {
+    session.pending_votes.push(PendingVote {
+        theme_id,
+        vote_type: vote_type.to_string(),
+        reason,
+    });
+    session.save()?;
+
+    print!("{}", "Submitting… ".bright_black());
+    io::stdout().flush()?;
+
+    // Earlier queued votes go first, so a changed vote can't be overtaken by its original
+    let flushed = flush_pending(session, auth).await?;
+
+    // Keys hammered while waiting were meant for this theme, not the next one
+    if input::discard_typed_ahead() > 0 {
+        print!("{}", "(ignored repeated input) ".bright_black());
+    }
+    if !session.pending_votes.is_empty() {
+        let why = if flushed.throttled {
+            "The server asked us to slow down"
+        } else {
+            "No connection"
+        };
+        println!(
+            "{}",
+            format!(
+                "📥 {why}, saved for later ({} queued)",
+                session.pending_votes.len()
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Asks whether to try reaching the server again. Queued votes stay saved either way.
+fn retry_offline(session: &Session) -> anyhow::Result<bool> {
+    println!(
+        "{}",
+        format!(
+            "📡 Can't reach the server ({} vote(s) queued). [Enter] to retry, [Q] to quit",
+            session.pending_votes.len()
+        )
+        .yellow()
+    );
+    print!("> ");
+    io::stdout().flush()?;
+
+    let input = input::read_line()?;
+    Ok(!input.trim().eq_ignore_ascii_case("q") && !input.is_empty())
+}
+
+/// Forgets the session unless votes are still waiting to be sent.
+fn end_session(session: &mut Session) -> anyhow::Result<()> {
+    if session.pending_votes.is_empty() {
+        session.clear()?;
+    } else {
+        session.last_theme = None;
+        session.save()?;
+        println!(
+            "{}",
+            format!(
+                "📥 {} vote(s) couldn't be sent yet; they'll go out next time you run the client.",
+                session.pending_votes.len()
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Votes on every theme left according to `rules`, logging each decision as it goes.
+async fn auto_vote_all(
+    auth: &mut Auth,
+    cli: &Cli,
+    config: &ServerConfig,
+    session: &mut Session,
+    rules: &autovote::Rules,
+) -> anyhow::Result<()> {
+    let strategy = config.strategy(cli)?;
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    loop {
+        let response = auth
+            .retry(|token| async move { fetch_next_theme(&token, strategy).await })
+            .await?;
+        if response.closed {
+            println!("{}", "🔒 Voting is closed.".yellow().bold());
+            break;
+        }
+        let Some(theme) = response.theme else {
+            break;
+        };
+
+        let (vote_type, keyword) = rules.decide(&theme);
+        let why = match keyword {
+            Some(keyword) => format!("(matched \"{keyword}\")"),
+            None => "(default)".to_string(),
+        };
+        println!(
+            "{} {}",
+            format!("[{}/{}]", response.seen + 1, response.total).bright_black(),
+            theme.content
+        );
+        cast_vote(session, theme.id, vote_type, None, auth).await?;
+        if !session.pending_votes.is_empty() {
+            // The server would only hand the same theme back until the vote gets through
+            break;
+        }
+        println!(
+            "→ {} {}",
+            vote_type.to_uppercase().bright_cyan(),
+            why.bright_black()
+        );
+
+        match counts.iter_mut().find(|(t, _)| *t == vote_type) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((vote_type, 1)),
+        }
+    }
+
+    println!();
+    if counts.is_empty() {
+        println!("{}", "No themes left to vote on.".yellow());
+    } else {
+        let summary: Vec<String> = counts
+            .iter()
+            .map(|(vote_type, count)| format!("{count} {vote_type}"))
+            .collect();
+        println!("{} {}", "🤖 Auto-voted:".green().bold(), summary.join(", "));
+    }
+    end_session(session)
+}
+
+async fn voting_loop(
+    auth: &mut Auth,
+    cli: &Cli,
+    config: &ServerConfig,
+    keys: &Keybindings,
+    session: &mut Session,
+    mut resume_theme: Option<Theme>,
+) -> anyhow::Result<()> {
+    let strategy = config.strategy(cli)?;
+    loop {
+        // Fetch next theme
+        println!("Fetching next theme...");
+        let response = match auth
+            .retry(|token| async move { fetch_next_theme(&token, strategy).await })
+            .await
+        {
+            Ok(response) => response,
+            Err(e) if is_offline(&e) => {
+                if retry_offline(session)? {
+                    flush_pending(session, auth).await?;
+                    continue;
+                }
+                end_session(session)?;
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        if response.closed {
+            println!();
+            println!("{}", "🔒 Voting is closed.".yellow().bold());
+            println!();
+            break;
+        }
+
+        if let Some(theme) = resume_theme.take().or(response.theme) {
+            session.last_theme = Some(theme.clone());
+            session.save()?;
+
+            println!("{}", "━".repeat(60).bright_black());
+            println!();
+            println!(
+                "{} {}/{}",
+                "Progress:".bright_black(),
+                response.seen.to_string().bright_cyan(),
+                response.total.to_string().bright_cyan()
+            );
+            println!();
+            println!("{}", "THEME:".bright_yellow().bold());
+            print_theme(&theme);
+            let theme_id = theme.id;
+            if let Some(tally) = shown_tally(cli, config, || fetch_theme_stats(theme_id)).await {
+                print_tally(&tally);
+            }
+            println!();
+            println!("{}", config.vote_prompt(keys).bright_black());
+            let timed = cli.theme_timer > 0 && config.allows("skip");
+            if timed {
+                println!("{}", time_left(cli.theme_timer));
+            }
+            print!("{}", "> ".bright_green().bold());
+            io::stdout().flush()?;
+
+            // Get user input, unless the timer runs out first
+            let input = if timed {
+                read_line_countdown(Duration::from_secs(cli.theme_timer))?
+            } else {
+                Some(input::read_line()?)
+            };
+            let Some(input) = input else {
+                println!();
+                println!("{}", "⏱ Time's up!".yellow());
+                cast_vote(session, theme.id, "skip", None, auth).await?;
+                println!("{}", "→ Skipped".yellow());
+                continue;
+            };
+            let action = match keys.parse(&input) {
+                Ok(action) if action.vote_type().is_some_and(|t| !config.allows(t)) => {
+                    println!("{}", "That option isn't available right now.".red());
+                    continue;
+                }
+                Ok(action) => action,
+                Err(hint) => {
+                    println!("{}", hint.red());
+                    continue;
+                }
+            };
+
+            if let Some(vote_type) = action.vote_type()
+                && undo_requested(cli.undo_window, vote_type, keys)?
+            {
+                println!("{}", "↶ Undone".bright_black());
+                resume_theme = Some(theme);
+                continue;
+            }
+
+            match action {
+                Action::Yes => {
+                    cast_vote(session, theme.id, "yes", None, auth).await?;
+                    println!("{}", "✓ Voted YES".green());
+                }
+                Action::No => {
+                    cast_vote(session, theme.id, "no", None, auth).await?;
+                    println!("{}", "✓ Voted NO".red());
+                }
+                Action::Maybe => {
+                    cast_vote(session, theme.id, "maybe", None, auth).await?;
+                    println!("{}", "✓ Voted MAYBE".blue());
+                }
+                Action::Skip => {
+                    let reason = if cli.skip_reasons {
+                        prompt_skip_reason()?
+                    } else {
+                        None
+                    };
+                    cast_vote(session, theme.id, "skip", reason, auth).await?;
+                    println!("{}", "→ Skipped".yellow());
+                }
+                Action::Quit => {
+                    println!();
+                    println!("{}", "Thanks for voting! 👋".bright_cyan().bold());
+                    end_session(session)?;
+                    return Ok(());
+                }
+                Action::Results => {
+                    show_results_if_visible(config, cli).await?;
+                    continue;
+                }
+                Action::Undo => {
+                    println!("{}", "Nothing to undo.".yellow());
+                    continue;
+                }
+            }
+        } else if response.total == 0 {
+            println!();
+            println!("{}", "📭 No themes have been added yet.".yellow().bold());
+            println!(
+                "{}",
+                "Check back once voting has been set up!".bright_black()
+            );
+            println!();
+            break;
+        } else {
+            println!();
+            println!("{}", "🎉 You've voted on all themes!".green().bold());
+            // Just a bit of fun, so a failure here isn't worth stopping for
+            if let Ok(rank) = auth
+                .retry(|token| async move { fetch_my_rank(&token).await })
+                .await
+                && rank.voters > 1
+            {
+                println!(
+                    "{}",
+                    format!(
+                        "🏅 You've voted on more themes than {:.0}% of voters.",
+                        rank.ahead_of_percent
+                    )
+                    .bright_cyan()
+                );
+            }
+            println!();
+            ring_bell(cli.notify)?;
+
+            let mut choice = end_of_session_choice(config, config.allow_revote, ask)?;
+            if choice == EndChoice::ChangeVote {
+                // Changing a vote goes back through the loop, in case new themes showed up meanwhile
+                if change_a_vote(auth, session, config, keys).await? {
+                    continue;
+                }
+                choice = end_of_session_choice(config, false, ask)?;
+            }
+            match choice {
+                EndChoice::Results => show_results(config.results_page_size(cli)).await?,
+                EndChoice::ResultsHidden => print_results_hidden(),
+                EndChoice::ChangeVote | EndChoice::Done => {}
+            }
+
+            break;
+        }
+    }
+
+    end_session(session)?;
+    Ok(())
+}
+
+/// Where the menu shown once every theme has a vote leads.
+#[derive(Debug, PartialEq)]
+enum EndChoice {
+    ChangeVote,
+    Results,
+    ResultsHidden,
+    Done,
+}
+
+/// Puts the end-of-session questions that apply to the user through `ask`: whether to change
+/// a vote first (when `offer_change`), then whether to see the results (when they're visible).
+fn end_of_session_choice(
+    config: &ServerConfig,
+    offer_change: bool,
+    mut ask: impl FnMut(&str) -> io::Result<String>,
+) -> io::Result<EndChoice> {
+    if offer_change
+        && ask("Change one of your votes first? [y/N]")?
+            .trim()
+            .to_lowercase()
+            .starts_with('y')
+    {
+        return Ok(EndChoice::ChangeVote);
+    }
+    if config.results_hidden {
+        return Ok(EndChoice::ResultsHidden);
+    }
+    if ask("View results? [Y/n]")?
+        .trim()
+        .to_lowercase()
+        .starts_with('n')
+    {
+        Ok(EndChoice::Done)
+    } else {
+        Ok(EndChoice::Results)
+    }
+}
+
+/// Shows `question` and reads the answer.
+fn ask(question: &str) -> io::Result<String> {
+    println!("{question}");
+    print!("> ");
+    io::stdout().flush()?;
+    input::read_line()
+}
+
+/// Lists the user's votes and lets them replace one. Returns whether a vote changed.
+async fn change_a_vote(
+    auth: &mut Auth,
+    session: &mut Session,
+    config: &ServerConfig,
+    keys: &Keybindings,
+) -> anyhow::Result<bool> {
+    let votes = auth
+        .retry(|token| async move { fetch_my_votes(&token).await })
+        .await?;
+    if votes.is_empty() {
+        return Ok(false);
+    }
+
+    println!();
+    for (i, vote) in votes.iter().enumerate() {
+        println!(
+            "[{}] {} {}",
+            (i + 1).to_string().bright_cyan(),
+            vote.content.bright_white().bold(),
+            format!("({})", vote.vote_type).bright_black()
+        );
+    }
+    print!(
+        "{}",
+        "Which one? (Enter to cancel) > ".bright_green().bold()
+    );
+    io::stdout().flush()?;
+
+    let input = input::read_line()?;
+    let Some(vote) = input
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| votes.get(i))
+    else {
+        return Ok(false);
+    };
+
+    println!();
+    println!("{}", vote.content.bright_white().bold());
+    let vote_type = loop {
+        println!("{}", config.vote_prompt(keys).bright_black());
+        print!("{}", "> ".bright_green().bold());
+        io::stdout().flush()?;
+
+        let input = input::read_line()?;
+        match keys.parse(&input) {
+            Ok(Action::Quit) => return Ok(false),
+            Ok(action) => match action.vote_type() {
+                Some(vote_type) if config.allows(vote_type) => break vote_type,
+                _ => println!("{}", "Pick a vote, or quit to keep it as is.".red()),
+            },
+            Err(hint) => println!("{}", hint.red()),
+        }
+    };
+
+    if vote_type == vote.vote_type {
+        println!("{}", "Unchanged.".bright_black());
+        return Ok(false);
+    }
+    cast_vote(session, vote.theme_id, vote_type, None, auth).await?;
+    if !session.pending_votes.is_empty() {
+        // Queued until the server takes it, which `cast_vote` has already said
+        return Ok(false);
+    }
+    println!(
+        "{}",
+        format!("✓ Changed to {}", vote_type.to_uppercase()).green()
+    );
+    Ok(true)
+}
+
+fn time_left(seconds: u64) -> ColoredString {
+    format!("⏱ {seconds}s left").bright_black()
+}
+
+/// Reads an answer at the prompt, ticking down the [`time_left`] line printed just above it.
+/// Returns `None` if nothing was entered within `limit`.
+fn read_line_countdown(limit: Duration) -> io::Result<Option<String>> {
+    let terminal = io::stdout().is_terminal();
+    let mut first = true;
+    input::read_line_countdown(limit, |left| {
+        // The caller already printed the starting count
+        if terminal && !std::mem::take(&mut first) {
+            // Save the cursor, rewrite the line above and come back, so typing isn't disturbed
+            print!("\x1b7\x1b[1A\r\x1b[2K{}\x1b8", time_left(left));
+            let _ = io::stdout().flush();
+        }
+    })
+}
+
+/// Holds a vote back for `window` seconds, returning true if the user typed the undo key.
+/// Any other input sends the vote right away.
+fn undo_requested(window: u64, vote_type: &str, keys: &Keybindings) -> io::Result<bool> {
+    if window == 0 {
+        return Ok(false);
+    }
+
+    print!(
+        "{}",
+        format!(
+            "voted {} — press {} to undo ({}s) ",
+            vote_type.to_uppercase(),
+            keys.key(Action::Undo),
+            window
+        )
+        .bright_black()
+    );
+    io::stdout().flush()?;
+
+    match input::read_line_timeout(Some(Duration::from_secs(window)))? {
+        Some(answer) => Ok(is_undo(Some(&answer), keys)),
+        None => {
+            // Nothing typed: wipe the hint so the confirmation takes its place
+            let mut stdout = io::stdout();
+            if stdout.is_terminal() {
+                print!("\r\x1b[2K");
+            } else {
+                println!();
+            }
+            stdout.flush()?;
+            Ok(false)
+        }
+    }
+}
+
+/// Whether the answer typed within the undo window takes the vote back. `None` means the
+/// window ran out with nothing typed, which lets the vote through.
+fn is_undo(answer: Option<&str>, keys: &Keybindings) -> bool {
+    answer.is_some_and(|answer| keys.parse(answer) == Ok(Action::Undo))
+}
+
+const SKIP_REASONS: [&str; 3] = ["didn't understand it", "not interested", "duplicate"];
+
+/// Asks why the theme is being skipped. Empty input means no reason.
+fn prompt_skip_reason() -> io::Result<Option<String>> {
+    println!(
+        "{}",
+        "Why skip? [1] Didn't understand  [2] Not interested  [3] Duplicate  (or type a reason, Enter for none)"
+            .bright_black()
+    );
+    print!("{}", "> ".bright_green().bold());
+    io::stdout().flush()?;
+
+    let input = input::read_line()?;
+    let answer = input.trim();
+
+    if answer.is_empty() {
+        return Ok(None);
+    }
+
+    let preset = answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| SKIP_REASONS.get(i));
+    Ok(Some(preset.copied().unwrap_or(answer).to_string()))
+}
+
+/// Emits the BEL character if asked to, unless stdout is redirected somewhere it would
+/// just be noise.
+fn ring_bell(enabled: bool) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let terminal = stdout.is_terminal();
+    write_bell(&mut stdout, enabled, terminal)
+}
+
+fn write_bell(out: &mut impl Write, enabled: bool, terminal: bool) -> io::Result<()> {
+    if enabled && terminal {
+        out.write_all(b"\x07")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+// ===== API Calls =====
+
+async fn fetch_next_theme(token: &str, strategy: Option<&str>) -> anyhow::Result<ThemeResponse> {
+    let client = reqwest::Client::new();
+    let mut request = client
+        .get(format!("{}/themes/next", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token));
+    if let Some(strategy) = strategy {
+        request = request.query(&[("strategy", strategy)]);
+    }
+    let response = request.send().await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn submit_vote(
+    theme_id: i64,
+    vote_type: &str,
+    reason: Option<String>,
+    token: &str,
+) -> anyhow::Result<()> {
+    let vote_req = VoteRequest {
+        theme_id,
+        vote_type: vote_type.to_string(),
+        reason,
+    };
+    let response = vote_request(&reqwest::Client::new(), &vote_req, token)
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if response.status() == reqwest::StatusCode::CONFLICT {
+        return Err(AlreadyVoted.into());
+    }
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(ChangedTooSoon.into());
+    }
+    if matches!(
+        response.status(),
+        reqwest::StatusCode::BAD_REQUEST
+            | reqwest::StatusCode::FORBIDDEN
+            | reqwest::StatusCode::NOT_FOUND
+    ) {
+        let status = response.status();
+        let text = response.text().await?;
+        return Err(VoteRejected(format!("{} ({})", text, status)).into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("Vote failed ({}): {}", status, text);
+    }
+
+    Ok(())
+}
+
+/// `POST /themes/vote`, saying which client version cast the vote so organizers can
+/// tell releases apart.
+fn vote_request(
+    client: &reqwest::Client,
+    vote_req: &VoteRequest,
+    token: &str,
+) -> reqwest::RequestBuilder {
+    client
+        .post(format!("{}/themes/vote", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .header("X-Client-Version", env!("CARGO_PKG_VERSION"))
+        .json(vote_req)
+}
+
+async fn fetch_eligibility(token: &str) -> anyhow::Result<Eligibility> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/me/eligibility", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn fetch_theme_pair(token: &str) -> anyhow::Result<PairResponse> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/themes/pair", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn submit_pair_vote(winner_id: i64, loser_id: i64, token: &str) -> anyhow::Result<()> {
+    let response = pair_vote_request(&reqwest::Client::new(), winner_id, loser_id, token)
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if response.status() == reqwest::StatusCode::CONFLICT {
+        return Err(AlreadyVoted.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("Vote failed ({}): {}", status, text);
+    }
+
+    Ok(())
+}
+
+/// `POST /themes/pair/vote`, with the client version like [`vote_request`].
+fn pair_vote_request(
+    client: &reqwest::Client,
+    winner_id: i64,
+    loser_id: i64,
+    token: &str,
+) -> reqwest::RequestBuilder {
+    client
+        .post(format!("{}/themes/pair/vote", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .header("X-Client-Version", env!("CARGO_PKG_VERSION"))
+        .json(&PairVoteRequest {
+            winner_id,
+            loser_id,
+        })
+}
+
+/// Server config, falling back to defaults when it can't be fetched.
+async fn load_config() -> ServerConfig {
+    fetch_config().await.unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            "⚠️  Could not fetch server config, using defaults:".yellow(),
+            e
+        );
+        ServerConfig::default()
+    })
+}
+
+async fn fetch_config() -> anyhow::Result<ServerConfig> {
+    let client = reqwest::Client::new();
+    let response = client.get(format!("{}/config", BACKEND_URL)).send().await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Fetches one active theme by id, or `None` if there's no such theme.
+async fn fetch_theme(theme_id: i64) -> anyhow::Result<Option<Theme>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/themes/{}", BACKEND_URL, theme_id))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(Some(response.json().await?))
+}
+
+/// Today's featured theme, or `None` when none has been set.
+async fn fetch_featured_theme() -> anyhow::Result<Option<FeaturedTheme>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/themes/featured", BACKEND_URL))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(Some(response.json().await?))
+}
+
+async fn has_voted_on(theme_id: i64, token: &str) -> anyhow::Result<bool> {
+    let votes = fetch_my_votes(token).await?;
+    Ok(votes.iter().any(|vote| vote.theme_id == theme_id))
+}
+
+async fn fetch_my_votes(token: &str) -> anyhow::Result<Vec<MyVote>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/themes/mine", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn fetch_my_rank(token: &str) -> anyhow::Result<VoterRank> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/me/rank", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn fetch_progress(token: &str) -> anyhow::Result<Progress> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/themes/progress", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn fetch_remaining_by_tag(token: &str) -> anyhow::Result<Vec<RemainingByTag>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/themes/remaining/by-tag", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Tallies for just the themes in `ids`, or `None` while results are hidden.
+async fn fetch_stats(ids: &[i64]) -> anyhow::Result<Option<Vec<serde_json::Value>>> {
+    let ids: Vec<String> = ids.iter().map(i64::to_string).collect();
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/admin/stats", BACKEND_URL))
+        .query(&[("ids", ids.join(","))])
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(Some(response.json().await?))
+}
+
+/// One theme's tally, or `None` if it no longer exists.
+async fn fetch_theme_stats(theme_id: i64) -> anyhow::Result<Option<serde_json::Value>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/themes/{}/stats", BACKEND_URL, theme_id))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(Some(response.json().await?))
+}
+
+async fn fetch_stats_page(limit: usize, offset: usize) -> anyhow::Result<Vec<serde_json::Value>> {
+    fetch_page("results/public", limit, offset).await
+}
+
+async fn fetch_page(
+    endpoint: &str,
+    limit: usize,
+    offset: usize,
+) -> anyhow::Result<Vec<serde_json::Value>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/{}", BACKEND_URL, endpoint))
+        .query(&[("limit", limit), ("offset", offset)])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn fetch_matching_themes(term: &str) -> anyhow::Result<Vec<Theme>> {
+    let response = search_request(&reqwest::Client::new(), term).send().await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("Search failed ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+/// `GET /themes?search=`, with the term encoded so any text is sent as typed.
+fn search_request(client: &reqwest::Client, term: &str) -> reqwest::RequestBuilder {
+    client
+        .get(format!("{}/themes", BACKEND_URL))
+        .query(&[("search", term)])
+}
+
+/// Shows the results, unless the server is keeping them from voters for now.
+async fn show_results_if_visible(config: &ServerConfig, cli: &Cli) -> anyhow::Result<()> {
+    if config.results_hidden {
+        print_results_hidden();
+        return Ok(());
+    }
+    show_results(config.results_page_size(cli)).await
+}
+
+fn print_results_hidden() {
+    println!("{}", "🙈 Results are hidden until voting closes.".yellow());
+}
+
+async fn show_results(page_size: usize) -> anyhow::Result<()> {
+    println!();
+    println!("{}", "=".repeat(60).bright_cyan());
+    println!("{}", "    📊 VOTING RESULTS".bright_yellow().bold());
+    println!("{}", "=".repeat(60).bright_cyan());
+    println!();
+
+    for_each_page(page_size, fetch_stats_page, |page, offset, last| {
+        print_results(page, offset);
+        Ok(!last && prompt_more()?)
+    })
+    .await?;
+
+    println!();
+    Ok(())
+}
+
+/// Fetches pages at successive offsets and hands each to `each` along with its offset and
+/// whether it's the last, until a short page marks the end or `each` returns false. An
+/// empty page isn't handed on.
+async fn for_each_page<F, Fut>(
+    page_size: usize,
+    mut fetch: F,
+    mut each: impl FnMut(&[serde_json::Value], usize, bool) -> anyhow::Result<bool>,
+) -> anyhow::Result<()>
+where
+    F: FnMut(usize, usize) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<Vec<serde_json::Value>>>,
+{
+    let mut offset = 0;
+    loop {
+        let page = fetch(page_size, offset).await?;
+        if page.is_empty() {
+            return Ok(());
+        }
+        // A short page means we've reached the end
+        let last = page.len() < page_size;
+        if !each(&page, offset, last)? || last {
+            return Ok(());
+        }
+        offset += page.len();
+    }
+}
+
+/// Prints every result as a GitHub-flavored markdown table, best first.
+async fn print_markdown_results(page_size: usize) -> anyhow::Result<()> {
+    let mut results = Vec::new();
+    for_each_page(page_size, fetch_stats_page, |page, _, _| {
+        results.extend_from_slice(page);
+        Ok(true)
+    })
+    .await?;
+    print!("{}", markdown_table(&results));
+    Ok(())
+}
+
+fn markdown_table(results: &[serde_json::Value]) -> String {
+    let mut table = String::from("| # | Theme | Yes | Maybe | No | Skip | Total | Yes % |\n");
+    table.push_str("|---:|---|---:|---:|---:|---:|---:|---:|\n");
+    for (i, theme) in results.iter().enumerate() {
+        let content = display::sanitize(theme["content"].as_str().unwrap_or("Unknown"));
+        let yes = theme["yes_votes"].as_i64().unwrap_or(0);
+        let no = theme["no_votes"].as_i64().unwrap_or(0);
+        let skip = theme["skip_votes"].as_i64().unwrap_or(0);
+        let maybe = theme["maybe_votes"].as_i64().unwrap_or(0);
+        let total = theme["total_votes"].as_i64().unwrap_or(0);
+        // Share of yes among yes/no votes, like the leaderboard
+        let percent = if yes + no > 0 {
+            format!("{:.0}%", yes as f64 * 100.0 / (yes + no) as f64)
+        } else {
+            "-".to_string()
+        };
+
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+            i + 1,
+            content.replace('|', "\\|"),
+            yes,
+            maybe,
+            no,
+            skip,
+            total,
+            percent
+        ));
+    }
+    table
+}
+
+fn print_results(page: &[serde_json::Value], offset: usize) {
+    for (i, theme) in page.iter().enumerate() {
+        let content = theme["content"].as_str().unwrap_or("Unknown");
+        let yes = theme["yes_votes"].as_i64().unwrap_or(0);
+        let no = theme["no_votes"].as_i64().unwrap_or(0);
+        let maybe = theme["maybe_votes"].as_i64().unwrap_or(0);
+        let total = theme["total_votes"].as_i64().unwrap_or(0);
+
+        // Only mentioned when used, so servers without "maybe" read as before
+        let maybe = match maybe {
+            0 => String::new(),
+            maybe => format!(", {} maybe", maybe.to_string().blue()),
+        };
+        println!(
+            "{}. {} ({} votes: {} yes{}, {} no)",
+            (offset + i + 1).to_string().bright_cyan(),
+            content.bright_white().bold(),
+            total.to_string().yellow(),
+            yes.to_string().green(),
+            maybe,
+            no.to_string().red()
+        );
+    }
+}
+
+/// Live dashboard: polls the top results and redraws only when they change, until Ctrl-C.
+async fn watch_results(page_size: usize, interval: Duration) -> anyhow::Result<()> {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    poll_results(
+        interval,
+        ctrl_c,
+        || fetch_stats_page(page_size, 0),
+        |page| {
+            // Clear the screen and redraw from the top
+            print!("\x1b[2J\x1b[H");
+            println!("{}", "=".repeat(60).bright_cyan());
+            println!("{}", "    📊 VOTING RESULTS (live)".bright_yellow().bold());
+            println!("{}", "=".repeat(60).bright_cyan());
+            println!();
+            print_results(page, 0);
+            println!();
+            println!(
+                "{}",
+                format!("Refreshing every {}s - Ctrl-C to stop", interval.as_secs()).bright_black()
+            );
+            io::stdout().flush()
+        },
+    )
+    .await?;
+
+    println!();
+    Ok(())
+}
+
+/// Fetches results every `interval` until `stop` completes, handing them to `redraw` only
+/// when they differ from the ones last shown. A failed fetch is retried at the next tick.
+async fn poll_results<F, Fut>(
+    interval: Duration,
+    stop: impl Future<Output = ()>,
+    mut fetch: F,
+    mut redraw: impl FnMut(&[serde_json::Value]) -> io::Result<()>,
+) -> anyhow::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<serde_json::Value>>>,
+{
+    let mut ticker = tokio::time::interval(interval);
+    let mut shown: Option<Vec<serde_json::Value>> = None;
+    tokio::pin!(stop);
+
+    loop {
+        tokio::select! {
+            _ = &mut stop => return Ok(()),
+            _ = ticker.tick() => {
+                let page = match fetch().await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        eprintln!("{} {}", "⚠️  Refresh failed, retrying:".yellow(), e);
+                        continue;
+                    }
+                };
+                if shown.as_ref() == Some(&page) {
+                    continue;
+                }
+                redraw(&page)?;
+                shown = Some(page);
+            }
+        }
+    }
+}
+
+// ===== Compare =====
+
+/// Width of each theme's column in the side-by-side comparison.
+const COMPARE_COLUMN: usize = 28;
+
+async fn compare_themes(a: i64, b: i64, token: &str) -> anyhow::Result<()> {
+    if a == b {
+        println!("{}", "Pick two different themes to compare.".yellow());
+        return Ok(());
+    }
+
+    let response = compare_request(&reqwest::Client::new(), a, b, token)
+        .send()
+        .await?;
+
+    // The server names whichever id it couldn't find, or says we aren't an admin
+    if matches!(
+        response.status(),
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::FORBIDDEN
+    ) {
+        let text = response.text().await?;
+        println!("{} {}", "⚠️".yellow(), text.yellow());
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    let comparison: Comparison = response.json().await?;
+    print_comparison(a, b, &comparison);
+    Ok(())
+}
+
+/// `GET /admin/compare?a=&b=`, comparing theme `a` (shown on the left) with `b`.
+fn compare_request(
+    client: &reqwest::Client,
+    a: i64,
+    b: i64,
+    token: &str,
+) -> reqwest::RequestBuilder {
+    client
+        .get(format!("{}/admin/compare", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .query(&[("a", a), ("b", b)])
+}
+
+fn print_comparison(a: i64, b: i64, comparison: &Comparison) {
+    for line in comparison_lines(a, b, comparison) {
+        println!("{line}");
+    }
+}
+
+/// The two themes side by side with their tallies, then how voters of both split.
+fn comparison_lines(a: i64, b: i64, comparison: &Comparison) -> Vec<String> {
+    let column = |id: i64, tally: &ThemeTally| {
+        let mut lines = vec![format!("#{id}")];
+        lines.extend(display::wrap(
+            &display::sanitize(&tally.content),
+            COMPARE_COLUMN,
+        ));
+        lines
+    };
+    let left = column(a, &comparison.a);
+    let right = column(b, &comparison.b);
+
+    let mut out = vec!["=".repeat(60).bright_cyan().to_string()];
+    for i in 0..left.len().max(right.len()) {
+        let cell = |lines: &[String]| lines.get(i).cloned().unwrap_or_default();
+        out.push(format!(
+            "{}  │  {}",
+            pad(&cell(&left), COMPARE_COLUMN).bright_white().bold(),
+            cell(&right).bright_white().bold()
+        ));
+    }
+    out.push("-".repeat(60).bright_black().to_string());
+
+    let mut row = |label: &str, count: fn(&ThemeTally) -> i64| {
+        let cell = |tally: &ThemeTally| pad(&format!("{label:<6}{}", count(tally)), COMPARE_COLUMN);
+        out.push(format!(
+            "{}  │  {}",
+            cell(&comparison.a),
+            cell(&comparison.b).trim_end()
+        ));
+    };
+    row("Yes", |tally| tally.yes_votes);
+    row("No", |tally| tally.no_votes);
+    row("Maybe", |tally| tally.maybe_votes);
+    row("Skip", |tally| tally.skip_votes);
+    row("Total", |tally| tally.total_votes);
+    out.push(format!(
+        "{}  │  {}",
+        vote_bar(&comparison.a, COMPARE_COLUMN),
+        vote_bar(&comparison.b, COMPARE_COLUMN)
+    ));
+    out.push("-".repeat(60).bright_black().to_string());
+
+    if comparison.voted_both == 0 {
+        out.push(
+            "Nobody has voted on both themes yet."
+                .bright_black()
+                .to_string(),
+        );
+    } else {
+        out.push(format!(
+            "{} {}",
+            "Voters who voted on both:".bright_black(),
+            comparison.voted_both.to_string().bright_cyan()
+        ));
+        let segments = display::bar_segments(
+            &[
+                comparison.preferred_a,
+                comparison.same,
+                comparison.preferred_b,
+            ],
+            60,
+        );
+        out.push(format!(
+            "{}{}{}",
+            "█".repeat(segments[0]).cyan(),
+            "█".repeat(segments[1]).bright_black(),
+            "█".repeat(segments[2]).magenta()
+        ));
+        out.push(format!(
+            "{} {}   {} {}   {} {}",
+            format!("preferred #{a}:").cyan(),
+            comparison.preferred_a,
+            "no preference:".bright_black(),
+            comparison.same,
+            format!("preferred #{b}:").magenta(),
+            comparison.preferred_b
+        ));
+        if comparison.no_opinion > 0 {
+            out.push(format!(
+                "{} {}",
+                "skipped one of them:".bright_black(),
+                comparison.no_opinion
+            ));
+        }
+    }
+    out.push("=".repeat(60).bright_cyan().to_string());
+    out
+}
+
+/// A theme's yes/maybe/no/skip split as a colored bar.
+fn vote_bar(tally: &ThemeTally, width: usize) -> String {
+    let segments = display::bar_segments(
+        &[
+            tally.yes_votes,
+            tally.maybe_votes,
+            tally.no_votes,
+            tally.skip_votes,
+        ],
+        width,
+    );
+    let filled: usize = segments.iter().sum();
+    format!(
+        "{}{}{}{}{}",
+        "█".repeat(segments[0]).green(),
+        "█".repeat(segments[1]).blue(),
+        "█".repeat(segments[2]).red(),
+        "█".repeat(segments[3]).bright_black(),
+        "░".repeat(width - filled).bright_black()
+    )
+}
+
+/// Pads `text` with spaces to `width` characters.
+fn pad(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    format!("{text}{}", " ".repeat(width.saturating_sub(len)))
+}
+
+/// Asks whether to show another page of output.
+fn prompt_more() -> io::Result<bool> {
+    print!(
+        "{}",
+        "More results - [Enter] to continue, [Q] to stop > ".bright_black()
+    );
+    io::stdout().flush()?;
+
+    let input = input::read_line()?;
+    println!();
+    Ok(!input.trim().eq_ignore_ascii_case("q"))
+}
+
+// ===== Export =====
+
+const EXPORT_PAGE_SIZE: usize = 500;
+
+/// Writes votes one JSON object per line, page by page, so the full export is never held in memory.
+async fn export_votes(auth: &mut Auth, path: &Path) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let mut exported = 0;
+    // `voted_at` and `id` of the last vote written, where the next page carries on from
+    let mut before: Option<(String, i64)> = None;
+
+    loop {
+        let after = before.as_ref();
+        let page = auth
+            .retry(|token| async move { fetch_export_page(&token, EXPORT_PAGE_SIZE, after).await })
+            .await?;
+        for vote in &page {
+            serde_json::to_writer(&mut writer, vote)?;
+            writer.write_all(b"\n")?;
+        }
+        exported += page.len();
+
+        let last = page
+            .last()
+            .and_then(|vote| Some((vote["voted_at"].as_str()?.to_string(), vote["id"].as_i64()?)));
+        match last {
+            Some(last) if page.len() == EXPORT_PAGE_SIZE => before = Some(last),
+            _ => break,
+        }
+    }
+    writer.flush()?;
+
+    println!(
+        "{} {} votes to {}",
+        "✓ Exported".green(),
+        exported.to_string().bright_cyan(),
+        path.display().to_string().bright_white()
+    );
+    Ok(())
+}
+
+/// Up to `limit` votes, newest first, older than the `(voted_at, id)` given in `before`.
+async fn fetch_export_page(
+    token: &str,
+    limit: usize,
+    before: Option<&(String, i64)>,
+) -> anyhow::Result<Vec<serde_json::Value>> {
+    let mut query = vec![("limit", limit.to_string())];
+    if let Some((voted_at, id)) = before {
+        query.push(("before", voted_at.clone()));
+        query.push(("before_id", id.to_string()));
+    }
+    let client = reqwest::Client::new();
     let response = client
-        .post(format!("{}/themes/vote", BACKEND_URL))
+        .get(format!("{}/admin/export", BACKEND_URL))
         .header("Authorization", format!("Bearer {}", token))
-        .json(&vote_req)
+        .query(&query)
         .send()
         .await?;
 
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
     if !response.status().is_success() {
         let status = response.status();
         let text = response.text().await?;
-        anyhow::bail!("Vote failed ({}): {}", status, text);
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Saves the caller's own data exactly as the server returns it.
+async fn export_my_data(auth: &mut Auth, path: &Path) -> anyhow::Result<()> {
+    let data = auth
+        .retry(|token| async move { fetch_my_data(&token).await })
+        .await?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, &data)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+
+    let votes = data["votes"].as_array().map_or(0, Vec::len);
+    println!(
+        "{} {} of your votes to {}",
+        "✓ Exported".green(),
+        votes.to_string().bright_cyan(),
+        path.display().to_string().bright_white()
+    );
+    Ok(())
+}
+
+async fn fetch_my_data(token: &str) -> anyhow::Result<serde_json::Value> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/me/export", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    Ok(response.json().await?)
+}
+
+async fn save_report(token: &str, path: &Path) -> anyhow::Result<()> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/admin/report.html", BACKEND_URL))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await?;
+        anyhow::bail!("API error ({}): {}", status, text);
+    }
+
+    fs::write(path, response.bytes().await?)?;
+    println!(
+        "{} {}",
+        "✓ Saved the results report to".green(),
+        path.display().to_string().bright_white()
+    );
+    Ok(())
+}
+
+// ===== Search =====
+
+const SEARCH_PAGE_SIZE: usize = 10;
+
+async fn search_themes(term: &str) -> anyhow::Result<()> {
+    let themes = fetch_matching_themes(term).await?;
+
+    if themes.is_empty() {
+        println!(
+            "{} {}",
+            "No themes match".yellow(),
+            format!("\"{}\"", term).bright_white().bold()
+        );
+        println!(
+            "{}",
+            "Looks like that idea hasn't been suggested yet!".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        themes.len().to_string().bright_cyan(),
+        format!("theme(s) matching \"{}\":", term).bright_black()
+    );
+    println!();
+
+    for (page, chunk) in themes.chunks(SEARCH_PAGE_SIZE).enumerate() {
+        if page > 0 && !prompt_more()? {
+            break;
+        }
+
+        // Only the themes about to be shown are looked up
+        let ids: Vec<i64> = chunk.iter().map(|theme| theme.id).collect();
+        let Some(stats) = fetch_stats(&ids).await? else {
+            if page == 0 {
+                println!(
+                    "{}",
+                    "(Vote counts are hidden until voting closes.)".bright_black()
+                );
+            }
+            for theme in chunk {
+                println!(
+                    "#{} {}",
+                    theme.id.to_string().bright_cyan(),
+                    theme.content.bright_white().bold()
+                );
+            }
+            continue;
+        };
+        // theme_id -> (yes, no, total)
+        let tallies: HashMap<i64, (i64, i64, i64)> = stats
+            .iter()
+            .filter_map(|theme| {
+                Some((
+                    theme["theme_id"].as_i64()?,
+                    (
+                        theme["yes_votes"].as_i64().unwrap_or(0),
+                        theme["no_votes"].as_i64().unwrap_or(0),
+                        theme["total_votes"].as_i64().unwrap_or(0),
+                    ),
+                ))
+            })
+            .collect();
+
+        for theme in chunk {
+            let (yes, no, total) = tallies.get(&theme.id).copied().unwrap_or_default();
+            println!(
+                "#{} {} ({} votes: {} yes, {} no)",
+                theme.id.to_string().bright_cyan(),
+                theme.content.bright_white().bold(),
+                total.to_string().yellow(),
+                yes.to_string().green(),
+                no.to_string().red()
+            );
+        }
+    }
+
+    println!();
+    Ok(())
+}
+
+// ===== Vote On =====
+
+/// Finds the theme meant by `target`: an id, or text that a search narrows down.
+/// Lets the user choose when several themes match. `None` means there's nothing to vote on.
+async fn pick_theme(target: &str) -> anyhow::Result<Option<Theme>> {
+    let target = target.trim().trim_start_matches('#');
+
+    if let Ok(theme_id) = target.parse::<i64>() {
+        let theme = fetch_theme(theme_id).await?;
+        if theme.is_none() {
+            println!(
+                "{} {}",
+                "No theme with id".yellow(),
+                format!("#{}", theme_id).bright_white().bold()
+            );
+        }
+        return Ok(theme);
+    }
+
+    let mut themes = fetch_matching_themes(target).await?;
+    if let Some(theme) = settled_match(&mut themes, target) {
+        return Ok(Some(theme));
+    }
+
+    match themes.len() {
+        0 => {
+            println!(
+                "{} {}",
+                "No themes match".yellow(),
+                format!("\"{}\"", target).bright_white().bold()
+            );
+            Ok(None)
+        }
+        n => {
+            println!(
+                "{}",
+                format!("{} themes match \"{}\":", n, target).bright_black()
+            );
+            for (i, theme) in themes.iter().take(SEARCH_PAGE_SIZE).enumerate() {
+                println!(
+                    "[{}] {} {}",
+                    (i + 1).to_string().bright_cyan(),
+                    theme.content.bright_white().bold(),
+                    format!("#{}", theme.id).bright_black()
+                );
+            }
+            if n > SEARCH_PAGE_SIZE {
+                println!(
+                    "{}",
+                    format!("...and {} more, try a longer search", n - SEARCH_PAGE_SIZE)
+                        .bright_black()
+                );
+            }
+            print!(
+                "{}",
+                "Which one? (Enter to cancel) > ".bright_green().bold()
+            );
+            io::stdout().flush()?;
+
+            let input = input::read_line()?;
+            let choice = input
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .filter(|&i| i < n.min(SEARCH_PAGE_SIZE));
+            println!();
+            Ok(choice.map(|i| themes.swap_remove(i)))
+        }
+    }
+}
+
+/// The search result `target` clearly means, taken out of `themes`: an exact match (even if
+/// the text also appears inside other themes), or else the only result.
+fn settled_match(themes: &mut Vec<Theme>, target: &str) -> Option<Theme> {
+    if let Some(i) = themes
+        .iter()
+        .position(|theme| theme.content.eq_ignore_ascii_case(target))
+    {
+        return Some(themes.swap_remove(i));
+    }
+    if themes.len() == 1 {
+        themes.pop()
+    } else {
+        None
+    }
+}
+
+/// Votes on a single chosen theme, then shows how it stands.
+async fn vote_on(
+    theme: &Theme,
+    token: &str,
+    cli: &Cli,
+    config: &ServerConfig,
+    keys: &Keybindings,
+) -> anyhow::Result<()> {
+    println!("{}", "THEME:".bright_yellow().bold());
+    print_theme(theme);
+    println!();
+
+    if !config.allow_revote && has_voted_on(theme.id, token).await? {
+        println!("{}", format!("⚠️  {}.", AlreadyVoted).yellow());
+        return Ok(());
+    }
+
+    let vote_type = loop {
+        println!("{}", config.vote_prompt(keys).bright_black());
+        print!("{}", "> ".bright_green().bold());
+        io::stdout().flush()?;
+
+        let input = input::read_line()?;
+        match keys.parse(&input) {
+            Ok(Action::Quit) => return Ok(()),
+            Ok(Action::Results) => show_results_if_visible(config, cli).await?,
+            Ok(Action::Undo) => println!("{}", "Nothing to undo.".yellow()),
+            Ok(action) => match action.vote_type() {
+                Some(vote_type) if config.allows(vote_type) => break vote_type,
+                _ => println!("{}", "That option isn't available right now.".red()),
+            },
+            Err(hint) => println!("{}", hint.red()),
+        }
+    };
+
+    let reason = if vote_type == "skip" && cli.skip_reasons {
+        prompt_skip_reason()?
+    } else {
+        None
+    };
+    submit_vote(theme.id, vote_type, reason, token).await?;
+    println!(
+        "{}",
+        format!("✓ Voted {}", vote_type.to_uppercase()).green()
+    );
+
+    if !config.results_hidden
+        && let Some(tally) = fetch_theme_stats(theme.id).await?
+    {
+        println!(
+            "{} ({} votes: {} yes, {} no)",
+            "Now standing at".bright_black(),
+            tally["total_votes"]
+                .as_i64()
+                .unwrap_or(0)
+                .to_string()
+                .yellow(),
+            tally["yes_votes"].as_i64().unwrap_or(0).to_string().green(),
+            tally["no_votes"].as_i64().unwrap_or(0).to_string().red()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// The theme's tally through `fetch`, only when `--show-tally` asks for it and the server
+/// isn't hiding results. A failed fetch just leaves it out.
+async fn shown_tally<F, Fut>(
+    cli: &Cli,
+    config: &ServerConfig,
+    fetch: F,
+) -> Option<serde_json::Value>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = anyhow::Result<Option<serde_json::Value>>>,
+{
+    if !cli.show_tally || config.results_hidden {
+        return None;
+    }
+    fetch().await.ok().flatten()
+}
+
+fn print_tally(tally: &serde_json::Value) {
+    match tally["total_votes"].as_i64().unwrap_or(0) {
+        0 => println!("{}", "No votes yet - you're the first!".bright_black()),
+        total => println!(
+            "{} ({} votes: {} yes, {} no)",
+            "So far".bright_black(),
+            total.to_string().yellow(),
+            tally["yes_votes"].as_i64().unwrap_or(0).to_string().green(),
+            tally["no_votes"].as_i64().unwrap_or(0).to_string().red()
+        ),
+    }
+}
+
+fn print_theme(theme: &Theme) {
+    for line in display::theme_lines(&theme.content) {
+        println!("{}", line.bright_white().bold());
+    }
+    if let Some(description) = &theme.description {
+        for line in display::theme_lines(description) {
+            println!("{}", line.bright_black().italic());
+        }
+    }
+}
+
+// ===== Speed Round =====
+
+/// Votes on as many themes as possible before `round` runs out, with a countdown at each
+/// prompt, then sums up the session. Votes are sent as usual; losing the server ends the
+/// round early, still with its summary, and only votes the server took are counted.
+async fn speed_round(
+    auth: &mut Auth,
+    cli: &Cli,
+    config: &ServerConfig,
+    keys: &Keybindings,
+    session: &mut Session,
+    round: Duration,
+) -> anyhow::Result<()> {
+    let strategy = config.strategy(cli)?;
+    println!(
+        "{}",
+        format!("⚡ SPEED ROUND: {}s on the clock!", round.as_secs())
+            .bright_yellow()
+            .bold()
+    );
+    println!();
+
+    let deadline = Instant::now() + round;
+    let mut tally: HashMap<&str, usize> = HashMap::new();
+    'round: while Instant::now() < deadline {
+        let response = match auth
+            .retry(|token| async move { fetch_next_theme(&token, strategy).await })
+            .await
+        {
+            Ok(response) => response,
+            Err(e) if is_offline(&e) => {
+                println!("{}", "⚠ Lost the connection to the server.".yellow());
+                break 'round;
+            }
+            Err(e) => return Err(e),
+        };
+        if response.closed {
+            println!("{}", "🔒 Voting is closed.".yellow().bold());
+            break;
+        }
+        let Some(theme) = response.theme else {
+            println!("{}", "🎉 You've voted on all themes!".green().bold());
+            break;
+        };
+
+        println!("{}", "━".repeat(60).bright_black());
+        print_theme(&theme);
+        println!("{}", config.vote_prompt(keys).bright_black());
+
+        let vote_type = loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break 'round;
+            }
+            println!(
+                "{}",
+                time_left(left.as_secs() + u64::from(left.subsec_nanos() > 0))
+            );
+            print!("{}", "> ".bright_green().bold());
+            io::stdout().flush()?;
+
+            let Some(input) = read_line_countdown(left)? else {
+                println!();
+                break 'round;
+            };
+            match keys.parse(&input) {
+                Ok(Action::Quit) => break 'round,
+                Ok(action) => match action.vote_type() {
+                    Some(vote_type) if config.allows(vote_type) => break vote_type,
+                    Some(_) => println!("{}", "That option isn't available right now.".red()),
+                    None => println!("{}", "Not during a speed round!".red()),
+                },
+                Err(hint) => println!("{}", hint.red()),
+            }
+        };
+
+        cast_vote(session, theme.id, vote_type, None, auth).await?;
+        if !session.pending_votes.is_empty() {
+            // The vote is queued, and the server would only hand the same theme back
+            break 'round;
+        }
+        println!("{}", format!("✓ {}", vote_type.to_uppercase()).green());
+        *tally.entry(vote_type).or_default() += 1;
+    }
+
+    print_speed_summary(&tally, round);
+    end_session(session)
+}
+
+fn print_speed_summary(tally: &HashMap<&str, usize>, round: Duration) {
+    let total: usize = tally.values().sum();
+    println!();
+    println!("{}", "⚡ Speed round over!".bright_yellow().bold());
+    println!(
+        "You voted on {} theme(s) in {}s.",
+        total.to_string().bright_cyan(),
+        round.as_secs()
+    );
+    if total > 0 {
+        println!("{}", speed_breakdown(tally).bright_black());
     }
+    println!();
+}
+
+/// Each kind of vote cast with its share of the round, e.g. "3 yes (75%), 1 no (25%)".
+/// Kinds that weren't used are left out.
+fn speed_breakdown(tally: &HashMap<&str, usize>) -> String {
+    let total: usize = tally.values().sum();
+    let breakdown: Vec<String> = ["yes", "no", "maybe", "skip"]
+        .iter()
+        .filter_map(|vote_type| {
+            let count = tally.get(vote_type)?;
+            Some(format!(
+                "{} {} ({:.0}%)",
+                count,
+                vote_type,
+                *count as f64 * 100.0 / total as f64
+            ))
+        })
+        .collect();
+    breakdown.join(", ")
+}
+
+// ===== Bracket Mode =====
+
+/// Shows two themes at a time and records which one the user prefers, until no pairs
+/// are left or they quit.
+async fn bracket_loop(auth: &mut Auth) -> anyhow::Result<()> {
+    let mut compared = 0;
+    loop {
+        println!("Fetching next pair...");
+        let response = auth
+            .retry(|token| async move { fetch_theme_pair(&token).await })
+            .await?;
+
+        if response.closed {
+            println!();
+            println!("{}", "🔒 Voting is closed.".yellow().bold());
+            println!();
+            break;
+        }
+        let Some([first, second]) = response.pair else {
+            println!();
+            if compared > 0 {
+                println!("{}", "🎉 You've compared every pair!".green().bold());
+            } else {
+                println!("{}", "📭 No pairs left to compare.".yellow().bold());
+                println!(
+                    "{}",
+                    "You've judged them all, or there aren't two themes yet.".bright_black()
+                );
+            }
+            println!();
+            break;
+        };
+
+        println!("{}", "━".repeat(60).bright_black());
+        println!();
+        println!(
+            "{} {}",
+            "Compared this session:".bright_black(),
+            compared.to_string().bright_cyan()
+        );
+        println!();
+        println!("{}", "[1]".bright_yellow().bold());
+        print_theme(&first);
+        println!();
+        println!("{}", "[2]".bright_yellow().bold());
+        print_theme(&second);
+        println!();
+        println!("{}", "Which is better? [1] / [2] / [Q]uit".bright_black());
+
+        let (winner, loser) = loop {
+            print!("{}", "> ".bright_green().bold());
+            io::stdout().flush()?;
+            let key = match input::read_key()? {
+                // Ctrl-C doesn't interrupt while a single key is read
+                None | Some('q' | 'Q' | '\u{3}') => {
+                    println!();
+                    println!();
+                    println!("{}", "Thanks for voting! 👋".bright_cyan().bold());
+                    return Ok(());
+                }
+                Some(key) => key,
+            };
+            println!("{key}");
+            match picked(key, &first, &second) {
+                Some(pick) => break pick,
+                None => println!("{}", "Press 1 or 2, or q to quit.".red()),
+            }
+        };
 
+        let (winner_id, loser_id) = (winner.id, loser.id);
+        match auth
+            .retry(|token| async move { submit_pair_vote(winner_id, loser_id, &token).await })
+            .await
+        {
+            Ok(()) => {
+                compared += 1;
+                println!("{} {}", "✓ Picked".green(), winner.content);
+            }
+            Err(e) if e.is::<AlreadyVoted>() => println!("{}", e.to_string().yellow()),
+            Err(e) => return Err(e),
+        }
+    }
     Ok(())
 }
 
-async fn show_results() -> anyhow::Result<()> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get(format!("{}/admin/stats", BACKEND_URL))
-        .send()
+/// The (winner, loser) a key press picks between the two themes shown, or `None` if
+/// it's neither choice.
+fn picked<T>(key: char, first: T, second: T) -> Option<(T, T)> {
+    match key {
+        '1' => Some((first, second)),
+        '2' => Some((second, first)),
+        _ => None,
+    }
+}
+
+// ===== Skip Review =====
+
+/// Walks through the user's skipped themes, oldest first, offering to turn each into a vote.
+async fn review_skips(
+    auth: &mut Auth,
+    config: &ServerConfig,
+    keys: &Keybindings,
+) -> anyhow::Result<()> {
+    if !config.allow_revote {
+        println!(
+            "{}",
+            "Votes are final on this server, so skips can't be changed.".yellow()
+        );
+        return Ok(());
+    }
+
+    let mut skips: Vec<MyVote> = auth
+        .retry(|token| async move { fetch_my_votes(&token).await })
         .await?
-        .json::<Vec<serde_json::Value>>()
-        .await?;
+        .into_iter()
+        .filter(|vote| vote.vote_type == "skip")
+        .collect();
+    skips.reverse();
+
+    if skips.is_empty() {
+        println!("{}", "You haven't skipped any themes. 🎉".green());
+        return Ok(());
+    }
+
+    // Any earlier unsent votes get sent along with the first change
+    let mut session = load_own_session(auth)?;
+    for vote in skips.clone() {
+        println!("{}", "━".repeat(60).bright_black());
+        println!();
+        println!(
+            "{} {}",
+            "Skipped themes left:".bright_black(),
+            skips.len().to_string().bright_cyan()
+        );
+        println!();
+        println!("{}", vote.content.bright_white().bold());
+        println!();
+
+        let vote_type = loop {
+            println!("{}", config.vote_prompt(keys).bright_black());
+            print!("{}", "> ".bright_green().bold());
+            io::stdout().flush()?;
+
+            let input = input::read_line()?;
+            match keys.parse(&input) {
+                Ok(Action::Quit) => return Ok(()),
+                Ok(action) => match action.vote_type() {
+                    Some(vote_type) if config.allows(vote_type) => break vote_type,
+                    _ => println!("{}", "Pick a vote, or quit to stop reviewing.".red()),
+                },
+                Err(hint) => println!("{}", hint.red()),
+            }
+        };
+
+        if vote_type == "skip" {
+            println!("{}", "→ Still skipped".yellow());
+            continue;
+        }
+        print!("{}", "Submitting… ".bright_black());
+        io::stdout().flush()?;
+        let changed = change_skip(&mut session, auth, &mut skips, vote.theme_id, vote_type).await?;
+        if input::discard_typed_ahead() > 0 {
+            print!("{}", "(ignored repeated input) ".bright_black());
+        }
+        if changed {
+            println!(
+                "{}",
+                format!("✓ Changed to {}", vote_type.to_uppercase()).green()
+            );
+        }
+    }
 
     println!();
+    println!(
+        "{}",
+        format!("Done reviewing, {} theme(s) still skipped.", skips.len()).bright_cyan()
+    );
+    Ok(())
+}
+
+/// Changes a skipped vote to `vote_type`, taking it off `skips` once it's sent or queued.
+/// If the server asks to slow down, the change is dropped and the theme stays skipped, so
+/// reviewing can go on with the next one. Returns whether the vote was changed.
+async fn change_skip(
+    session: &mut Session,
+    sender: &mut impl SendVote,
+    skips: &mut Vec<MyVote>,
+    theme_id: i64,
+    vote_type: &str,
+) -> anyhow::Result<bool> {
+    session.pending_votes.push(PendingVote {
+        theme_id,
+        vote_type: vote_type.to_string(),
+        reason: None,
+    });
+    session.save()?;
+
+    let flushed = flush_pending(session, sender).await?;
+    let queued = session
+        .pending_votes
+        .iter()
+        .rposition(|pending| pending.theme_id == theme_id);
+    match queued {
+        Some(queued) if flushed.throttled => {
+            session.pending_votes.remove(queued);
+            session.save()?;
+            println!(
+                "{}",
+                "⏳ The server asked us to slow down, so this one stays skipped for now.".yellow()
+            );
+            return Ok(false);
+        }
+        Some(_) => println!(
+            "{}",
+            format!(
+                "📥 No connection, saved for later ({} queued)",
+                session.pending_votes.len()
+            )
+            .yellow()
+        ),
+        None => {}
+    }
+    skips.retain(|skip| skip.theme_id != theme_id);
+    Ok(true)
+}
+
+// ===== Personal Stats =====
+
+const RECENT_VOTES_SHOWN: usize = 5;
+
+async fn show_my_stats(auth: &mut Auth) -> anyhow::Result<()> {
+    let votes = auth
+        .retry(|token| async move { fetch_my_votes(&token).await })
+        .await?;
+    let progress = auth
+        .retry(|token| async move { fetch_progress(&token).await })
+        .await?;
+
     println!("{}", "=".repeat(60).bright_cyan());
-    println!("{}", "    📊 VOTING RESULTS".bright_yellow().bold());
+    println!("{}", "    🗳️  YOUR VOTES".bright_yellow().bold());
     println!("{}", "=".repeat(60).bright_cyan());
     println!();
 
-    for (i, theme) in response.iter().enumerate().take(10) {
-        let content = theme["content"].as_str().unwrap_or("Unknown");
-        let yes = theme["yes_votes"].as_i64().unwrap_or(0);
-        let no = theme["no_votes"].as_i64().unwrap_or(0);
-        let total = theme["total_votes"].as_i64().unwrap_or(0);
-
+    if votes.is_empty() {
+        println!("{}", "You haven't voted on any themes yet.".yellow());
         println!(
-            "{}. {} ({} votes: {} yes, {} no)",
-            (i + 1).to_string().bright_cyan(),
-            content.bright_white().bold(),
-            total.to_string().yellow(),
-            yes.to_string().green(),
-            no.to_string().red()
+            "{}",
+            "Run the client without a command to start voting!".bright_black()
         );
+        println!();
+        return Ok(());
+    }
+
+    let counts = VoteCounts::of(&votes);
+    println!(
+        "{} {}/{} themes ({:.0}%)",
+        "Covered:".bright_black(),
+        progress.seen.to_string().bright_cyan(),
+        progress.total.to_string().bright_cyan(),
+        progress.percent
+    );
+    println!(
+        "{} {} yes, {} maybe, {} no, {} skipped",
+        "Votes:".bright_black(),
+        counts.yes.to_string().green(),
+        counts.maybe.to_string().blue(),
+        counts.no.to_string().red(),
+        counts.skip.to_string().yellow()
+    );
+
+    // Only worth a breakdown once themes are actually tagged
+    let remaining = auth
+        .retry(|token| async move { fetch_remaining_by_tag(&token).await })
+        .await?;
+    if remaining.iter().any(|r| r.tag.is_some()) {
+        let left: Vec<String> = remaining
+            .iter()
+            .map(|r| {
+                format!(
+                    "{} ({} left)",
+                    r.tag.as_deref().unwrap_or("untagged"),
+                    r.remaining
+                )
+            })
+            .collect();
+        println!("{} {}", "By tag:".bright_black(), left.join(", "));
+    }
+    println!();
+
+    println!("{}", "Most recent:".bright_white().bold());
+    for vote in votes.iter().take(RECENT_VOTES_SHOWN) {
+        let label = match vote.vote_type.as_str() {
+            "yes" => "YES  ".green(),
+            "no" => "NO   ".red(),
+            "maybe" => "MAYBE".blue(),
+            _ => "SKIP ".yellow(),
+        };
+        println!("  {} {}", label, vote.content.bright_white());
     }
 
     println!();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_bell_rings_only_when_enabled_on_a_terminal() {
+        for (enabled, terminal, rung) in [
+            (true, true, true),
+            (true, false, false),
+            (false, true, false),
+            (false, false, false),
+        ] {
+            let mut out = Vec::new();
+            write_bell(&mut out, enabled, terminal).unwrap();
+            assert_eq!(
+                out == b"\x07",
+                rung,
+                "enabled: {enabled}, terminal: {terminal}"
+            );
+            assert_eq!(out.is_empty(), !rung);
+        }
+    }
+
+    #[test]
+    fn auth_timeout_is_parsed_and_must_be_positive() {
+        let timeout = |args: &[&str]| {
+            Cli::try_parse_from(["client"].iter().chain(args)).map(|cli| cli.auth_timeout)
+        };
+        assert_eq!(timeout(&[]).unwrap(), 120);
+        assert_eq!(timeout(&["--auth-timeout", "5"]).unwrap(), 5);
+        assert!(timeout(&["--auth-timeout", "0"]).is_err());
+        assert!(timeout(&["--auth-timeout", "soon"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn login_gives_up_after_a_short_timeout() {
+        let token_store = Mutex::new(None);
+        let started = tokio::time::Instant::now();
+        let err = wait_for_token(&token_store, tokio::time::Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timeout"), "{err}");
+        assert!(started.elapsed() < tokio::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn login_returns_a_token_that_arrives_in_time() {
+        let token_store = Mutex::new(Some("token".to_string()));
+        let token = wait_for_token(&token_store, tokio::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(token, "token");
+    }
+
+    #[test]
+    fn vote_counts_match_a_themes_mine_response() {
+        let response = r#"[
+            {"theme_id": 1, "content": "Bees", "vote_type": "yes", "voted_at": "2026-10-16T10:00:00Z"},
+            {"theme_id": 2, "content": "Wasps", "vote_type": "skip"},
+            {"theme_id": 3, "content": "Ants", "vote_type": "yes"},
+            {"theme_id": 4, "content": "Moths", "vote_type": "no"},
+            {"theme_id": 5, "content": "Flies", "vote_type": "maybe"}
+        ]"#;
+        let votes: Vec<MyVote> = serde_json::from_str(response).unwrap();
+        assert_eq!(
+            VoteCounts::of(&votes),
+            VoteCounts {
+                yes: 2,
+                maybe: 1,
+                no: 1,
+                skip: 1,
+            }
+        );
+        assert_eq!(VoteCounts::of(&[]), VoteCounts::default());
+    }
+
+    #[test]
+    fn search_terms_are_url_encoded() {
+        let request = search_request(&reqwest::Client::new(), "cats & dogs #1?")
+            .build()
+            .unwrap();
+        assert_eq!(request.url().path(), "/themes");
+        assert_eq!(request.url().query(), Some("search=cats+%26+dogs+%231%3F"));
+        let pairs: Vec<_> = request.url().query_pairs().collect();
+        assert_eq!(pairs, [("search".into(), "cats & dogs #1?".into())]);
+    }
+
+    #[test]
+    fn votes_say_which_client_version_cast_them() {
+        let client = reqwest::Client::new();
+        let vote = VoteRequest {
+            theme_id: 7,
+            vote_type: "yes".into(),
+            reason: None,
+        };
+        let requests = [
+            vote_request(&client, &vote, "token").build().unwrap(),
+            pair_vote_request(&client, 7, 8, "token").build().unwrap(),
+        ];
+        for request in requests {
+            assert_eq!(
+                request.headers()["X-Client-Version"],
+                env!("CARGO_PKG_VERSION"),
+                "{}",
+                request.url()
+            );
+            assert_eq!(request.headers()["Authorization"], "Bearer token");
+        }
+    }
+
+    #[test]
+    fn markdown_table_has_a_header_and_a_row_per_theme() {
+        let results = [serde_json::json!({
+            "content": "Bees",
+            "yes_votes": 3,
+            "no_votes": 1,
+            "skip_votes": 2,
+            "maybe_votes": 0,
+            "total_votes": 6,
+        })];
+        let table = markdown_table(&results);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "| # | Theme | Yes | Maybe | No | Skip | Total | Yes % |",
+                "|---:|---|---:|---:|---:|---:|---:|---:|",
+                "| 1 | Bees | 3 | 0 | 1 | 2 | 6 | 75% |",
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_table_escapes_pipes_in_themes() {
+        let results = [serde_json::json!({"content": "Us | Them"})];
+        let table = markdown_table(&results);
+        assert_eq!(
+            table.lines().last(),
+            Some(r"| 1 | Us \| Them | 0 | 0 | 0 | 0 | 0 | - |")
+        );
+    }
+
+    /// Pages through `total` fake results, returning the offsets asked for and, for each
+    /// page handed on, whether it was marked as the last.
+    async fn pages_fetched(
+        total: usize,
+        page_size: usize,
+        pages_wanted: usize,
+    ) -> (Vec<usize>, Vec<bool>) {
+        let mut offsets = Vec::new();
+        let mut lasts = Vec::new();
+        for_each_page(
+            page_size,
+            |limit, offset| {
+                offsets.push(offset);
+                let page = (offset..total.min(offset + limit))
+                    .map(|i| serde_json::json!({ "id": i }))
+                    .collect();
+                async move { Ok(page) }
+            },
+            |_, _, last| {
+                lasts.push(last);
+                Ok(lasts.len() < pages_wanted)
+            },
+        )
+        .await
+        .unwrap();
+        (offsets, lasts)
+    }
+
+    async fn offsets_fetched(total: usize, page_size: usize, pages_wanted: usize) -> Vec<usize> {
+        pages_fetched(total, page_size, pages_wanted).await.0
+    }
+
+    #[tokio::test]
+    async fn paging_requests_successive_offsets_and_stops_at_the_end() {
+        assert_eq!(offsets_fetched(5, 2, usize::MAX).await, [0, 2, 4]);
+        // A full last page can't tell it's the last, so one empty page follows
+        assert_eq!(offsets_fetched(4, 2, usize::MAX).await, [0, 2, 4]);
+        assert_eq!(offsets_fetched(0, 2, usize::MAX).await, [0]);
+
+        // Only the short page is the last, so no "more results" prompt follows it
+        assert_eq!(
+            pages_fetched(5, 2, usize::MAX).await.1,
+            [false, false, true]
+        );
+        assert_eq!(pages_fetched(3, 5, usize::MAX).await.1, [true]);
+        // The empty page after a full one isn't shown at all
+        assert_eq!(pages_fetched(4, 2, usize::MAX).await.1, [false, false]);
+    }
+
+    #[tokio::test]
+    async fn paging_stops_when_the_user_has_seen_enough() {
+        assert_eq!(offsets_fetched(10, 2, 2).await, [0, 2]);
+    }
+
+    /// Stands in for the server, answering each vote with the next queued outcome
+    /// (success once they run out).
+    #[derive(Default)]
+    struct FakeServer {
+        received: Vec<i64>,
+        /// Each theme's vote as last received
+        votes: HashMap<i64, String>,
+        outcomes: Vec<anyhow::Result<()>>,
+    }
+
+    impl SendVote for FakeServer {
+        async fn send_vote(&mut self, vote: PendingVote) -> anyhow::Result<()> {
+            if !self.outcomes.is_empty() {
+                self.outcomes.remove(0)?;
+            }
+            self.received.push(vote.theme_id);
+            self.votes.insert(vote.theme_id, vote.vote_type);
+            Ok(())
+        }
+    }
+
+    fn pending(theme_ids: &[i64]) -> Session {
+        Session {
+            pending_votes: theme_ids
+                .iter()
+                .map(|&theme_id| PendingVote {
+                    theme_id,
+                    vote_type: "yes".to_string(),
+                    reason: None,
+                })
+                .collect(),
+            ..Session::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn resuming_flushes_saved_votes_in_order() {
+        let saved = serde_json::to_string(&pending(&[3, 1])).unwrap();
+        let mut resumed: Session = serde_json::from_str(&saved).unwrap();
+        let mut server = FakeServer::default();
+
+        let flushed = flush_pending(&mut resumed, &mut server).await.unwrap();
+        assert_eq!(server.received, [3, 1]);
+        assert_eq!(flushed.sent, [3, 1]);
+        assert!(resumed.pending_votes.is_empty());
+    }
+
+    #[test]
+    fn results_page_size_comes_from_config_unless_top_is_given() {
+        let config: ServerConfig = serde_json::from_str(r#"{"page_size": 25}"#).unwrap();
+
+        let default = Cli::try_parse_from(["client", "results"]).unwrap();
+        assert_eq!(config.results_page_size(&default), 25);
+
+        let top = Cli::try_parse_from(["client", "results", "--top", "5"]).unwrap();
+        assert_eq!(config.results_page_size(&top), 5);
+    }
+
+    /// Answers 401 until the token is "fresh", counting the logins it took.
+    async fn run_with_expired_token(always_rejected: bool) -> (anyhow::Result<String>, u32) {
+        let mut token = "expired".to_string();
+        let mut logins = 0;
+        let result = retry_with_login(
+            &mut token,
+            |token| async move {
+                if always_rejected || token != "fresh" {
+                    Err(Unauthorized.into())
+                } else {
+                    Ok(token)
+                }
+            },
+            || {
+                logins += 1;
+                async { Ok("fresh".to_string()) }
+            },
+        )
+        .await;
+        (result, logins)
+    }
+
+    #[tokio::test]
+    async fn an_expired_token_is_renewed_and_the_request_retried() {
+        let (result, logins) = run_with_expired_token(false).await;
+        assert_eq!(result.unwrap(), "fresh");
+        assert_eq!(logins, 1);
+    }
+
+    #[tokio::test]
+    async fn logging_in_again_gives_up_after_a_few_tries() {
+        let (result, logins) = run_with_expired_token(true).await;
+        assert!(result.unwrap_err().is::<Unauthorized>());
+        assert_eq!(logins, MAX_REAUTH_ATTEMPTS);
+    }
+
+    #[test]
+    fn only_the_undo_key_within_the_window_takes_a_vote_back() {
+        let keys = Keybindings::default();
+        assert!(is_undo(Some("u"), &keys));
+        assert!(is_undo(Some(" undo "), &keys));
+        // The window ran out, or something else was typed: the vote goes through
+        assert!(!is_undo(None, &keys));
+        assert!(!is_undo(Some("y"), &keys));
+        assert!(!is_undo(Some(""), &keys));
+    }
+
+    fn themes(contents: &[&str]) -> Vec<Theme> {
+        contents
+            .iter()
+            .zip(1..)
+            .map(|(content, id)| Theme {
+                id,
+                content: content.to_string(),
+                description: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn vote_on_settles_on_an_exact_or_only_match() {
+        let mut exact = themes(&["Bees and knees", "bees", "Killer bees"]);
+        assert_eq!(settled_match(&mut exact, "Bees").map(|t| t.id), Some(2));
+
+        let mut only = themes(&["Killer bees"]);
+        assert_eq!(settled_match(&mut only, "bee").map(|t| t.id), Some(1));
+
+        // Several partial matches are left for the user to choose from
+        let mut several = themes(&["Bees and knees", "Killer bees"]);
+        assert!(settled_match(&mut several, "bee").is_none());
+        assert_eq!(several.len(), 2);
+    }
+
+    #[test]
+    fn vote_on_submits_the_settled_themes_id() {
+        let mut exact = themes(&["Bees and knees", "bees", "Killer bees"]);
+        let theme = settled_match(&mut exact, "Bees").unwrap();
+        let vote = VoteRequest {
+            theme_id: theme.id,
+            vote_type: "yes".into(),
+            reason: None,
+        };
+        let request = vote_request(&reqwest::Client::new(), &vote, "token")
+            .build()
+            .unwrap();
+        let body: serde_json::Value =
+            serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body["theme_id"], 2);
+        assert_eq!(body["vote_type"], "yes");
+    }
+
+    /// Runs the end-of-session menu with scripted `answers`, returning the choice and how
+    /// many questions were asked.
+    fn end_menu(config: &ServerConfig, offer_change: bool, answers: &[&str]) -> (EndChoice, usize) {
+        let mut answers = answers.iter();
+        let mut asked = 0;
+        let choice = end_of_session_choice(config, offer_change, |_| {
+            asked += 1;
+            Ok(answers.next().unwrap().to_string())
+        })
+        .unwrap();
+        (choice, asked)
+    }
+
+    #[test]
+    fn end_of_session_menu_branches_on_the_answers_and_settings() {
+        let config = ServerConfig::default();
+        assert_eq!(end_menu(&config, true, &["y"]), (EndChoice::ChangeVote, 1));
+        assert_eq!(end_menu(&config, true, &["", ""]), (EndChoice::Results, 2));
+        assert_eq!(end_menu(&config, true, &["n", "N"]), (EndChoice::Done, 2));
+        // Re-voting off, or already declined: straight to results
+        assert_eq!(end_menu(&config, false, &["yes"]), (EndChoice::Results, 1));
+
+        let hidden = ServerConfig {
+            results_hidden: true,
+            ..ServerConfig::default()
+        };
+        assert_eq!(
+            end_menu(&hidden, true, &["n"]),
+            (EndChoice::ResultsHidden, 1)
+        );
+        assert_eq!(end_menu(&hidden, false, &[]), (EndChoice::ResultsHidden, 0));
+    }
+
+    #[tokio::test]
+    async fn watching_polls_on_the_interval_and_redraws_only_changes() {
+        const INTERVAL: Duration = Duration::from_millis(40);
+        let pages = [
+            serde_json::json!([{ "theme_id": 1, "yes_votes": 1 }]),
+            serde_json::json!([{ "theme_id": 1, "yes_votes": 1 }]),
+            serde_json::json!([{ "theme_id": 1, "yes_votes": 2 }]),
+        ];
+        let done = Arc::new(tokio::sync::Notify::new());
+        let mut fetched_at = Vec::new();
+        let mut redrawn = Vec::new();
+
+        let notify = done.clone();
+        poll_results(
+            INTERVAL,
+            async move { done.notified().await },
+            || {
+                fetched_at.push(Instant::now());
+                let page = pages[fetched_at.len() - 1].as_array().unwrap().clone();
+                if fetched_at.len() == pages.len() {
+                    notify.notify_one();
+                }
+                async move { Ok(page) }
+            },
+            |page| {
+                redrawn.push(page[0]["yes_votes"].clone());
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fetched_at.len(), 3);
+        for pair in fetched_at.windows(2) {
+            assert!(
+                pair[1] - pair[0] >= INTERVAL * 3 / 4,
+                "{:?}",
+                pair[1] - pair[0]
+            );
+        }
+        // The unchanged second page isn't drawn again
+        assert_eq!(redrawn, [1, 2]);
+    }
+
+    #[test]
+    fn the_auth_url_names_the_selected_provider() {
+        let provider = |args: &[&str]| {
+            Cli::try_parse_from(["client"].iter().chain(args)).map(|cli| cli.provider)
+        };
+
+        let url = auth_url("https://x.supabase.co", provider(&[]).unwrap());
+        assert!(
+            url.starts_with("https://x.supabase.co/auth/v1/authorize?provider=discord&"),
+            "{url}"
+        );
+
+        let github = provider(&["--provider", "github"]).unwrap();
+        assert!(auth_url("https://x.supabase.co", github).contains("provider=github&"));
+        assert!(provider(&["--provider", "myspace"]).is_err());
+    }
+
+    /// The error sending a vote gives without a connection.
+    async fn offline() -> anyhow::Error {
+        // Nothing listens on port 1
+        reqwest::get("http://127.0.0.1:1").await.unwrap_err().into()
+    }
+
+    #[tokio::test]
+    async fn votes_queued_while_offline_are_sent_once_back_online() {
+        assert!(is_offline(&offline().await));
+        let mut session = pending(&[1, 2, 3]);
+        let mut server = FakeServer {
+            outcomes: vec![Ok(()), Err(offline().await)],
+            ..FakeServer::default()
+        };
+
+        // The connection drops after the first vote: the rest stay queued
+        let flushed = flush_pending(&mut session, &mut server).await.unwrap();
+        assert_eq!(flushed.sent, [1]);
+        assert!(!flushed.throttled);
+        let queued: Vec<i64> = session.pending_votes.iter().map(|v| v.theme_id).collect();
+        assert_eq!(queued, [2, 3]);
+
+        // Back online, each vote is sent exactly once
+        let flushed = flush_pending(&mut session, &mut server).await.unwrap();
+        assert_eq!(flushed.sent, [2, 3]);
+        assert_eq!(server.received, [1, 2, 3]);
+        assert!(session.pending_votes.is_empty());
+    }
+
+    #[test]
+    fn a_key_press_picks_the_winner_of_a_pair() {
+        assert_eq!(picked('1', "first", "second"), Some(("first", "second")));
+        // The second theme wins, so the first one loses
+        assert_eq!(picked('2', "first", "second"), Some(("second", "first")));
+        assert_eq!(picked('3', "first", "second"), None);
+        assert_eq!(picked('\n', "first", "second"), None);
+    }
+
+    fn skipped(theme_ids: &[i64]) -> Vec<MyVote> {
+        theme_ids
+            .iter()
+            .map(|&theme_id| MyVote {
+                theme_id,
+                content: format!("Theme {theme_id}"),
+                vote_type: "skip".to_string(),
+            })
+            .collect()
+    }
+
+    fn skipped_ids(skips: &[MyVote]) -> Vec<i64> {
+        skips.iter().map(|skip| skip.theme_id).collect()
+    }
+
+    #[tokio::test]
+    async fn changing_a_skip_updates_the_vote_and_takes_it_off_the_list() {
+        let mut session = Session::default();
+        let mut server = FakeServer::default();
+        let mut skips = skipped(&[1, 2]);
+
+        let changed = change_skip(&mut session, &mut server, &mut skips, 1, "yes")
+            .await
+            .unwrap();
+        assert!(changed);
+        assert_eq!(server.votes[&1], "yes");
+        assert_eq!(skipped_ids(&skips), [2]);
+        assert!(session.pending_votes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_change_made_too_soon_leaves_that_theme_skipped_and_goes_on() {
+        let mut session = Session::default();
+        let mut server = FakeServer {
+            outcomes: vec![Err(ChangedTooSoon.into())],
+            ..FakeServer::default()
+        };
+        let mut skips = skipped(&[1, 2]);
+
+        let changed = change_skip(&mut session, &mut server, &mut skips, 1, "yes")
+            .await
+            .unwrap();
+        assert!(!changed);
+        assert_eq!(skipped_ids(&skips), [1, 2]);
+        // Dropped rather than sent along with the next change
+        assert!(session.pending_votes.is_empty());
+
+        let changed = change_skip(&mut session, &mut server, &mut skips, 2, "no")
+            .await
+            .unwrap();
+        assert!(changed);
+        assert_eq!(server.received, [2]);
+        assert_eq!(skipped_ids(&skips), [1]);
+    }
+
+    /// Whether voting with `args` fetches the tally, and whether it's then there to show.
+    async fn tally_fetched(args: &[&str], config: &ServerConfig) -> (bool, bool) {
+        let cli = Cli::try_parse_from(["client"].iter().chain(args)).unwrap();
+        let mut fetched = false;
+        let tally = shown_tally(&cli, config, || {
+            fetched = true;
+            async { Ok(Some(serde_json::json!({ "total_votes": 3 }))) }
+        })
+        .await;
+        (fetched, tally.is_some())
+    }
+
+    #[tokio::test]
+    async fn the_tally_is_fetched_only_with_show_tally() {
+        let config = ServerConfig::default();
+        assert_eq!(tally_fetched(&[], &config).await, (false, false));
+        assert_eq!(
+            tally_fetched(&["--show-tally"], &config).await,
+            (true, true)
+        );
+
+        // Never while the server hides results
+        let hidden = ServerConfig {
+            results_hidden: true,
+            ..ServerConfig::default()
+        };
+        assert_eq!(
+            tally_fetched(&["--show-tally"], &hidden).await,
+            (false, false)
+        );
+    }
+
+    /// `line` without its color codes, as it reads on screen.
+    fn plain(line: &str) -> String {
+        let mut text = String::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                text.push(c);
+            }
+        }
+        text
+    }
+
+    fn tally(content: &str, yes: i64, no: i64, maybe: i64, skip: i64) -> ThemeTally {
+        ThemeTally {
+            content: content.to_string(),
+            yes_votes: yes,
+            no_votes: no,
+            skip_votes: skip,
+            maybe_votes: maybe,
+            total_votes: yes + no + maybe + skip,
+        }
+    }
+
+    #[test]
+    fn the_compare_request_names_both_themes() {
+        let request = compare_request(&reqwest::Client::new(), 3, 9, "secret")
+            .build()
+            .unwrap();
+        assert_eq!(request.url().path(), "/admin/compare");
+        let pairs: Vec<_> = request.url().query_pairs().collect();
+        assert_eq!(pairs, [("a".into(), "3".into()), ("b".into(), "9".into())]);
+        assert_eq!(request.headers()["Authorization"], "Bearer secret");
+    }
+
+    #[test]
+    fn vote_bars_fill_in_proportion_to_the_votes() {
+        assert_eq!(plain(&vote_bar(&tally("", 0, 0, 0, 0), 4)), "░░░░");
+        // Every vote counts toward the bar, so it's always full once there are any
+        assert_eq!(plain(&vote_bar(&tally("", 1, 2, 0, 1), 8)), "████████");
+        assert_eq!(
+            plain(&vote_bar(&tally("", 3, 0, 0, 0), COMPARE_COLUMN))
+                .chars()
+                .count(),
+            COMPARE_COLUMN
+        );
+    }
+
+    #[test]
+    fn comparisons_put_the_themes_side_by_side() {
+        let comparison = Comparison {
+            a: tally("Bees", 3, 1, 0, 0),
+            b: tally("Wasps", 1, 2, 1, 0),
+            voted_both: 3,
+            preferred_a: 1,
+            preferred_b: 1,
+            same: 0,
+            no_opinion: 1,
+        };
+        let lines: Vec<String> = comparison_lines(4, 7, &comparison)
+            .iter()
+            .map(|line| plain(line))
+            .collect();
+
+        let column = |text: &str| format!("{text:<width$}  │  ", width = COMPARE_COLUMN);
+        assert!(lines.contains(&format!("{}#7", column("#4"))), "{lines:#?}");
+        assert!(lines.contains(&format!("{}Wasps", column("Bees"))));
+        assert!(lines.contains(&format!("{}Yes   1", column("Yes   3"))));
+        assert!(lines.contains(&format!("{}Maybe 1", column("Maybe 0"))));
+        assert!(lines.contains(&format!("{}Total 4", column("Total 4"))));
+        assert!(lines.contains(&"Voters who voted on both: 3".to_string()));
+        assert!(
+            lines.contains(&"preferred #4: 1   no preference: 0   preferred #7: 1".to_string())
+        );
+        assert!(lines.contains(&"skipped one of them: 1".to_string()));
+
+        let unshared = Comparison {
+            voted_both: 0,
+            preferred_a: 0,
+            preferred_b: 0,
+            no_opinion: 0,
+            ..comparison
+        };
+        let lines: Vec<String> = comparison_lines(4, 7, &unshared)
+            .iter()
+            .map(|line| plain(line))
+            .collect();
+        assert!(lines.contains(&"Nobody has voted on both themes yet.".to_string()));
+        assert!(!lines.iter().any(|line| line.starts_with("preferred")));
+    }
+
+    #[test]
+    fn the_speed_summary_breaks_votes_down_by_share() {
+        let tally = HashMap::from([("skip", 1), ("yes", 2), ("no", 3)]);
+        // Always in the prompt's order, rounded to whole percents
+        assert_eq!(
+            speed_breakdown(&tally),
+            "2 yes (33%), 3 no (50%), 1 skip (17%)"
+        );
+        assert_eq!(
+            speed_breakdown(&HashMap::from([("maybe", 4)])),
+            "4 maybe (100%)"
+        );
+    }
+}
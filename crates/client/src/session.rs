@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+use crate::Theme;
+
+/// A vote the user made that hasn't been confirmed by the server yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingVote {
+    pub theme_id: i64,
+    pub vote_type: String,
+    pub reason: Option<String>,
+}
+
+/// What's needed to pick a voting session back up after a crash or an early quit.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    /// Who the queued votes belong to, so another account never sends them
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub pending_votes: Vec<PendingVote>,
+    pub last_theme: Option<Theme>,
+}
+
+impl Session {
+    fn path() -> Option<PathBuf> {
+        // Tests never touch the user's real session
+        if cfg!(test) {
+            return None;
+        }
+        Some(
+            dirs::config_dir()?
+                .join("slaughter_vote")
+                .join("session.json"),
+        )
+    }
+
+    /// The saved session, if there is one worth resuming.
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(Self::path()?).ok()?;
+        let session: Self = serde_json::from_str(&content).ok()?;
+        (!session.pending_votes.is_empty() || session.last_theme.is_some()).then_some(session)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Makes the session `user_id`'s, dropping anything another account left behind.
+    /// Returns how many queued votes were dropped. Sessions saved before owners were
+    /// recorded are taken to be the current user's.
+    pub fn claim(&mut self, user_id: &str) -> usize {
+        let mut dropped = 0;
+        if self
+            .user_id
+            .as_deref()
+            .is_some_and(|owner| owner != user_id)
+        {
+            dropped = self.pending_votes.len();
+            self.pending_votes.clear();
+            self.last_theme = None;
+        }
+        self.user_id = Some(user_id.to_string());
+        dropped
+    }
+
+    /// Forgets the saved session, once voting ended cleanly.
+    pub fn clear(&mut self) -> io::Result<()> {
+        *self = Self::default();
+        match Self::path().map(fs::remove_file) {
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_of(user_id: Option<&str>) -> Session {
+        Session {
+            user_id: user_id.map(String::from),
+            pending_votes: vec![PendingVote {
+                theme_id: 1,
+                vote_type: "yes".to_string(),
+                reason: None,
+            }],
+            last_theme: None,
+        }
+    }
+
+    #[test]
+    fn another_users_votes_are_dropped() {
+        let mut session = session_of(Some("alice"));
+        assert_eq!(session.claim("bob"), 1);
+        assert!(session.pending_votes.is_empty());
+        assert_eq!(session.user_id.as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn own_and_unowned_votes_are_kept() {
+        let mut own = session_of(Some("alice"));
+        assert_eq!(own.claim("alice"), 0);
+        assert_eq!(own.pending_votes.len(), 1);
+
+        let mut unowned = session_of(None);
+        assert_eq!(unowned.claim("alice"), 0);
+        assert_eq!(unowned.pending_votes.len(), 1);
+        assert_eq!(unowned.user_id.as_deref(), Some("alice"));
+    }
+}
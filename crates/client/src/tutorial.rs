@@ -0,0 +1,129 @@
+use colored::*;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::ServerConfig;
+use crate::input::{self, Action, Keybindings};
+
+/// Left in the config directory once the tutorial has been shown, so it only shows once.
+fn marker_path() -> Option<PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join("slaughter_vote")
+            .join("tutorial_seen"),
+    )
+}
+
+/// Whether to walk the user through voting: on their first run, or whenever asked.
+pub fn should_show(requested: bool) -> bool {
+    show_with_marker(requested, marker_path().as_deref())
+}
+
+/// [`should_show`] with the marker at `marker`, so it can be tried without touching the
+/// user's config directory. Without one there's nowhere to remember the first run, so the
+/// tutorial only shows when asked.
+fn show_with_marker(requested: bool, marker: Option<&Path>) -> bool {
+    requested || marker.is_some_and(|path| !path.exists())
+}
+
+/// Explains the prompt before the first theme, then waits for Enter.
+pub fn show(config: &ServerConfig, keys: &Keybindings) -> io::Result<()> {
+    println!("{}", "🎓 HOW VOTING WORKS".bright_yellow().bold());
+    println!();
+    println!("You'll see one theme at a time. Answer with one of these keys:");
+    println!();
+
+    let meanings = [
+        (Action::Yes, "you'd be happy to make a game on this theme"),
+        (Action::No, "you'd rather not; no votes count against it"),
+        (Action::Maybe, "interested, but not sure yet"),
+        (
+            Action::Skip,
+            "no opinion, or it's unclear; it counts neither way",
+        ),
+    ];
+    for (action, meaning) in meanings {
+        if action.vote_type().is_some_and(|t| config.allows(t)) {
+            let label = format!("{:<12}", keys.label(action));
+            println!("  {} {}", label.bright_cyan(), meaning);
+        }
+    }
+    println!();
+    println!("Themes with the most yes votes rise to the top and become the finalists.");
+    if config.results_hidden {
+        println!("Results are hidden until voting closes.");
+    } else {
+        println!(
+            "Press {} at any prompt to see how themes are doing so far.",
+            keys.key(Action::Results).bright_cyan()
+        );
+    }
+    println!(
+        "Press {} to stop; you can pick up where you left off next time.",
+        keys.key(Action::Quit).bright_cyan()
+    );
+    println!();
+    print!("{}", "[Enter] to start voting > ".bright_green().bold());
+    io::stdout().flush()?;
+    input::read_line()?;
+    println!();
+
+    mark_seen()
+}
+
+fn mark_seen() -> io::Result<()> {
+    match marker_path() {
+        Some(path) => mark_seen_at(&path),
+        None => Ok(()),
+    }
+}
+
+fn mark_seen_at(marker: &Path) -> io::Result<()> {
+    if let Some(dir) = marker.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(marker, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// A marker path in a fresh directory that doesn't exist yet.
+    fn fresh_marker(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "slaughter_vote_tutorial_{}_{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("tutorial_seen")
+    }
+
+    #[test]
+    fn the_tutorial_shows_on_the_first_run_only() {
+        let marker = fresh_marker("first_run");
+        assert!(show_with_marker(false, Some(&marker)));
+
+        mark_seen_at(&marker).unwrap();
+        assert!(!show_with_marker(false, Some(&marker)));
+        fs::remove_dir_all(marker.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn asking_for_the_tutorial_always_shows_it() {
+        let marker = fresh_marker("requested");
+        mark_seen_at(&marker).unwrap();
+        assert!(show_with_marker(true, Some(&marker)));
+        assert!(show_with_marker(true, None));
+        fs::remove_dir_all(marker.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn without_a_config_directory_the_tutorial_waits_to_be_asked() {
+        assert!(!show_with_marker(false, None));
+    }
+}
@@ -0,0 +1,2 @@
+-- Soft delete: archived themes leave the voting pool but keep their votes
+ALTER TABLE themes ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
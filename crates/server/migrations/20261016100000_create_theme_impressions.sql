@@ -0,0 +1,9 @@
+-- One row per (user, theme) the first time the theme is served to that user
+CREATE TABLE IF NOT EXISTS theme_impressions (
+    user_id TEXT NOT NULL,
+    theme_id INTEGER NOT NULL REFERENCES themes(id),
+    first_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, theme_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_theme_impressions_theme_id ON theme_impressions(theme_id);
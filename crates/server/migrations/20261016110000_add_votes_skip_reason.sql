@@ -0,0 +1,2 @@
+-- Optional free-text reason attached to skip votes
+ALTER TABLE votes ADD COLUMN IF NOT EXISTS skip_reason TEXT;
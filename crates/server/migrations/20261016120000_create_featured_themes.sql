@@ -0,0 +1,6 @@
+-- One featured theme per day
+CREATE TABLE IF NOT EXISTS featured_themes (
+    feature_date DATE PRIMARY KEY,
+    theme_id INTEGER NOT NULL REFERENCES themes(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Audit trail of theme content changes
+CREATE TABLE IF NOT EXISTS theme_edits (
+    id SERIAL PRIMARY KEY,
+    theme_id INTEGER NOT NULL REFERENCES themes(id),
+    previous_content TEXT NOT NULL,
+    new_content TEXT NOT NULL,
+    editor_user_id TEXT NOT NULL,
+    edited_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_theme_edits_theme_id ON theme_edits(theme_id);
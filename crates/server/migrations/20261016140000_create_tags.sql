@@ -0,0 +1,13 @@
+-- Free-form labels, a theme can carry any number of them
+CREATE TABLE IF NOT EXISTS tags (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS theme_tags (
+    theme_id INTEGER NOT NULL REFERENCES themes(id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (theme_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_theme_tags_tag ON theme_tags(tag_id);
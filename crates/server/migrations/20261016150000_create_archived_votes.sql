@@ -0,0 +1,13 @@
+-- Votes cleared by an admin reset, kept for the record
+CREATE TABLE IF NOT EXISTS archived_votes (
+    id INTEGER NOT NULL,
+    user_id TEXT NOT NULL,
+    theme_id INTEGER NOT NULL REFERENCES themes(id),
+    vote_type TEXT NOT NULL,
+    skip_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    archived_by TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_archived_votes_theme_id ON archived_votes(theme_id);
@@ -0,0 +1,6 @@
+-- The theme last served to each user, re-served until they vote on it
+CREATE TABLE IF NOT EXISTS pending_themes (
+    user_id TEXT PRIMARY KEY,
+    theme_id INTEGER NOT NULL REFERENCES themes(id) ON DELETE CASCADE,
+    served_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
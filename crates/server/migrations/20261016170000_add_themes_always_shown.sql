@@ -0,0 +1,2 @@
+-- Admin override: keep serving a theme even if it looks low quality
+ALTER TABLE themes ADD COLUMN IF NOT EXISTS always_shown BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Newest-first paging through the recent votes feed
+CREATE INDEX IF NOT EXISTS idx_votes_created_at_id ON votes(created_at DESC, id DESC);
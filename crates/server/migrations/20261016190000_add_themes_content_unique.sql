@@ -0,0 +1,62 @@
+-- Theme content is normalized before it's stored, so a plain unique index is enough to
+-- stop concurrent creates (or edits) from producing two identical themes.
+--
+-- Rows stored before normalization get the same treatment first (NFC, whitespace runs
+-- to one space, zero-width and control characters dropped), and copies that turn out
+-- identical are folded into one: the oldest live copy, or the oldest if all are deleted.
+UPDATE themes SET content = btrim(regexp_replace(
+    regexp_replace(
+        regexp_replace(
+            -- normalize() only works on UTF8 databases (as Supabase's are)
+            CASE WHEN current_setting('server_encoding') = 'UTF8'
+                THEN normalize(content, NFC) ELSE content END,
+            '[[:space:]\u0085\u00A0\u1680\u2000-\u200A\u2028\u2029\u202F\u205F\u3000]', ' ', 'g'
+        ),
+        '[\u200B\u200C\u200D\u2060\uFEFF[:cntrl:]]', '', 'g'
+    ),
+    ' {2,}', ' ', 'g'
+));
+
+CREATE TEMPORARY TABLE theme_merges AS
+SELECT id, FIRST_VALUE(id) OVER (
+    PARTITION BY content ORDER BY deleted_at IS NOT NULL, id
+) AS keep
+FROM themes;
+DELETE FROM theme_merges WHERE id = keep;
+
+-- A user's vote on the kept copy wins; failing that, their latest on any other copy
+DELETE FROM votes v USING theme_merges m
+WHERE v.theme_id = m.id AND EXISTS (
+    SELECT 1 FROM votes o
+    LEFT JOIN theme_merges om ON om.id = o.theme_id
+    WHERE o.user_id = v.user_id AND o.id <> v.id
+      AND (o.theme_id = m.keep
+           OR (om.keep = m.keep AND (o.created_at, o.id) > (v.created_at, v.id)))
+);
+UPDATE votes SET theme_id = m.keep FROM theme_merges m WHERE theme_id = m.id;
+
+INSERT INTO theme_impressions (user_id, theme_id, first_seen_at)
+SELECT i.user_id, m.keep, MIN(i.first_seen_at)
+FROM theme_impressions i JOIN theme_merges m ON m.id = i.theme_id
+GROUP BY i.user_id, m.keep
+ON CONFLICT (user_id, theme_id)
+DO UPDATE SET first_seen_at = LEAST(theme_impressions.first_seen_at, EXCLUDED.first_seen_at);
+DELETE FROM theme_impressions USING theme_merges m WHERE theme_id = m.id;
+
+INSERT INTO theme_tags (theme_id, tag_id)
+SELECT m.keep, t.tag_id FROM theme_tags t JOIN theme_merges m ON m.id = t.theme_id
+ON CONFLICT DO NOTHING;
+DELETE FROM theme_tags USING theme_merges m WHERE theme_id = m.id;
+
+UPDATE featured_themes SET theme_id = m.keep FROM theme_merges m WHERE theme_id = m.id;
+UPDATE theme_edits SET theme_id = m.keep FROM theme_merges m WHERE theme_id = m.id;
+UPDATE archived_votes SET theme_id = m.keep FROM theme_merges m WHERE theme_id = m.id;
+UPDATE pending_themes SET theme_id = m.keep FROM theme_merges m WHERE theme_id = m.id;
+
+UPDATE themes t SET always_shown = TRUE
+FROM theme_merges m JOIN themes d ON d.id = m.id
+WHERE t.id = m.keep AND d.always_shown;
+DELETE FROM themes USING theme_merges m WHERE themes.id = m.id;
+DROP TABLE theme_merges;
+
+CREATE UNIQUE INDEX IF NOT EXISTS themes_content_key ON themes(content);
@@ -0,0 +1,5 @@
+-- "maybe" sits between yes and skip: interested, but not sure yet.
+-- Existing yes/no/skip votes still satisfy the widened check.
+ALTER TABLE votes DROP CONSTRAINT IF EXISTS votes_vote_type_check;
+ALTER TABLE votes ADD CONSTRAINT votes_vote_type_check
+    CHECK (vote_type IN ('yes', 'no', 'skip', 'maybe'));
@@ -0,0 +1,2 @@
+-- Optional longer text shown under a theme, e.g. what it means or a sample prompt
+ALTER TABLE themes ADD COLUMN IF NOT EXISTS description TEXT;
@@ -0,0 +1,3 @@
+-- Client version that last set each vote, from the X-Client-Version header.
+-- Null for votes from clients that don't send it.
+ALTER TABLE votes ADD COLUMN IF NOT EXISTS client_version TEXT;
@@ -0,0 +1,15 @@
+-- Head-to-head picks between two themes, for the optional bracket mode.
+-- One pick per user and pair, whichever way round it was shown.
+CREATE TABLE IF NOT EXISTS pairwise_votes (
+    id SERIAL PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    winner_id INTEGER NOT NULL REFERENCES themes(id),
+    loser_id INTEGER NOT NULL REFERENCES themes(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (winner_id <> loser_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS pairwise_votes_user_pair
+    ON pairwise_votes (user_id, LEAST(winner_id, loser_id), GREATEST(winner_id, loser_id));
+CREATE INDEX IF NOT EXISTS idx_pairwise_votes_winner_id ON pairwise_votes(winner_id);
+CREATE INDEX IF NOT EXISTS idx_pairwise_votes_loser_id ON pairwise_votes(loser_id);
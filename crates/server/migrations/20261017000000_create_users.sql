@@ -0,0 +1,7 @@
+-- Per-user settings; users without a row count as trust_weight 1.0.
+-- trust_weight scales the user's votes in weighted stats only.
+CREATE TABLE IF NOT EXISTS users (
+    user_id TEXT PRIMARY KEY,
+    trust_weight FLOAT8 NOT NULL DEFAULT 1.0 CHECK (trust_weight >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,2 @@
+-- Themes with a higher priority are served first; ties are picked between as before
+ALTER TABLE themes ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- When each vote was last changed, so created_at can keep when it was first cast.
+-- Existing votes have only ever had one time, so they start out the same.
+ALTER TABLE votes ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ;
+UPDATE votes SET updated_at = created_at WHERE updated_at IS NULL;
+ALTER TABLE votes
+    ALTER COLUMN updated_at SET DEFAULT NOW(),
+    ALTER COLUMN updated_at SET NOT NULL;
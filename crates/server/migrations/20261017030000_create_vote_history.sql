@@ -0,0 +1,11 @@
+-- The vote type a changed vote had before, and when it was replaced, so stats can be
+-- shown as they stood at an earlier time. Changes made before this table existed
+-- are lost: those votes count with their current type throughout.
+CREATE TABLE IF NOT EXISTS vote_history (
+    id SERIAL PRIMARY KEY,
+    vote_id INTEGER NOT NULL REFERENCES votes(id) ON DELETE CASCADE,
+    vote_type TEXT NOT NULL,
+    replaced_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_vote_history_vote_id ON vote_history(vote_id, replaced_at);
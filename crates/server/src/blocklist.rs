@@ -0,0 +1,118 @@
+use anyhow::Context;
+use std::{collections::HashSet, env, fs};
+
+/// Words and phrases themes may not contain, read from the file named by `BLOCKLIST_FILE`:
+/// one entry per line, `#` starts a comment. Matching is case-insensitive and on whole
+/// words, so "ass" blocks "Ass" but not "Assassin".
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    /// Entries as lowercase words joined by single spaces
+    entries: HashSet<String>,
+    /// Most words in any entry, so lookups only try phrases up to that length
+    longest: usize,
+}
+
+impl Blocklist {
+    /// The configured blocklist, or an empty one when `BLOCKLIST_FILE` isn't set.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match env::var("BLOCKLIST_FILE") {
+            Ok(path) if !path.trim().is_empty() => {
+                let path = path.trim();
+                let content = fs::read_to_string(path)
+                    .with_context(|| format!("Couldn't read BLOCKLIST_FILE {path}"))?;
+                Ok(Self::parse(&content))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut blocklist = Self::default();
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words = words(line);
+            if words.is_empty() {
+                continue;
+            }
+            blocklist.longest = blocklist.longest.max(words.len());
+            blocklist.entries.insert(words.join(" "));
+        }
+        blocklist
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Fails with the offending entry when `content` contains a blocked word or phrase.
+    pub fn check(&self, content: &str) -> Result<(), String> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let words = words(content);
+        for start in 0..words.len() {
+            for end in start + 1..=(start + self.longest).min(words.len()) {
+                let candidate = words[start..end].join(" ");
+                if self.entries.contains(&candidate) {
+                    return Err(format!("Theme contains a blocked word (\"{candidate}\")"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase words of `text`, split on anything that isn't a letter or digit.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_ignores_case() {
+        let blocklist = Blocklist::parse("Ass\n");
+        assert!(blocklist.check("ass").is_err());
+        assert!(blocklist.check("Kick ASS").is_err());
+        assert_eq!(
+            blocklist.check("Bad ass bees").unwrap_err(),
+            "Theme contains a blocked word (\"ass\")"
+        );
+    }
+
+    #[test]
+    fn only_whole_words_match() {
+        let blocklist = Blocklist::parse("ass");
+        assert_eq!(blocklist.check("Assassin"), Ok(()));
+        assert_eq!(blocklist.check("Bass fishing"), Ok(()));
+        // Punctuation separates words as much as spaces do
+        assert!(blocklist.check("Pain-in-the-ass").is_err());
+        assert!(blocklist.check("ass!").is_err());
+    }
+
+    #[test]
+    fn phrases_match_across_any_separators() {
+        let blocklist = Blocklist::parse("# Comments and blank lines are ignored\n\nkill   all\n");
+        assert_eq!(blocklist.len(), 1);
+        assert!(blocklist.check("Kill all humans").is_err());
+        assert!(blocklist.check("kill, all").is_err());
+        assert_eq!(blocklist.check("Kill them all"), Ok(()));
+        assert_eq!(blocklist.check("Skill all day"), Ok(()));
+    }
+
+    #[test]
+    fn an_empty_blocklist_allows_everything() {
+        let blocklist = Blocklist::parse("# nothing here\n");
+        assert!(blocklist.is_empty());
+        assert_eq!(blocklist.check("Anything at all"), Ok(()));
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// A single value kept for a short while, so hot endpoints don't query on every request.
+pub struct Cached<T> {
+    ttl: Duration,
+    value: Mutex<Option<(Instant, T)>>,
+}
+
+impl<T: Clone> Cached<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            value: Mutex::new(None),
+        }
+    }
+
+    /// The stored value, unless it has expired (or caching is off, with a zero TTL).
+    pub fn get(&self) -> Option<T> {
+        let value = self.value.lock().unwrap();
+        value
+            .as_ref()
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// The stored value, or else the one `load` comes up with, which is stored for next time.
+    pub async fn get_or_load<E, F>(&self, load: impl FnOnce() -> F) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+        let value = load().await?;
+        self.set(value.clone());
+        Ok(value)
+    }
+
+    pub fn set(&self, value: T) {
+        *self.value.lock().unwrap() = Some((Instant::now(), value));
+    }
+
+    /// Drops the stored value, for when what it was computed from has changed.
+    pub fn invalidate(&self) {
+        *self.value.lock().unwrap() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Gets the value from `cache`, counting in `loads` each time it has to be loaded.
+    async fn get(cache: &Cached<u32>, loads: &AtomicU32) -> u32 {
+        cache
+            .get_or_load(|| async { Ok::<_, ()>(loads.fetch_add(1, Ordering::Relaxed) + 1) })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn repeat_gets_within_the_ttl_load_once() {
+        let cache = Cached::new(Duration::from_secs(60));
+        let loads = AtomicU32::new(0);
+        for _ in 0..3 {
+            assert_eq!(get(&cache, &loads).await, 1);
+        }
+        assert_eq!(loads.load(Ordering::Relaxed), 1);
+
+        cache.invalidate();
+        assert_eq!(get(&cache, &loads).await, 2);
+    }
+
+    #[tokio::test]
+    async fn values_are_loaded_again_once_they_expire() {
+        let ttl = Duration::from_millis(50);
+        let cache = Cached::new(ttl);
+        let loads = AtomicU32::new(0);
+        assert_eq!(get(&cache, &loads).await, 1);
+        assert_eq!(cache.get(), Some(1));
+
+        tokio::time::sleep(ttl).await;
+        assert_eq!(cache.get(), None);
+        assert_eq!(get(&cache, &loads).await, 2);
+    }
+
+    #[tokio::test]
+    async fn a_zero_ttl_loads_every_time() {
+        let cache = Cached::new(Duration::ZERO);
+        let loads = AtomicU32::new(0);
+        assert_eq!(get(&cache, &loads).await, 1);
+        assert_eq!(get(&cache, &loads).await, 2);
+    }
+
+    #[tokio::test]
+    async fn failed_loads_are_not_stored() {
+        let cache = Cached::<u32>::new(Duration::from_secs(60));
+        assert_eq!(
+            cache.get_or_load(|| async { Err("down") }).await,
+            Err("down")
+        );
+        assert_eq!(cache.get(), None);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::models::Strategy;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use slaughter_vote::{database_url, theme_content::LengthLimits};
+use std::{env, net::IpAddr, time::Duration};
+
+/// Server settings read from the environment at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Votes are refused before this instant (unset = already open).
+    pub voting_opens_at: Option<DateTime<Utc>>,
+    /// Votes are refused after this instant (unset = never closes).
+    pub voting_closes_at: Option<DateTime<Utc>>,
+    /// Supabase user ids allowed to call admin-only endpoints.
+    pub admin_user_ids: Vec<String>,
+    /// Admins who may still see raw user ids when exports are anonymized.
+    pub super_admin_user_ids: Vec<String>,
+    /// When set, exported user ids are replaced by a salted hash of this secret.
+    pub export_salt: Option<String>,
+    /// Whether skipped themes come back around once their cooldown is over.
+    pub resurface_skips: bool,
+    /// A skip cools down after this many other votes by the same user...
+    pub skip_cooldown_votes: i64,
+    /// ...or after this many minutes, whichever comes first.
+    pub skip_cooldown_minutes: i64,
+    /// How `/themes/next` picks a theme when the request doesn't say.
+    pub default_strategy: Strategy,
+    /// Stop serving themes skipped more often than this share of votes (0-1, unset = never).
+    pub hide_skip_ratio: Option<f64>,
+    /// ...once they have at least this many votes.
+    pub hide_min_votes: i64,
+    /// Keep serving a user the same theme until they vote on it.
+    pub sticky_next_theme: bool,
+    /// Whether a user may change a vote they've already cast.
+    pub allow_revote: bool,
+    /// How long after casting or changing a vote before it may be changed again (zero = off).
+    pub revote_cooldown: Duration,
+    /// Whether voters may answer `maybe` as well as yes, no or skip.
+    pub allow_maybe: bool,
+    /// Shortest and longest theme accepted.
+    pub theme_length: LengthLimits,
+    /// Votes a user may cast per UTC day; changing a vote counts again (unset = no limit).
+    pub daily_vote_limit: Option<i64>,
+    /// How many results clients show per page unless told otherwise.
+    pub results_page_size: usize,
+    /// Keep results from non-admins until voting closes, so early tallies can't sway voters.
+    pub hide_results_until_close: bool,
+    /// Leave skipped themes out of the `seen` count, so progress shows decisions made.
+    /// Skipped themes still aren't served again unless `resurface_skips` is on.
+    pub progress_excludes_skips: bool,
+    /// Votes a theme needs before it shows up in public results.
+    pub public_results_min_votes: i64,
+    /// Head-to-head picks a theme needs before its pairwise ranking is trusted;
+    /// `/themes/pair` serves themes below it first.
+    pub min_pairwise_comparisons: i64,
+    /// Whether `POST /themes/vote` also takes form-encoded bodies, not just JSON.
+    pub accept_form_bodies: bool,
+    /// Verify the database schema and exit instead of serving.
+    pub check_only: bool,
+    /// Requests each client IP may make to unauthenticated endpoints per window.
+    pub public_rate_limit: u32,
+    pub public_rate_window: Duration,
+    /// Requests each signed-in user may make to authenticated endpoints per window.
+    pub user_rate_limit: u32,
+    pub user_rate_window: Duration,
+    /// Reverse proxies whose `X-Forwarded-For` header is trusted for the client IP.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// How long a request waits for a free database connection before giving up with a 503.
+    pub db_acquire_timeout: Duration,
+    /// How long rarely-changing responses (`/config`, `/themes/count`) may be reused.
+    pub cache_ttl: Duration,
+    /// How often a line of live voting figures is logged (zero = never).
+    pub metrics_log_every: Duration,
+    /// How often signing keys are re-fetched in the background.
+    pub jwks_refresh_every: Duration,
+}
+
+/// The settings `from_env` picks when nothing is set.
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            voting_opens_at: None,
+            voting_closes_at: None,
+            admin_user_ids: Vec::new(),
+            super_admin_user_ids: Vec::new(),
+            export_salt: None,
+            resurface_skips: false,
+            skip_cooldown_votes: 5,
+            skip_cooldown_minutes: 30,
+            default_strategy: Strategy::Random,
+            hide_skip_ratio: None,
+            hide_min_votes: 10,
+            sticky_next_theme: false,
+            allow_revote: true,
+            revote_cooldown: Duration::ZERO,
+            allow_maybe: true,
+            theme_length: LengthLimits::default(),
+            daily_vote_limit: None,
+            results_page_size: 10,
+            hide_results_until_close: false,
+            progress_excludes_skips: false,
+            public_results_min_votes: 0,
+            min_pairwise_comparisons: 5,
+            accept_form_bodies: true,
+            check_only: false,
+            public_rate_limit: 30,
+            public_rate_window: Duration::from_secs(60),
+            user_rate_limit: 120,
+            user_rate_window: Duration::from_secs(60),
+            trusted_proxies: Vec::new(),
+            db_acquire_timeout: database_url::DEFAULT_ACQUIRE_TIMEOUT,
+            cache_ttl: Duration::from_secs(5),
+            metrics_log_every: Duration::ZERO,
+            jwks_refresh_every: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            voting_opens_at: env_timestamp("VOTING_OPENS_AT")?,
+            voting_closes_at: env_timestamp("VOTING_CLOSES_AT")?,
+            admin_user_ids: env_list("ADMIN_USER_IDS"),
+            super_admin_user_ids: env_list("SUPER_ADMIN_USER_IDS"),
+            export_salt: env::var("EXPORT_SALT").ok().filter(|s| !s.is_empty()),
+            resurface_skips: env_parse("RESURFACE_SKIPS", false)?,
+            skip_cooldown_votes: env_parse("SKIP_COOLDOWN_VOTES", 5)?,
+            skip_cooldown_minutes: env_parse("SKIP_COOLDOWN_MINUTES", 30)?,
+            default_strategy: env_parse("DEFAULT_STRATEGY", Strategy::Random)?,
+            hide_skip_ratio: env_parse_optional("HIDE_SKIP_RATIO")?,
+            hide_min_votes: env_parse("HIDE_MIN_VOTES", 10)?,
+            sticky_next_theme: env_parse("STICKY_NEXT_THEME", false)?,
+            allow_revote: env_parse("ALLOW_REVOTE", true)?,
+            revote_cooldown: Duration::from_secs(env_parse("REVOTE_COOLDOWN_SECS", 0)?),
+            allow_maybe: env_parse("ALLOW_MAYBE", true)?,
+            theme_length: LengthLimits::from_env()?,
+            daily_vote_limit: env_parse_optional("DAILY_VOTE_LIMIT")?,
+            results_page_size: env_parse("RESULTS_PAGE_SIZE", 10)?,
+            hide_results_until_close: env_parse("HIDE_RESULTS_UNTIL_CLOSE", false)?,
+            progress_excludes_skips: env_parse("PROGRESS_EXCLUDES_SKIPS", false)?,
+            public_results_min_votes: env_parse("PUBLIC_RESULTS_MIN_VOTES", 0)?,
+            min_pairwise_comparisons: env_parse("MIN_PAIRWISE_COMPARISONS", 5)?,
+            accept_form_bodies: env_parse("ACCEPT_FORM_BODIES", true)?,
+            check_only: env_parse("SLAUGHTER_CHECK_ONLY", false)?,
+            public_rate_limit: env_parse("PUBLIC_RATE_LIMIT", 30)?,
+            public_rate_window: Duration::from_secs(env_parse("PUBLIC_RATE_WINDOW_SECS", 60)?),
+            user_rate_limit: env_parse("USER_RATE_LIMIT", 120)?,
+            user_rate_window: Duration::from_secs(env_parse("USER_RATE_WINDOW_SECS", 60)?),
+            trusted_proxies: env_list("TRUSTED_PROXIES")
+                .iter()
+                .map(|ip| ip.parse())
+                .collect::<Result<_, _>>()
+                .context("TRUSTED_PROXIES must be a comma-separated list of IP addresses")?,
+            db_acquire_timeout: database_url::acquire_timeout_from_env()?,
+            cache_ttl: Duration::from_secs(env_parse("CACHE_TTL_SECS", 5)?),
+            metrics_log_every: Duration::from_secs(env_parse("METRICS_LOG_SECS", 0)?),
+            jwks_refresh_every: Duration::from_secs(env_parse("JWKS_REFRESH_SECS", 3600)?),
+        })
+    }
+
+    pub fn is_voting_open(&self, now: DateTime<Utc>) -> bool {
+        self.voting_opens_at.is_none_or(|opens| now >= opens)
+            && self.voting_closes_at.is_none_or(|closes| now < closes)
+    }
+
+    /// Whether results may be shown to everyone: always, unless hidden until a close that
+    /// hasn't come yet (with no close time set, they stay hidden).
+    pub fn results_visible(&self, now: DateTime<Utc>) -> bool {
+        !self.hide_results_until_close || self.voting_closes_at.is_some_and(|closes| now >= closes)
+    }
+
+    pub fn is_admin(&self, user_id: &str) -> bool {
+        self.admin_user_ids.iter().any(|id| id == user_id)
+    }
+
+    pub fn is_super_admin(&self, user_id: &str) -> bool {
+        self.super_admin_user_ids.iter().any(|id| id == user_id)
+    }
+}
+
+fn env_timestamp(key: &str) -> anyhow::Result<Option<DateTime<Utc>>> {
+    match env::var(key) {
+        Ok(value) if !value.trim().is_empty() => {
+            let parsed = DateTime::parse_from_rfc3339(value.trim())
+                .with_context(|| format!("{key} must be an RFC 3339 timestamp"))?;
+            Ok(Some(parsed.with_timezone(&Utc)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn env_parse<T>(key: &str, default: T) -> anyhow::Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    Ok(env_parse_optional(key)?.unwrap_or(default))
+}
+
+fn env_parse_optional<T>(key: &str) -> anyhow::Result<Option<T>>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match env::var(key) {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .map(Some)
+            .with_context(|| format!("{key} has an invalid value")),
+        _ => Ok(None),
+    }
+}
+
+fn env_list(key: &str) -> Vec<String> {
+    env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
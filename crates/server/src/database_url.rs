@@ -0,0 +1,129 @@
+use anyhow::Context;
+use std::{env, fs, time::Duration};
+
+/// How long to wait for a pooled connection when `DB_ACQUIRE_TIMEOUT_SECS` isn't set.
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The database connection string, from `DATABASE_URL` or else from the file named by
+/// `DATABASE_URL_FILE`, as secret managers that mount files provide it.
+pub fn from_env() -> anyhow::Result<String> {
+    resolve(
+        env::var("DATABASE_URL").ok(),
+        env::var("DATABASE_URL_FILE").ok(),
+    )
+}
+
+/// How long to wait for a pooled connection before giving up, from
+/// `DB_ACQUIRE_TIMEOUT_SECS`. Every binary's pool uses this, so a saturated pool fails
+/// instead of hanging.
+pub fn acquire_timeout_from_env() -> anyhow::Result<Duration> {
+    acquire_timeout(env::var("DB_ACQUIRE_TIMEOUT_SECS").ok())
+}
+
+fn acquire_timeout(secs: Option<String>) -> anyhow::Result<Duration> {
+    let Some(secs) = secs.filter(|secs| !secs.trim().is_empty()) else {
+        return Ok(DEFAULT_ACQUIRE_TIMEOUT);
+    };
+    let secs = secs
+        .trim()
+        .parse()
+        .context("DB_ACQUIRE_TIMEOUT_SECS must be a number of seconds")?;
+    Ok(Duration::from_secs(secs))
+}
+
+/// [`from_env`] given the two variables' values, so it can be tried without touching the
+/// process environment.
+fn resolve(url: Option<String>, file: Option<String>) -> anyhow::Result<String> {
+    if let Some(url) = url
+        && !url.trim().is_empty()
+    {
+        return Ok(url);
+    }
+    let path = file
+        .filter(|path| !path.trim().is_empty())
+        .context("DATABASE_URL or DATABASE_URL_FILE must be set")?;
+    let url = fs::read_to_string(&path)
+        .with_context(|| format!("Couldn't read DATABASE_URL_FILE {path}"))?;
+    let url = url.trim_end();
+    anyhow::ensure!(!url.is_empty(), "DATABASE_URL_FILE {path} is empty");
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "postgres://user:secret@db/votes";
+
+    /// Writes `content` to a fresh file, returning its path.
+    fn secret_file(name: &str, content: &str) -> String {
+        let path = env::temp_dir().join(format!(
+            "slaughter_vote_database_url_{}_{name}",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn the_file_is_read_when_database_url_is_unset() {
+        let file = secret_file("unset", URL);
+        assert_eq!(resolve(None, Some(file.clone())).unwrap(), URL);
+        // Set but blank counts as unset
+        assert_eq!(resolve(Some(" ".into()), Some(file.clone())).unwrap(), URL);
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn database_url_takes_precedence_over_the_file() {
+        let file = secret_file("precedence", "postgres://from-the-file");
+        assert_eq!(resolve(Some(URL.into()), Some(file.clone())).unwrap(), URL);
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn database_url_is_used_without_a_file() {
+        assert_eq!(resolve(Some(URL.into()), None).unwrap(), URL);
+        assert_eq!(resolve(Some(URL.into()), Some("".into())).unwrap(), URL);
+    }
+
+    #[test]
+    fn trailing_whitespace_is_trimmed_from_the_file() {
+        let file = secret_file("newline", &format!("{URL}\n"));
+        assert_eq!(resolve(None, Some(file.clone())).unwrap(), URL);
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn a_missing_or_empty_file_is_an_error() {
+        let missing = resolve(None, Some("/nonexistent/database_url".into())).unwrap_err();
+        assert!(
+            missing
+                .to_string()
+                .contains("Couldn't read DATABASE_URL_FILE"),
+            "{missing}"
+        );
+
+        let file = secret_file("empty", "\n");
+        let empty = resolve(None, Some(file.clone())).unwrap_err();
+        assert!(empty.to_string().contains("is empty"), "{empty}");
+        fs::remove_file(file).unwrap();
+
+        let unset = resolve(None, None).unwrap_err();
+        assert!(unset.to_string().contains("must be set"), "{unset}");
+    }
+
+    #[test]
+    fn acquire_timeout_defaults_when_unset_or_blank() {
+        assert_eq!(acquire_timeout(None).unwrap(), DEFAULT_ACQUIRE_TIMEOUT);
+        assert_eq!(
+            acquire_timeout(Some(" ".into())).unwrap(),
+            DEFAULT_ACQUIRE_TIMEOUT
+        );
+        assert_eq!(
+            acquire_timeout(Some("30".into())).unwrap(),
+            Duration::from_secs(30)
+        );
+        assert!(acquire_timeout(Some("soon".into())).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use axum::{
+    Form, Json, async_trait,
+    extract::{FromRef, FromRequest, Request},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+use crate::config::Config;
+
+/// A request body sent as JSON, or as `application/x-www-form-urlencoded` for clients
+/// where a form is easier (a plain HTML form, curl). JSON stays the default: anything
+/// not marked as a form goes through [`Json`], with its usual rejections. Forms are only
+/// taken while `accept_form_bodies` is on; otherwise this is just [`Json`].
+pub struct JsonOrForm<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for JsonOrForm<T>
+where
+    S: Send + Sync,
+    Arc<Config>: FromRef<S>,
+    T: DeserializeOwned,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let accept_forms = Arc::<Config>::from_ref(state).accept_form_bodies;
+        let is_form = accept_forms
+            && req
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|content_type| {
+                    content_type.starts_with("application/x-www-form-urlencoded")
+                });
+
+        if is_form {
+            let Form(value) = Form::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Ok(Self(value))
+        } else {
+            let Json(value) = Json::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Ok(Self(value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::VoteRequest;
+    use axum::{body::Body, http::StatusCode};
+
+    fn config(accept_form_bodies: bool) -> Arc<Config> {
+        Arc::new(Config {
+            accept_form_bodies,
+            ..Config::default()
+        })
+    }
+
+    fn request(content_type: &str, body: &str) -> Request {
+        Request::builder()
+            .method("POST")
+            .uri("/themes/vote")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn extract(
+        config: &Arc<Config>,
+        content_type: &str,
+        body: &str,
+    ) -> Result<VoteRequest, Response> {
+        JsonOrForm::<VoteRequest>::from_request(request(content_type, body), config)
+            .await
+            .map(|JsonOrForm(vote)| vote)
+    }
+
+    #[tokio::test]
+    async fn json_and_form_bodies_give_the_same_vote() {
+        let config = config(true);
+        let cases = [
+            (
+                r#"{"theme_id": 7, "vote_type": "yes"}"#,
+                "theme_id=7&vote_type=yes",
+            ),
+            (
+                r#"{"theme_id": 3, "vote_type": "skip", "reason": "too vague"}"#,
+                "theme_id=3&vote_type=skip&reason=too+vague",
+            ),
+        ];
+
+        for (json, form) in cases {
+            let from_json = extract(&config, "application/json", json).await.unwrap();
+            let from_form = extract(
+                &config,
+                "application/x-www-form-urlencoded; charset=utf-8",
+                form,
+            )
+            .await
+            .unwrap();
+            assert_eq!(from_json.theme_id, from_form.theme_id);
+            assert_eq!(from_json.vote_type, from_form.vote_type);
+            assert_eq!(from_json.reason, from_form.reason);
+        }
+    }
+
+    #[tokio::test]
+    async fn bad_bodies_are_rejected_either_way() {
+        let config = config(true);
+        let json = extract(&config, "application/json", r#"{"theme_id": "x"}"#).await;
+        let form = extract(&config, "application/x-www-form-urlencoded", "theme_id=x").await;
+        assert!(json.unwrap_err().status().is_client_error());
+        assert!(form.unwrap_err().status().is_client_error());
+    }
+
+    #[tokio::test]
+    async fn forms_are_turned_away_when_switched_off() {
+        let config = config(false);
+        let form = extract(
+            &config,
+            "application/x-www-form-urlencoded",
+            "theme_id=7&vote_type=yes",
+        )
+        .await;
+        assert_eq!(
+            form.unwrap_err().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        let json = extract(
+            &config,
+            "application/json",
+            r#"{"theme_id": 7, "vote_type": "yes"}"#,
+        );
+        assert_eq!(json.await.unwrap().theme_id, 7);
+    }
+}
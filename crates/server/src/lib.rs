@@ -0,0 +1,6 @@
+//! Code shared between the `server`, `load_themes` and `seed` binaries.
+
+pub mod blocklist;
+pub mod database_url;
+pub mod tags;
+pub mod theme_content;
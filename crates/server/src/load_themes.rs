@@ -1,59 +1,713 @@
-use sqlx::postgres::PgPoolOptions;
+use anyhow::Context;
+use slaughter_vote::theme_content::{self, LengthLimits};
+use slaughter_vote::{blocklist::Blocklist, database_url, tags};
+use sqlx::{PgPool, postgres::PgPoolOptions};
 use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::time::{Duration, Instant, SystemTime};
+
+const THEMES_FILE: &str = "themes.txt";
+/// Lines handled per transaction, so a huge file doesn't hold one open for the whole run.
+const BATCH_SIZE: usize = 500;
+const PROGRESS_EVERY: usize = 100;
+/// How often `--watch` looks at the file, and how long it has to sit unchanged before it's
+/// reloaded, so a burst of saves is applied once.
+const WATCH_POLL: Duration = Duration::from_millis(250);
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Where each field sits in a CSV file, read from its header row.
+struct CsvColumns {
+    content: usize,
+    description: Option<usize>,
+    category: Option<usize>,
+    priority: Option<usize>,
+}
+
+impl CsvColumns {
+    fn from_header(header: &str) -> anyhow::Result<Self> {
+        let names: Vec<String> = parse_csv_line(header)
+            .map_err(|reason| anyhow::anyhow!("Invalid CSV header: {reason}"))?
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect();
+        let find = |column: &str| names.iter().position(|name| name == column);
+
+        Ok(Self {
+            content: find("content")
+                .ok_or_else(|| anyhow::anyhow!("CSV header has no \"content\" column"))?,
+            description: find("description"),
+            category: find("category"),
+            priority: find("priority"),
+        })
+    }
+
+    /// Theme content, description and tags from one row, or why the row can't be used.
+    fn parse_row(&self, line: &str) -> Result<ParsedTheme, String> {
+        let fields = parse_csv_line(line)?;
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| fields.get(index))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+
+        let content = field(Some(self.content)).ok_or("missing content")?;
+        let mut theme_tags = Vec::new();
+        if let Some(category) = field(self.category) {
+            theme_tags.push(tags::normalize(category)?);
+        }
+        let priority = field(self.priority)
+            .map(|priority| {
+                priority
+                    .parse::<i32>()
+                    .map_err(|_| format!("priority \"{priority}\" is not a whole number"))
+            })
+            .transpose()?;
+        Ok(ParsedTheme {
+            content: content.to_string(),
+            description: field(self.description).map(String::from),
+            tags: theme_tags,
+            priority,
+        })
+    }
+}
+
+/// One theme as read from the input, before normalization.
+struct ParsedTheme {
+    content: String,
+    description: Option<String>,
+    tags: Vec<String>,
+    /// Higher is served first; unset keeps the default (or the stored one)
+    priority: Option<i32>,
+}
+
+/// Parses a plain-text line: `content #tag | description`, where the tags and the
+/// description are optional. `\|` and `\#` stand for a literal `|` or `#`, e.g.
+/// `Us \| Them` or `Level \#1`.
+fn parse_text_line(line: &str) -> Result<ParsedTheme, String> {
+    let (theme, description) = match find_unescaped(line, '|') {
+        Some(at) => (line[..at].trim(), Some(unescape(line[at + 1..].trim()))),
+        None => (line, None),
+    };
+
+    // Trailing "#tag" tokens label the theme, e.g. "Lost at sea #horror #co-op";
+    // an escaped "\#1" isn't one, since it doesn't start with '#'
+    let (theme, raw_tags) = tags::split_trailing(theme);
+    let tags = raw_tags
+        .into_iter()
+        .map(tags::normalize)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|reason| format!("invalid tag: {reason}"))?;
+    Ok(ParsedTheme {
+        content: unescape(theme),
+        description,
+        tags,
+        priority: None,
+    })
+}
+
+/// Byte offset of the first `delimiter` not escaped with a backslash.
+fn find_unescaped(line: &str, delimiter: char) -> Option<usize> {
+    let mut escaped = false;
+    for (at, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == delimiter => return Some(at),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("\\|", "|").replace("\\#", "#")
+}
+
+/// Splits one CSV line into fields, honoring double quotes and `""` escapes.
+fn parse_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".into());
+    }
+    fields.push(field);
+    Ok(fields)
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
 
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    // Usage: load_themes [PATH] [--csv] [--watch]; a .csv extension implies --csv.
+    // Text lines look like "content #tag | description" (\| and \# for literal ones);
+    // CSV files need a header row.
+    // --watch keeps running and loads the file again whenever it changes.
+    let mut path = THEMES_FILE.to_string();
+    let mut csv = false;
+    let mut watch = false;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--csv" => csv = true,
+            "--watch" => watch = true,
+            _ => path = arg,
+        }
+    }
+    csv |= path.to_lowercase().ends_with(".csv");
+
+    let database_url = database_url::from_env()?;
+    let blocklist = Blocklist::from_env()?;
+    let theme_length = LengthLimits::from_env()?;
     let db = PgPoolOptions::new()
         .max_connections(5)
+        .acquire_timeout(database_url::acquire_timeout_from_env()?)
         .connect(&database_url)
         .await?;
 
     println!("Connected to database!");
 
-    // Read themes from file
-    let themes_content = std::fs::read_to_string("themes.txt")
-        .expect("Failed to read themes.txt - make sure it exists!");
+    let loader = Loader {
+        db,
+        path,
+        csv,
+        blocklist,
+        theme_length,
+    };
+    let counts = loader.load(true).await?;
 
-    let mut count = 0;
-    let mut skipped = 0;
+    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("✓ Successfully loaded {} new themes!", counts.added);
+    if counts.updated > 0 {
+        println!("✎ Updated {} existing themes", counts.updated);
+    }
+    if counts.duplicates > 0 {
+        println!("⊘ Skipped {} duplicate themes", counts.duplicates);
+    }
+    if counts.invalid > 0 {
+        println!("⚠ Skipped {} invalid themes", counts.invalid);
+    }
+    if counts.blocked > 0 {
+        println!("⛔ Skipped {} themes with blocked words", counts.blocked);
+    }
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    for line in themes_content.lines() {
-        let theme = line.trim();
-        if theme.is_empty() || theme.starts_with('#') {
-            continue;
-        }
+    if watch {
+        loader.watch().await?;
+    }
+    Ok(())
+}
+
+/// What one pass over the file did.
+#[derive(Default)]
+struct LoadCounts {
+    added: usize,
+    /// Duplicates whose description or priority changed
+    updated: usize,
+    duplicates: usize,
+    invalid: usize,
+    blocked: usize,
+}
+
+impl LoadCounts {
+    fn skipped(&self) -> usize {
+        self.duplicates + self.invalid + self.blocked
+    }
+}
+
+struct Loader {
+    db: PgPool,
+    path: String,
+    csv: bool,
+    blocklist: Blocklist,
+    theme_length: LengthLimits,
+}
+
+impl Loader {
+    /// Loads every line of the file. Themes already in the database are skipped, so this
+    /// is safe to run again; `log_duplicates` lists each of them.
+    async fn load(&self, log_duplicates: bool) -> anyhow::Result<LoadCounts> {
+        let Self {
+            db,
+            path,
+            csv,
+            blocklist,
+            theme_length,
+        } = self;
+        let csv = *csv;
+
+        // Stream the file line by line so memory stays flat however big it is
+        let open = || {
+            File::open(path)
+                .map(BufReader::new)
+                .with_context(|| format!("Failed to read {path} - make sure it exists!"))
+        };
+        let total_lines = open()?.lines().count();
+        println!("Reading {} lines from {}", total_lines, path);
+
+        let mut counts = LoadCounts::default();
+        let mut columns: Option<CsvColumns> = None;
+
+        let mut tx = db.begin().await?;
+        for (line_number, line) in open()?.lines().enumerate() {
+            if line_number > 0 && line_number.is_multiple_of(BATCH_SIZE) {
+                tx.commit().await?;
+                tx = db.begin().await?;
+            }
+
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || (!csv && line.starts_with('#')) {
+                continue;
+            }
+
+            let parsed = if csv {
+                let Some(columns) = &columns else {
+                    columns = Some(CsvColumns::from_header(line)?);
+                    continue;
+                };
+                columns.parse_row(line)
+            } else {
+                parse_text_line(line)
+            };
+            let parsed = parsed.and_then(|parsed| {
+                let description = parsed
+                    .description
+                    .as_deref()
+                    .map(theme_content::description)
+                    .transpose()?
+                    .flatten();
+                Ok((parsed.content, description, parsed.tags, parsed.priority))
+            });
+            let (theme, description, theme_tags, priority) = match parsed {
+                Ok(parsed) => parsed,
+                Err(reason) => {
+                    println!("⚠ Skipped line {}: {} - {}", line_number + 1, line, reason);
+                    counts.invalid += 1;
+                    continue;
+                }
+            };
+            let theme = theme_content::normalize(&theme);
+            let theme = theme.as_str();
+
+            if let Err(reason) = theme_length.validate(theme) {
+                println!(
+                    "⚠ Skipped line {} (invalid): {} - {}",
+                    line_number + 1,
+                    theme,
+                    reason
+                );
+                counts.invalid += 1;
+                continue;
+            }
+            if let Err(reason) = std::iter::once(theme)
+                .chain(description.as_deref())
+                .try_for_each(|text| blocklist.check(text))
+            {
+                println!(
+                    "⛔ Skipped line {} (blocked): {} - {}",
+                    line_number + 1,
+                    theme,
+                    reason
+                );
+                counts.blocked += 1;
+                continue;
+            }
+
+            // Let the unique index spot duplicates, so a concurrent create can't fail the batch
+            let inserted: Option<i32> = sqlx::query_scalar(
+                "INSERT INTO themes (content, description, priority) VALUES ($1, $2, COALESCE($3, 0))
+                 ON CONFLICT (content) DO NOTHING
+                 RETURNING id",
+            )
+            .bind(theme)
+            .bind(&description)
+            .bind(priority)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let theme_id = match inserted {
+                None => {
+                    // Like tags, a description or priority given for a duplicate is applied to it
+                    let updated = sqlx::query(
+                        "UPDATE themes
+                         SET description = COALESCE($2, description), priority = COALESCE($3, priority)
+                         WHERE content = $1 AND deleted_at IS NULL
+                           AND (description IS DISTINCT FROM COALESCE($2, description)
+                                OR priority <> COALESCE($3, priority))",
+                    )
+                    .bind(theme)
+                    .bind(&description)
+                    .bind(priority)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected()
+                        > 0;
+                    if updated {
+                        println!("✎ Updated: {}", theme);
+                        counts.updated += 1;
+                    } else {
+                        if log_duplicates {
+                            println!("⊘ Skipped (duplicate): {}", theme);
+                        }
+                        counts.duplicates += 1;
+                    }
+                    sqlx::query_scalar("SELECT id FROM themes WHERE content = $1")
+                        .bind(theme)
+                        .fetch_one(&mut *tx)
+                        .await?
+                }
+                Some(id) => {
+                    counts.added += 1;
+                    if counts.added.is_multiple_of(PROGRESS_EVERY) {
+                        println!(
+                            "… {} themes loaded ({}/{} lines)",
+                            counts.added,
+                            line_number + 1,
+                            total_lines
+                        );
+                    }
+                    id
+                }
+            };
 
-        // Check if theme already exists
-        let exists: bool =
-            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM themes WHERE content = $1)")
-                .bind(theme)
-                .fetch_one(&db)
+            // Tags are still applied to duplicates, so re-running picks up newly added ones
+            for tag in &theme_tags {
+                sqlx::query(
+                    "WITH new_tag AS (
+                         INSERT INTO tags (name) VALUES ($2)
+                         ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+                         RETURNING id
+                     )
+                     INSERT INTO theme_tags (theme_id, tag_id)
+                     SELECT $1, id FROM new_tag
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(theme_id)
+                .bind(tag)
+                .execute(&mut *tx)
                 .await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(counts)
+    }
+
+    /// Loads the file again whenever it changes, until Ctrl-C.
+    async fn watch(&self) -> anyhow::Result<()> {
+        println!("👀 Watching {} for changes (Ctrl-C to stop)", self.path);
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        let mut poll = tokio::time::interval(WATCH_POLL);
 
-        if exists {
-            println!("⊘ Skipped (duplicate): {}", theme);
-            skipped += 1;
-            continue;
+        let mut watched = Watched::new(self.version());
+        loop {
+            tokio::select! {
+                result = &mut ctrl_c => {
+                    result?;
+                    println!("\nStopped watching {}", self.path);
+                    return Ok(());
+                }
+                _ = poll.tick() => {}
+            }
+
+            match self.reload_if_changed(&mut watched, Instant::now()).await {
+                None => {}
+                Some(Ok(counts)) => println!(
+                    "🔄 Reloaded {}: {} added, {} updated, {} skipped ({} duplicate, {} invalid, {} blocked)",
+                    self.path,
+                    counts.added,
+                    counts.updated,
+                    counts.skipped(),
+                    counts.duplicates,
+                    counts.invalid,
+                    counts.blocked
+                ),
+                Some(Err(e)) => println!("⚠ Reloading {} failed: {e:#}", self.path),
+            }
         }
+    }
 
-        sqlx::query("INSERT INTO themes (content) VALUES ($1)")
-            .bind(theme)
+    /// One poll of `--watch`: loads the file again if it has changed and then sat
+    /// unchanged for [`WATCH_DEBOUNCE`] by `now`, or does nothing (`None`).
+    async fn reload_if_changed(
+        &self,
+        watched: &mut Watched,
+        now: Instant,
+    ) -> Option<anyhow::Result<LoadCounts>> {
+        if !watched.settled(self.version(), now) {
+            return None;
+        }
+        println!();
+        Some(self.load(false).await)
+    }
+
+    /// When the file was last written and how big it is, or `None` while it's missing.
+    fn version(&self) -> FileVersion {
+        let metadata = fs::metadata(&self.path).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+}
+
+type FileVersion = Option<(SystemTime, u64)>;
+
+/// What `--watch` has seen of the file so far.
+struct Watched {
+    loaded: FileVersion,
+    /// A change not applied yet, and when it was first seen
+    pending: Option<(FileVersion, Instant)>,
+}
+
+impl Watched {
+    fn new(loaded: FileVersion) -> Self {
+        Self {
+            loaded,
+            pending: None,
+        }
+    }
+
+    /// Whether the file, found at `current`, has a change that's due to be loaded. Once
+    /// it says so, that version counts as loaded.
+    fn settled(&mut self, current: FileVersion, now: Instant) -> bool {
+        if current == self.loaded {
+            self.pending = None;
+            return false;
+        }
+        match self.pending {
+            Some((seen, since)) if seen == current && now - since >= WATCH_DEBOUNCE => {}
+            Some((seen, _)) if seen == current => return false,
+            _ => {
+                self.pending = Some((current, now));
+                return false;
+            }
+        }
+        self.pending = None;
+        self.loaded = current;
+        // Editors that save by replacing the file briefly leave nothing there
+        current.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A loader for a fresh file called `name` holding `content`, read as CSV if the name
+    /// ends in `.csv`.
+    fn loader(db: &PgPool, name: &str, content: &str) -> Loader {
+        let path = env::temp_dir().join(format!(
+            "slaughter_vote_themes_{}_{name}",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+        Loader {
+            db: db.clone(),
+            path: path.to_string_lossy().into_owned(),
+            csv: name.ends_with(".csv"),
+            blocklist: Blocklist::default(),
+            theme_length: LengthLimits::default(),
+        }
+    }
+
+    /// Loads `content` from a file called `name`, as CSV if that ends in `.csv`.
+    async fn load(db: &PgPool, name: &str, content: &str) -> LoadCounts {
+        let loader = loader(db, name, content);
+        let counts = loader.load(false).await.unwrap();
+        fs::remove_file(&loader.path).unwrap();
+        counts
+    }
+
+    async fn description(db: &PgPool, content: &str) -> Option<String> {
+        sqlx::query_scalar("SELECT description FROM themes WHERE content = $1")
+            .bind(content)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn escaped_pipes_and_hashes_stay_in_the_content() {
+        let theme = parse_text_line(r"Us \| Them #versus | Who wins?").unwrap();
+        assert_eq!(theme.content, "Us | Them");
+        assert_eq!(theme.tags, ["versus"]);
+        assert_eq!(theme.description.as_deref(), Some("Who wins?"));
+
+        let theme = parse_text_line(r"Level \#1").unwrap();
+        assert_eq!(theme.content, "Level #1");
+        assert!(theme.tags.is_empty());
+        assert_eq!(theme.description, None);
+
+        let theme = parse_text_line(r"Bees | Either \| or").unwrap();
+        assert_eq!(theme.content, "Bees");
+        assert_eq!(theme.description.as_deref(), Some("Either | or"));
+    }
+
+    #[test]
+    fn unescaped_pipes_and_hashes_still_split() {
+        let theme = parse_text_line("Lost at sea #horror #co-op | Adrift").unwrap();
+        assert_eq!(theme.content, "Lost at sea");
+        assert_eq!(theme.tags, ["horror", "co-op"]);
+        assert_eq!(theme.description.as_deref(), Some("Adrift"));
+    }
+
+    #[sqlx::test]
+    async fn only_changed_descriptions_count_as_updated(db: PgPool) {
+        let first = load(&db, "first.txt", "Bees | Buzzing\nWasps\n").await;
+        assert_eq!((first.added, first.updated, first.duplicates), (2, 0, 0));
+
+        let again = load(&db, "again.txt", "Bees | Buzzing\nWasps | Stinging\nAnts\n").await;
+        assert_eq!((again.added, again.updated, again.duplicates), (1, 1, 1));
+        assert_eq!(description(&db, "Wasps").await.as_deref(), Some("Stinging"));
+
+        // A missing description leaves the stored one alone
+        let bare = load(&db, "bare.txt", "Bees\n").await;
+        assert_eq!((bare.updated, bare.duplicates), (0, 1));
+        assert_eq!(description(&db, "Bees").await.as_deref(), Some("Buzzing"));
+    }
+
+    #[sqlx::test]
+    async fn csv_rows_load_with_category_and_priority(db: PgPool) {
+        let counts = load(
+            &db,
+            "themes.csv",
+            "content,category,priority\nBees,nature,3\nWasps,,\n,horror,1\nAnts,nature,high\n",
+        )
+        .await;
+        assert_eq!((counts.added, counts.invalid), (2, 2));
+
+        let themes: Vec<(String, i32, Option<String>)> = sqlx::query_as(
+            "SELECT t.content, t.priority, g.name FROM themes t
+             LEFT JOIN theme_tags tt ON tt.theme_id = t.id
+             LEFT JOIN tags g ON g.id = tt.tag_id
+             ORDER BY t.content",
+        )
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(
+            themes,
+            [
+                ("Bees".to_string(), 3, Some("nature".to_string())),
+                ("Wasps".to_string(), 0, None),
+            ]
+        );
+
+        // Loading again with a new priority updates it
+        let again = load(&db, "again.csv", "content,priority\nBees,5\nWasps,0\n").await;
+        assert_eq!((again.updated, again.duplicates), (1, 1));
+    }
+
+    #[sqlx::test]
+    async fn lines_outside_the_length_limits_are_skipped(db: PgPool) {
+        let mut loader = loader(&db, "lengths.txt", "Ox\nBees\nArmadillos\n");
+        loader.theme_length = LengthLimits { min: 3, max: 8 };
+        let counts = loader.load(false).await.unwrap();
+        fs::remove_file(&loader.path).unwrap();
+        assert_eq!((counts.added, counts.invalid), (1, 2));
+
+        let themes: Vec<String> = sqlx::query_scalar("SELECT content FROM themes")
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(themes, ["Bees"]);
+    }
+
+    #[sqlx::test]
+    async fn deleted_themes_keep_their_description(db: PgPool) {
+        load(&db, "first.txt", "Bees | Buzzing\n").await;
+        sqlx::query("UPDATE themes SET deleted_at = NOW() WHERE content = 'Bees'")
             .execute(&db)
-            .await?;
+            .await
+            .unwrap();
 
-        count += 1;
-        println!("✓ Loaded: {}", theme);
+        let again = load(&db, "again.txt", "Bees | Stinging\n").await;
+        assert_eq!((again.updated, again.duplicates), (0, 1));
+        assert_eq!(description(&db, "Bees").await.as_deref(), Some("Buzzing"));
     }
 
-    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("✓ Successfully loaded {} new themes!", count);
-    if skipped > 0 {
-        println!("⊘ Skipped {} duplicate themes", skipped);
+    #[sqlx::test]
+    async fn large_files_load_every_theme_line_across_batches(db: PgPool) {
+        let lines = 5 * BATCH_SIZE + 7;
+        let mut content = String::new();
+        let mut themes = 0;
+        for i in 0..lines {
+            if i % 10 == 0 {
+                content.push_str(&format!("# Section {i}\n"));
+            } else if i % 25 == 1 {
+                content.push('\n');
+            } else {
+                content.push_str(&format!("Theme number {i}\n"));
+                themes += 1;
+            }
+        }
+
+        let counts = load(&db, "large.txt", &content).await;
+        assert_eq!(counts.added, themes);
+        assert_eq!(counts.skipped(), 0);
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM themes")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(stored, themes as i64);
     }
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    Ok(())
+    #[sqlx::test]
+    async fn editing_the_watched_file_loads_the_changed_lines(db: PgPool) {
+        let loader = loader(&db, "watched.txt", "Bees | Buzzing\nWasps\n");
+        loader.load(false).await.unwrap();
+        let mut watched = Watched::new(loader.version());
+        let start = Instant::now();
+        assert!(
+            loader
+                .reload_if_changed(&mut watched, start)
+                .await
+                .is_none()
+        );
+
+        fs::write(&loader.path, "Bees | Stinging\nWasps\nAnts\n").unwrap();
+        // A change waits until it has settled
+        assert!(
+            loader
+                .reload_if_changed(&mut watched, start)
+                .await
+                .is_none()
+        );
+        let counts = loader
+            .reload_if_changed(&mut watched, start + WATCH_DEBOUNCE)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((counts.added, counts.updated, counts.duplicates), (1, 1, 1));
+        assert_eq!(description(&db, "Bees").await.as_deref(), Some("Stinging"));
+        assert_eq!(description(&db, "Ants").await, None);
+
+        // Once loaded, the same version isn't loaded again
+        let later = start + 2 * WATCH_DEBOUNCE;
+        assert!(
+            loader
+                .reload_if_changed(&mut watched, later)
+                .await
+                .is_none()
+        );
+        fs::remove_file(&loader.path).unwrap();
+    }
 }
@@ -1,91 +1,656 @@
+mod cache;
+mod config;
+mod extract;
+mod jwks;
 mod models;
+mod rate_limit;
+mod report;
 
-use chrono::Utc;
+use anyhow::Context;
+use cache::Cached;
+use chrono::{DateTime, Utc};
+use config::Config;
+use extract::JsonOrForm;
+use jwks::Jwks;
 use models::*;
+use rate_limit::{RateLimiter, Usage};
+use slaughter_vote::{blocklist::Blocklist, database_url, tags, theme_content};
 
 use axum::{
-    Json, Router,
-    extract::State,
-    http::{HeaderMap, StatusCode},
+    Json, Router, async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts, Path, Query, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header, request::Parts},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
+use sha2::{Digest, Sha256};
 use sqlx::{PgPool, postgres::PgPoolOptions};
-use std::{env, sync::Arc};
-use supabase_jwt::{Claims, JwksCache};
-use tower_http::cors::CorsLayer;
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    env,
+    net::{IpAddr, SocketAddr},
+    ops::Deref,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use supabase_jwt::{AuthError, Claims};
+use tower_http::{
+    LatencyUnit,
+    cors::CorsLayer,
+    trace::{DefaultOnResponse, TraceLayer},
+};
+use tracing_subscriber::util::SubscriberInitExt;
 
 // ===== App State =====
 
 #[derive(Clone)]
 struct AppState {
     db: PgPool,
-    jwks_cache: Arc<JwksCache>,
+    auth: Authenticator,
+    config: Arc<Config>,
+    public_limiter: Arc<RateLimiter<IpAddr>>,
+    user_limiter: Arc<RateLimiter<String>>,
+    /// Words themes may not contain
+    blocklist: Arc<Blocklist>,
+    /// Active theme count for `/themes/count`, cleared whenever themes are added or removed.
+    theme_count: Arc<Cached<i64>>,
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
 }
 
 // ===== Auth Middleware =====
 
-async fn verify_jwt(jwks_cache: &Arc<JwksCache>, headers: &HeaderMap) -> Result<String, AppError> {
+/// Why a request failed authentication. Logged instead of the token itself.
+#[derive(Debug, Clone, Copy)]
+enum AuthFailure {
+    MissingHeader,
+    NoBearerPrefix,
+    Malformed,
+    UnknownKey,
+    KeysUnavailable,
+    /// Bad signature, or a lifetime rejected by the JWT library itself
+    VerificationFailed,
+    InvalidClaims,
+    Expired,
+}
+
+impl AuthFailure {
+    fn from_jwt_error(err: &AuthError) -> Self {
+        match err {
+            AuthError::NoMatchingKey => AuthFailure::UnknownKey,
+            AuthError::NetworkError(_) | AuthError::JwksError(_) => AuthFailure::KeysUnavailable,
+            AuthError::Verification => AuthFailure::VerificationFailed,
+            AuthError::InvalidClaims => AuthFailure::InvalidClaims,
+            _ => AuthFailure::Malformed,
+        }
+    }
+
+    fn reason(self) -> &'static str {
+        match self {
+            AuthFailure::MissingHeader => "missing_header",
+            AuthFailure::NoBearerPrefix => "no_bearer_prefix",
+            AuthFailure::Malformed => "malformed",
+            AuthFailure::UnknownKey => "unknown_key",
+            AuthFailure::KeysUnavailable => "keys_unavailable",
+            AuthFailure::VerificationFailed => "verification_failed",
+            AuthFailure::InvalidClaims => "invalid_claims",
+            AuthFailure::Expired => "expired",
+        }
+    }
+}
+
+/// Total auth failures since startup; a sudden climb is worth an alert.
+static AUTH_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+fn auth_failed(failure: AuthFailure) -> AppError {
+    let total = AUTH_FAILURES.fetch_add(1, Ordering::Relaxed) + 1;
+    tracing::warn!(
+        reason = failure.reason(),
+        total_failures = total,
+        "JWT verification failed"
+    );
+
+    match failure {
+        AuthFailure::MissingHeader => AppError::BadRequest("no auth".into()),
+        AuthFailure::NoBearerPrefix => AppError::BadRequest("no bearer".into()),
+        _ => AppError::Unauthorized,
+    }
+}
+
+/// Works out who is calling. Handlers go through this rather than the JWT code
+/// directly, so tests can swap in a fake.
+#[derive(Clone)]
+enum Authenticator {
+    /// Verifies Supabase JWTs against the project's signing keys.
+    Jwks(Arc<Jwks>),
+    /// Treats every request as coming from this user.
+    #[cfg(test)]
+    Fixed(String),
+}
+
+impl Authenticator {
+    async fn verify(&self, headers: &AuthHeaders) -> Result<String, AppError> {
+        if let Some(user_id) = &headers.identified {
+            return Ok(user_id.clone());
+        }
+        self.identify(headers).await.map_err(auth_failed)
+    }
+
+    /// Like `verify`, but a failure is neither logged nor counted.
+    async fn identify(&self, headers: &HeaderMap) -> Result<String, AuthFailure> {
+        match self {
+            Authenticator::Jwks(jwks) => verify_jwt(jwks, headers).await,
+            #[cfg(test)]
+            Authenticator::Fixed(user_id) => Ok(user_id.clone()),
+        }
+    }
+}
+
+/// The user `limit_user_requests` identified, kept in the request's extensions.
+#[derive(Clone)]
+struct Identified(String);
+
+/// A request's headers, plus the user already identified from them on the way in, if any,
+/// so the handler doesn't verify the same token a second time.
+struct AuthHeaders {
+    headers: HeaderMap,
+    identified: Option<String>,
+}
+
+impl From<HeaderMap> for AuthHeaders {
+    fn from(headers: HeaderMap) -> Self {
+        Self {
+            headers,
+            identified: None,
+        }
+    }
+}
+
+impl Deref for AuthHeaders {
+    type Target = HeaderMap;
+
+    fn deref(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthHeaders {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            headers: parts.headers.clone(),
+            identified: parts
+                .extensions
+                .get::<Identified>()
+                .map(|Identified(user_id)| user_id.clone()),
+        })
+    }
+}
+
+async fn verify_jwt(jwks_cache: &Jwks, headers: &HeaderMap) -> Result<String, AuthFailure> {
     let auth_header = headers
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
-        .ok_or(AppError::BadRequest("no auth".into()))?;
+        .ok_or(AuthFailure::MissingHeader)?;
 
     let token = auth_header
         .strip_prefix("Bearer ")
-        .ok_or(AppError::BadRequest("no bearer".into()))?;
+        .ok_or(AuthFailure::NoBearerPrefix)?;
 
-    let claims = Claims::from_token(token, jwks_cache)
-        .await
-        .map_err(|_| StatusCode::UNAUTHORIZED);
+    let claims = Claims::from_token(token, &jwks_cache.cache()).await;
     pub fn is_expired(exp: i64) -> bool {
         Utc::now().timestamp() > exp
     }
 
     match claims {
-        Err(_) => Err(AppError::Unauthorized),
-        Ok(claims) if is_expired(claims.exp as i64) => Err(AppError::Unauthorized),
+        Err(err) => {
+            // Possibly a freshly rotated key we haven't fetched yet
+            if matches!(err, AuthError::NoMatchingKey) {
+                jwks_cache.request_refresh();
+            }
+            Err(AuthFailure::from_jwt_error(&err))
+        }
+        Ok(claims) if is_expired(claims.exp as i64) => Err(AuthFailure::Expired),
         Ok(claims) => Ok(claims.sub),
     }
 }
 
+// ===== Rate Limiting =====
+
+async fn limit_public_requests(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = client_ip(peer.ip(), request.headers(), &state.config.trusted_proxies);
+    let usage = state.public_limiter.check(ip);
+    limited(usage, request, next).await
+}
+
+/// Limits signed-in routes per user, so one account can't hog them from many addresses.
+/// Requests that don't authenticate go straight through for the handler to turn away.
+/// The user found is passed on to the handler as [`Identified`].
+async fn limit_user_requests(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match state.auth.identify(request.headers()).await {
+        Ok(user_id) => {
+            let usage = state.user_limiter.check(user_id.clone());
+            request.extensions_mut().insert(Identified(user_id));
+            limited(usage, request, next).await
+        }
+        Err(_) => next.run(request).await,
+    }
+}
+
+/// Runs the request if `usage` allows it. Either way the caller's standing goes in
+/// `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` (seconds until the
+/// window starts over), so clients can slow down before they get a 429.
+async fn limited(usage: Usage, request: Request, next: Next) -> Response {
+    let mut response = if usage.allowed {
+        next.run(request).await
+    } else {
+        AppError::TooManyRequests.into_response()
+    };
+
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(usage.limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(usage.remaining));
+    headers.insert(
+        "x-ratelimit-reset",
+        HeaderValue::from(usage.reset.as_secs_f64().ceil() as u64),
+    );
+    response
+}
+
+/// The address a request really came from. `X-Forwarded-For` is only believed when the
+/// connection comes from one of our own proxies, and then only up to the first hop
+/// that isn't one, so clients can't dodge the limit by sending the header themselves.
+fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("X-Forwarded-For")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse().ok())
+        .collect();
+    forwarded
+        .iter()
+        .rev()
+        .find(|hop| !trusted_proxies.contains(hop))
+        .or(forwarded.first())
+        .copied()
+        .unwrap_or(peer)
+}
+
+async fn verify_admin(state: &AppState, headers: &AuthHeaders) -> Result<String, AppError> {
+    let user_id = state.auth.verify(headers).await?;
+    if !state.config.is_admin(&user_id) {
+        return Err(AppError::Forbidden("Admin access required".into()));
+    }
+    Ok(user_id)
+}
+
+/// Whether the caller may see results: anyone once they're public, admins always.
+async fn results_visible_to(state: &AppState, headers: &AuthHeaders) -> bool {
+    state.config.results_visible(Utc::now())
+        || (headers.contains_key("Authorization") && verify_admin(state, headers).await.is_ok())
+}
+
+/// Lets admins see results even while they're hidden from voters.
+async fn ensure_results_visible(state: &AppState, headers: &AuthHeaders) -> Result<(), AppError> {
+    if results_visible_to(state, headers).await {
+        return Ok(());
+    }
+    Err(AppError::Forbidden(
+        "Results are hidden until voting closes".into(),
+    ))
+}
+
+// ===== Schema Check =====
+
+/// Columns the queries rely on, per table, so a missing migration shows up before serving.
+const EXPECTED_COLUMNS: &[(&str, &str)] = &[
+    (
+        "themes",
+        "id, content, description, created_at, deleted_at, always_shown, priority",
+    ),
+    (
+        "votes",
+        "id, user_id, theme_id, vote_type, created_at, updated_at, skip_reason, client_version",
+    ),
+    ("theme_impressions", "user_id, theme_id, first_seen_at"),
+    ("featured_themes", "feature_date, theme_id, created_at"),
+    (
+        "theme_edits",
+        "theme_id, previous_content, new_content, editor_user_id, edited_at",
+    ),
+    ("tags", "id, name"),
+    (
+        "archived_votes",
+        "id, user_id, theme_id, vote_type, skip_reason, created_at, archived_at, archived_by",
+    ),
+    ("theme_tags", "theme_id, tag_id"),
+    ("pending_themes", "user_id, theme_id, served_at"),
+    (
+        "pairwise_votes",
+        "id, user_id, winner_id, loser_id, created_at",
+    ),
+    ("users", "user_id, trust_weight, updated_at"),
+    ("vote_history", "id, vote_id, vote_type, replaced_at"),
+];
+
+async fn check_schema(db: &PgPool) -> anyhow::Result<()> {
+    for (table, columns) in EXPECTED_COLUMNS {
+        sqlx::query(&format!("SELECT {columns} FROM {table} LIMIT 0"))
+            .execute(db)
+            .await
+            .with_context(|| format!("Table {table} is missing or lacks some of: {columns}"))?;
+    }
+
+    // Theme creation leans on this index to reject duplicates
+    let unique_content: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM pg_indexes WHERE tablename = 'themes' AND indexname = 'themes_content_key')",
+    )
+    .fetch_one(db)
+    .await?;
+    anyhow::ensure!(
+        unique_content,
+        "Index themes_content_key is missing: theme content must be unique"
+    );
+
+    // Run a few real queries too, so their column types get decoded
+    let page = Pagination {
+        limit: Some(1),
+        offset: None,
+    };
+    vote_stats(db, &page, None, 0, StatsSort::Yes, None)
+        .await
+        .context("Vote stats query failed")?;
+    user_progress(db, "", false)
+        .await
+        .context("Progress query failed")?;
+    sqlx::query_as::<_, Theme>("SELECT id, content, description FROM themes LIMIT 1")
+        .fetch_optional(db)
+        .await
+        .context("Theme query failed")?;
+
+    Ok(())
+}
+
+/// Numbers requests in logs, so the lines of one request can be picked out.
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Logs as one JSON object per line, for log aggregators.
+fn json_logs<W>(writer: W) -> impl tracing::Subscriber + Send + Sync
+where
+    W: for<'w> tracing_subscriber::fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .json()
+        .with_writer(writer)
+        .finish()
+}
+
 // ===== Main =====
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
-    tracing_subscriber::fmt::init();
+    // JSON lines for log aggregators, human-readable otherwise
+    match env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => json_logs(std::io::stdout).init(),
+        Ok("pretty") | Ok("") | Err(_) => tracing_subscriber::fmt::init(),
+        Ok(other) => anyhow::bail!("LOG_FORMAT must be \"json\" or \"pretty\", got \"{other}\""),
+    }
 
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let database_url = database_url::from_env()?;
+    let config = Arc::new(Config::from_env()?);
+    let blocklist = Blocklist::from_env()?;
+    if !blocklist.is_empty() {
+        tracing::info!("Blocking themes with any of {} words", blocklist.len());
+    }
+    let check_only = config.check_only || env::args().skip(1).any(|arg| arg == "--check");
 
     // Setup database connection
     let db = PgPoolOptions::new()
         .max_connections(5)
+        .acquire_timeout(config.db_acquire_timeout)
         .connect(&database_url)
         .await?;
 
-    let jwks_cache = Arc::new(JwksCache::new(
+    // Pre-deploy gate: fail on a schema mismatch without ever taking traffic
+    if check_only {
+        check_schema(&db).await?;
+        tracing::info!("Schema check passed");
+        return Ok(());
+    }
+
+    let jwks = Jwks::spawn(
         "https://haiqmpqncyioxkwaegiu.supabase.co/auth/v1/.well-known/jwks.json",
-    ));
-    let state = AppState { db, jwks_cache };
+        config.jwks_refresh_every,
+    );
+    let state = AppState {
+        db,
+        auth: Authenticator::Jwks(jwks),
+        public_limiter: Arc::new(RateLimiter::new(
+            config.public_rate_limit,
+            config.public_rate_window,
+        )),
+        user_limiter: Arc::new(RateLimiter::new(
+            config.user_rate_limit,
+            config.user_rate_window,
+        )),
+        theme_count: Arc::new(Cached::new(config.cache_ttl)),
+        blocklist: Arc::new(blocklist),
+        config,
+    };
+
+    let heartbeat = (!state.config.metrics_log_every.is_zero()).then(|| {
+        tokio::spawn(log_metrics(
+            state.db.clone(),
+            state.config.metrics_log_every,
+        ))
+    });
+
+    let app = router(state);
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+    tracing::info!("Server running on http://0.0.0.0:3000");
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
+
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.abort();
+    }
+    tracing::info!("Server stopped");
+    Ok(())
+}
 
-    // Build router
-    let app = Router::new()
+/// Every route, with rate limiting, request tracing and CORS.
+fn router(state: AppState) -> Router {
+    // Anyone can call these, so they're limited per client IP
+    let public = Router::new()
         .route("/", get(root))
         .route("/health", get(health))
-        .route("/themes/next", get(get_next_theme))
-        .route("/themes/vote", post(submit_vote))
+        .route("/config", get(get_config))
+        .route("/themes", get(list_themes))
+        .route("/themes/count", get(count_themes))
+        .route("/themes/:id", get(get_theme))
+        .route("/themes/:id/stats", get(get_theme_stats))
+        .route("/themes/featured", get(get_featured_theme))
+        .route("/themes/random", get(get_random_theme))
+        .route("/results/public", get(get_public_results))
+        .route("/results/pairwise", get(get_pairwise_ranking))
         // TODO: these may have to not exist or be protected.
         .route("/admin/stats", get(get_stats))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            limit_public_requests,
+        ));
+
+    Router::new()
+        .route("/themes", post(create_theme))
+        .route("/themes/:id", put(update_theme).delete(delete_theme))
+        .route("/themes/:id/restore", post(restore_theme))
+        .route("/themes/:id/always-show", post(always_show_theme))
+        .route("/themes/:id/reset-votes", post(reset_theme_votes))
+        .route("/themes/:id/tags", post(add_theme_tag))
+        .route("/themes/:id/tags/:tag", delete(remove_theme_tag))
+        .route("/themes/merge", post(merge_themes))
+        .route("/themes/mine", get(get_my_votes))
+        .route("/themes/next", get(get_next_theme))
+        .route("/themes/remaining", get(get_remaining_themes))
+        .route("/themes/remaining/by-tag", get(get_remaining_by_tag))
+        .route("/themes/progress", get(get_progress))
+        .route("/themes/vote", post(submit_vote))
+        .route("/themes/pair", get(get_theme_pair))
+        .route("/themes/pair/vote", post(submit_pair_vote))
+        .route("/me/export", get(export_my_data))
+        .route("/me/rank", get(get_my_rank))
+        .route("/me/eligibility", get(get_my_eligibility))
+        .route("/auth/me", get(get_current_user))
+        .route("/admin/featured", put(set_featured_theme))
+        .route("/admin/users/:user_id/trust", put(set_user_trust))
+        .route("/admin/recent", get(get_recent_votes))
         .route("/admin/export", get(export_votes))
+        .route("/admin/client-versions", get(get_client_versions))
+        .route("/admin/pairwise/ranking", get(export_pairwise_ranking))
+        .route("/admin/pairwise/coverage", get(get_pairwise_coverage))
+        .route("/admin/stats/at", get(get_stats_at))
+        .route("/admin/stats/diff", get(get_stats_diff))
+        .route("/admin/stats/weighted", get(get_weighted_stats))
+        .route("/admin/tags", get(get_tag_stats))
+        .route("/admin/compare", get(compare_themes))
+        .route("/admin/leaderboard", get(get_leaderboard))
+        .route("/admin/skip-reasons", get(get_skip_reasons))
+        .route("/admin/summary", get(get_summary))
+        .route("/admin/stats/export", get(export_stats))
+        .route("/admin/coverage", get(get_coverage))
+        .route("/admin/report.html", get(get_report))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            limit_user_requests,
+        ))
+        .merge(public)
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(|request: &axum::extract::Request| {
+                    let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+                    tracing::info_span!(
+                        "request",
+                        request_id,
+                        method = %request.method(),
+                        uri = %request.uri(),
+                    )
+                })
+                .on_response(
+                    DefaultOnResponse::new()
+                        .level(tracing::Level::INFO)
+                        .latency_unit(LatencyUnit::Millis),
+                ),
+        )
         .layer(CorsLayer::permissive())
-        .with_state(state);
+        .with_state(state)
+}
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
-    tracing::info!("Server running on http://0.0.0.0:3000");
+/// Resolves on Ctrl-C, or SIGTERM where there is one, so in-flight requests can finish.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {:?}", err);
+            std::future::pending::<()>().await;
+        }
+    };
 
-    axum::serve(listener, app).await?;
-    Ok(())
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("Failed to listen for SIGTERM: {:?}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutting down");
+}
+
+// ===== Metrics Heartbeat =====
+
+/// Live figures logged by the heartbeat.
+#[derive(Debug, sqlx::FromRow)]
+struct VotingMetrics {
+    total_votes: i64,
+    /// Votes cast or changed within the last interval
+    recent_votes: i64,
+    /// Users who voted within the last interval
+    active_voters: i64,
+}
+
+async fn voting_metrics(db: &PgPool, interval: Duration) -> Result<VotingMetrics, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT
+             COUNT(*) AS total_votes,
+             COUNT(*) FILTER (WHERE updated_at > NOW() - $1::FLOAT8 * INTERVAL '1 second')
+                 AS recent_votes,
+             COUNT(DISTINCT user_id)
+                 FILTER (WHERE updated_at > NOW() - $1::FLOAT8 * INTERVAL '1 second')
+                 AS active_voters
+         FROM votes",
+    )
+    .bind(interval.as_secs_f64())
+    .fetch_one(db)
+    .await
+}
+
+async fn log_metrics(db: PgPool, every: Duration) {
+    let mut ticks = tokio::time::interval(every);
+    // The first tick fires straight away, before there's an interval to report on
+    ticks.tick().await;
+    loop {
+        ticks.tick().await;
+        match voting_metrics(&db, every).await {
+            Ok(metrics) => tracing::info!(
+                total_votes = metrics.total_votes,
+                recent_votes = metrics.recent_votes,
+                active_voters = metrics.active_voters,
+                "Voting metrics"
+            ),
+            Err(err) => tracing::warn!("Failed to collect voting metrics: {:?}", err),
+        }
+    }
 }
 
 // ===== Handlers =====
@@ -96,173 +661,5197 @@ async fn root() -> &'static str {
 
 async fn health(State(state): State<AppState>) -> impl IntoResponse {
     // Check DB connection
+    let pool = serde_json::json!({
+        "size": state.db.size(),
+        "idle": state.db.num_idle(),
+        "acquire_timeouts": POOL_TIMEOUTS.load(Ordering::Relaxed),
+    });
     match sqlx::query("SELECT 1").execute(&state.db).await {
         Ok(_) => Json(serde_json::json!({
             "status": "ok",
-            "database": "connected"
+            "database": "connected",
+            "pool": pool
         })),
         Err(_) => Json(serde_json::json!({
             "status": "error",
-            "database": "disconnected"
+            "database": "disconnected",
+            "pool": pool
         })),
     }
 }
 
-async fn get_next_theme(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-) -> Result<Json<ThemeResponse>, AppError> {
-    let user_id = verify_jwt(&state.jwks_cache, &headers).await?;
+/// `maybe` comes last, so it can be left off when it isn't allowed.
+const VOTE_TYPES: &[&str] = &["yes", "no", "skip", "maybe"];
+
+/// The vote types this server takes.
+fn vote_types(config: &Config) -> &'static [&'static str] {
+    if config.allow_maybe {
+        VOTE_TYPES
+    } else {
+        &VOTE_TYPES[..VOTE_TYPES.len() - 1]
+    }
+}
 
-    // Get total themes count
-    let total_themes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM themes")
-        .fetch_one(&state.db)
+/// `Cache-Control` for responses that change rarely, matching the server-side cache.
+fn cache_control(state: &AppState) -> [(header::HeaderName, String); 1] {
+    [(
+        header::CACHE_CONTROL,
+        format!("public, max-age={}", state.config.cache_ttl.as_secs()),
+    )]
+}
+
+async fn get_config(State(state): State<AppState>) -> impl IntoResponse {
+    let config = &state.config;
+    let public = Json(PublicConfig {
+        vote_types: vote_types(config),
+        voting_open: config.is_voting_open(Utc::now()),
+        voting_opens_at: config.voting_opens_at,
+        voting_closes_at: config.voting_closes_at,
+        min_theme_length: config.theme_length.min,
+        max_theme_length: config.theme_length.max,
+        page_size: config.results_page_size,
+        public_results_min_votes: config.public_results_min_votes,
+        allow_revote: config.allow_revote,
+        results_hidden: !config.results_visible(Utc::now()),
+        strategies: Strategy::ALL,
+        default_strategy: config.default_strategy,
+    });
+    (cache_control(&state), public)
+}
+
+/// How many themes are up for a vote. Cached briefly, as every client asks at startup.
+async fn count_themes(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let total = state
+        .theme_count
+        .get_or_load(|| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM themes WHERE deleted_at IS NULL")
+                .fetch_one(&state.db)
+        })
         .await?;
+    Ok((cache_control(&state), Json(ThemeCount { total })))
+}
 
-    // Get themes already voted on by this user
-    let voted_theme_ids: Vec<i32> =
-        sqlx::query_scalar("SELECT theme_id FROM votes WHERE user_id = $1")
-            .bind(&user_id)
-            .fetch_all(&state.db)
-            .await?;
+async fn list_themes(
+    State(state): State<AppState>,
+    Query(query): Query<ThemeListQuery>,
+) -> Result<Json<Vec<Theme>>, AppError> {
+    let search = query
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let tags = requested_tags(query.tags.as_deref())?;
 
-    // Get a random unvoted theme
-    let theme: Option<Theme> = if voted_theme_ids.is_empty() {
-        sqlx::query_as("SELECT id, content FROM themes ORDER BY RANDOM() LIMIT 1")
-            .fetch_optional(&state.db)
-            .await?
-    } else {
-        sqlx::query_as(
-            "SELECT id, content FROM themes 
-             WHERE id != ALL($1) 
-             ORDER BY RANDOM() 
-             LIMIT 1",
-        )
-        .bind(&voted_theme_ids)
-        .fetch_optional(&state.db)
-        .await?
-    };
+    let themes: Vec<Theme> = sqlx::query_as(&format!(
+        "SELECT t.id, t.content, t.description FROM themes t
+         WHERE t.deleted_at IS NULL
+           AND ($1::TEXT IS NULL OR strpos(lower(t.content), lower($1)) > 0)
+           AND {TAG_FILTER}
+         ORDER BY t.id",
+    ))
+    .bind(search)
+    .bind(&tags)
+    .bind(query.tag_match == TagMatch::All)
+    .fetch_all(&state.db)
+    .await?;
 
-    Ok(Json(ThemeResponse {
-        theme,
-        total: total_themes,
-        seen: voted_theme_ids.len() as i64,
-    }))
+    Ok(Json(themes))
 }
 
-async fn submit_vote(
+async fn get_theme(
     State(state): State<AppState>,
-    headers: HeaderMap,
-    Json(vote_req): Json<VoteRequest>,
-) -> Result<StatusCode, AppError> {
-    let user_id = verify_jwt(&state.jwks_cache, &headers).await?;
+    Path(theme_id): Path<i32>,
+) -> Result<Json<Theme>, AppError> {
+    let theme: Option<Theme> = sqlx::query_as(
+        "SELECT id, content, description FROM themes WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(theme_id)
+    .fetch_optional(&state.db)
+    .await?;
 
-    // Validate vote type
-    if !["yes", "no", "skip"].contains(&vote_req.vote_type.as_str()) {
-        return Err(AppError::BadRequest("Invalid vote type".into()));
-    }
+    theme
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("Theme not found".into()))
+}
 
-    // Check theme exists
-    let theme_exists: bool =
-        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM themes WHERE id = $1)")
-            .bind(vote_req.theme_id)
-            .fetch_one(&state.db)
-            .await?;
+/// One theme's vote breakdown, without computing stats for every theme.
+async fn get_theme_stats(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(theme_id): Path<i32>,
+) -> Result<Json<ThemeTally>, AppError> {
+    ensure_results_visible(&state, &headers).await?;
+    Ok(Json(theme_tally(&state.db, theme_id).await?))
+}
 
-    if !theme_exists {
-        return Err(AppError::BadRequest("Theme not found".into()));
+async fn create_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Json(req): Json<CreateThemeRequest>,
+) -> Result<(StatusCode, Json<Theme>), AppError> {
+    verify_admin(&state, &headers).await?;
+
+    let content = theme_content::normalize(&req.content);
+    let content = content.as_str();
+    state
+        .config
+        .theme_length
+        .validate(content)
+        .map_err(AppError::BadRequest)?;
+    check_blocklist(&state, content)?;
+    let description = req
+        .description
+        .as_deref()
+        .map(theme_content::description)
+        .transpose()
+        .map_err(AppError::BadRequest)?
+        .flatten();
+    if let Some(description) = &description {
+        check_blocklist(&state, description)?;
     }
 
-    // Insert or update vote
-    sqlx::query(
-        "INSERT INTO votes (user_id, theme_id, vote_type) 
-         VALUES ($1, $2, $3)
-         ON CONFLICT (user_id, theme_id) 
-         DO UPDATE SET vote_type = $3, created_at = NOW()",
+    // The unique index decides, so two concurrent creates can't both get through
+    let theme: Option<Theme> = sqlx::query_as(
+        "INSERT INTO themes (content, description) VALUES ($1, $2)
+         ON CONFLICT (content) DO NOTHING
+         RETURNING id, content, description",
     )
-    .bind(&user_id)
-    .bind(vote_req.theme_id)
-    .bind(&vote_req.vote_type)
-    .execute(&state.db)
+    .bind(content)
+    .bind(description)
+    .fetch_optional(&state.db)
     .await?;
 
-    Ok(StatusCode::OK)
+    let Some(theme) = theme else {
+        return Err(AppError::Conflict("Theme already exists".into()));
+    };
+    state.theme_count.invalidate();
+
+    Ok((StatusCode::CREATED, Json(theme)))
 }
 
-async fn get_stats(State(state): State<AppState>) -> Result<Json<Vec<VoteStats>>, AppError> {
-    let stats: Vec<VoteStats> = sqlx::query_as!(
-        VoteStats,
-        r#"
-        SELECT 
-            t.id as theme_id,
-            t.content,
-            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) as "yes_votes!",
-            COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) as "no_votes!",
-            COUNT(CASE WHEN v.vote_type = 'skip' THEN 1 END) as "skip_votes!",
-            COUNT(v.id) as "total_votes!"
-        FROM themes t
-        LEFT JOIN votes v ON t.id = v.theme_id
-        GROUP BY t.id, t.content
-        ORDER BY COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) DESC
-        "#
+/// Rejects text containing a blocked word, logging it so organizers can see what was refused.
+fn check_blocklist(state: &AppState, text: &str) -> Result<(), AppError> {
+    state.blocklist.check(text).map_err(|reason| {
+        tracing::info!(text, "Rejected theme: {reason}");
+        AppError::BadRequest(reason)
+    })
+}
+
+async fn update_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(theme_id): Path<i32>,
+    Json(req): Json<UpdateThemeRequest>,
+) -> Result<Json<Theme>, AppError> {
+    let editor = verify_admin(&state, &headers).await?;
+
+    let content = theme_content::normalize(&req.content);
+    let content = content.as_str();
+    state
+        .config
+        .theme_length
+        .validate(content)
+        .map_err(AppError::BadRequest)?;
+    check_blocklist(&state, content)?;
+
+    let mut tx = state.db.begin().await?;
+
+    let previous: Option<String> = sqlx::query_scalar(
+        "SELECT content FROM themes WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
     )
-    .fetch_all(&state.db)
+    .bind(theme_id)
+    .fetch_optional(&mut *tx)
     .await?;
 
-    Ok(Json(stats))
-}
+    let Some(previous) = previous else {
+        return Err(AppError::NotFound("Theme not found".into()));
+    };
 
-async fn export_votes(State(state): State<AppState>) -> Result<Json<Vec<ExportVote>>, AppError> {
-    let votes: Vec<ExportVote> = sqlx::query_as!(
-        ExportVote,
-        r#"
-        SELECT 
-            v.user_id,
-            v.theme_id,
-            t.content as theme_content,
-            v.vote_type
-        FROM votes v 
-        JOIN themes t ON v.theme_id = t.id 
-        ORDER BY v.created_at DESC
-        "#
+    let duplicate: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM themes WHERE content = $1 AND id != $2)")
+            .bind(content)
+            .bind(theme_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    if duplicate {
+        return Err(AppError::Conflict(
+            "Another theme already has this content".into(),
+        ));
+    }
+
+    // Votes reference the theme id, so they stay attached to the edited theme
+    // A concurrent create or edit can still take the content after the check above
+    let theme: Theme = sqlx::query_as(
+        "UPDATE themes SET content = $1 WHERE id = $2 RETURNING id, content, description",
     )
-    .fetch_all(&state.db)
+    .bind(content)
+    .bind(theme_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| {
+        if is_unique_violation(&err) {
+            AppError::Conflict("Another theme already has this content".into())
+        } else {
+            err.into()
+        }
+    })?;
+
+    sqlx::query(
+        "INSERT INTO theme_edits (theme_id, previous_content, new_content, editor_user_id)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(theme_id)
+    .bind(&previous)
+    .bind(content)
+    .bind(&editor)
+    .execute(&mut *tx)
     .await?;
 
-    Ok(Json(votes))
+    tx.commit().await?;
+
+    Ok(Json(theme))
 }
 
-// ===== Error Handling =====
+async fn delete_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(theme_id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    verify_admin(&state, &headers).await?;
 
-enum AppError {
-    Unauthorized,
-    BadRequest(String),
-    Database(sqlx::Error),
-}
+    // Soft delete so the theme's votes stay exportable
+    let result =
+        sqlx::query("UPDATE themes SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL")
+            .bind(theme_id)
+            .execute(&state.db)
+            .await?;
 
-impl From<sqlx::Error> for AppError {
-    fn from(err: sqlx::Error) -> Self {
-        AppError::Database(err)
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Theme not found".into()));
     }
+    state.theme_count.invalidate();
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, message) = match self {
-            AppError::Unauthorized => (
-                StatusCode::UNAUTHORIZED,
-                "Unauthorized - Invalid or missing JWT token".to_string(),
-            ),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            AppError::Database(err) => {
-                tracing::error!("Database error: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Database error".to_string(),
-                )
-            }
-        };
+/// Clears every vote on a theme so everyone gets it again, archiving the old votes.
+async fn reset_theme_votes(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(theme_id): Path<i32>,
+    Query(query): Query<ResetVotesQuery>,
+) -> Result<Json<ResetVotesResponse>, AppError> {
+    let admin_id = verify_admin(&state, &headers).await?;
 
-        (status, message).into_response()
+    if !query.confirm {
+        return Err(AppError::BadRequest(
+            "This deletes every vote on the theme; pass confirm=true to go ahead".into(),
+        ));
+    }
+
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM themes WHERE id = $1 AND deleted_at IS NULL)",
+    )
+    .bind(theme_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !exists {
+        return Err(AppError::NotFound("Theme not found".into()));
+    }
+
+    let result = sqlx::query(
+        "WITH cleared AS (
+             DELETE FROM votes WHERE theme_id = $1
+             RETURNING id, user_id, theme_id, vote_type, skip_reason, created_at
+         )
+         INSERT INTO archived_votes
+             (id, user_id, theme_id, vote_type, skip_reason, created_at, archived_by)
+         SELECT id, user_id, theme_id, vote_type, skip_reason, created_at, $2 FROM cleared",
+    )
+    .bind(theme_id)
+    .bind(&admin_id)
+    .execute(&state.db)
+    .await?;
+
+    Ok(Json(ResetVotesResponse {
+        theme_id,
+        deleted: result.rows_affected(),
+    }))
+}
+
+/// Folds duplicate themes into `keep_id`: their votes move over (a user who voted on
+/// several keeps only their latest vote) and the duplicates are soft-deleted.
+async fn merge_themes(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Json(req): Json<MergeThemesRequest>,
+) -> Result<Json<MergeThemesResponse>, AppError> {
+    verify_admin(&state, &headers).await?;
+
+    let mut merge_ids = req.merge_ids;
+    merge_ids.sort_unstable();
+    merge_ids.dedup();
+    if merge_ids.is_empty() {
+        return Err(AppError::BadRequest("Nothing to merge".into()));
+    }
+    if merge_ids.contains(&req.keep_id) {
+        return Err(AppError::BadRequest(
+            "Can't merge a theme into itself".into(),
+        ));
+    }
+    let all_ids: Vec<i32> = merge_ids.iter().copied().chain([req.keep_id]).collect();
+
+    let mut tx = state.db.begin().await?;
+
+    // Lock the themes involved so a concurrent merge or delete can't interleave
+    let active: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM (
+             SELECT id FROM themes WHERE id = ANY($1) AND deleted_at IS NULL FOR UPDATE
+         ) locked",
+    )
+    .bind(&all_ids)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if active != all_ids.len() as i64 {
+        return Err(AppError::NotFound("One or more themes not found".into()));
+    }
+
+    // Keep only each user's most recent vote across the themes being merged
+    sqlx::query(
+        "DELETE FROM votes v
+         WHERE v.theme_id = ANY($1)
+           AND EXISTS (
+               SELECT 1 FROM votes w
+               WHERE w.user_id = v.user_id
+                 AND w.theme_id = ANY($1)
+                 AND (w.updated_at, w.id) > (v.updated_at, v.id)
+           )",
+    )
+    .bind(&all_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE votes SET theme_id = $1 WHERE theme_id = ANY($2)")
+        .bind(req.keep_id)
+        .bind(&merge_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    // Head-to-head picks follow too. Picks between the merged themes would be against
+    // itself, and of the picks that end up on the same pair only each user's latest stays
+    sqlx::query("DELETE FROM pairwise_votes WHERE winner_id = ANY($1) AND loser_id = ANY($1)")
+        .bind(&all_ids)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "WITH mapped AS (
+             SELECT id, user_id, created_at, LEAST(w, l) AS lo, GREATEST(w, l) AS hi
+             FROM (
+                 SELECT id, user_id, created_at,
+                        CASE WHEN winner_id = ANY($2) THEN $1 ELSE winner_id END AS w,
+                        CASE WHEN loser_id = ANY($2) THEN $1 ELSE loser_id END AS l
+                 FROM pairwise_votes
+                 WHERE winner_id = ANY($3) OR loser_id = ANY($3)
+             ) picks
+         )
+         DELETE FROM pairwise_votes p
+         USING mapped a
+         WHERE p.id = a.id
+           AND EXISTS (
+               SELECT 1 FROM mapped b
+               WHERE b.user_id = a.user_id
+                 AND (b.lo, b.hi) = (a.lo, a.hi)
+                 AND (b.created_at, b.id) > (a.created_at, a.id)
+           )",
+    )
+    .bind(req.keep_id)
+    .bind(&merge_ids)
+    .bind(&all_ids)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE pairwise_votes SET
+             winner_id = CASE WHEN winner_id = ANY($2) THEN $1 ELSE winner_id END,
+             loser_id = CASE WHEN loser_id = ANY($2) THEN $1 ELSE loser_id END
+         WHERE winner_id = ANY($2) OR loser_id = ANY($2)",
+    )
+    .bind(req.keep_id)
+    .bind(&merge_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    // Carry over who has seen the theme and how it was tagged
+    sqlx::query(
+        "INSERT INTO theme_impressions (user_id, theme_id, first_seen_at)
+         SELECT user_id, $1, MIN(first_seen_at) FROM theme_impressions
+         WHERE theme_id = ANY($2)
+         GROUP BY user_id
+         ON CONFLICT (user_id, theme_id)
+         DO UPDATE SET first_seen_at = LEAST(theme_impressions.first_seen_at, EXCLUDED.first_seen_at)",
+    )
+    .bind(req.keep_id)
+    .bind(&merge_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO theme_tags (theme_id, tag_id)
+         SELECT DISTINCT $1, tag_id FROM theme_tags WHERE theme_id = ANY($2)
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(req.keep_id)
+    .bind(&merge_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE themes SET deleted_at = NOW() WHERE id = ANY($1)")
+        .bind(&merge_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    let total_votes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM votes WHERE theme_id = $1")
+        .bind(req.keep_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    state.theme_count.invalidate();
+
+    Ok(Json(MergeThemesResponse {
+        theme_id: req.keep_id,
+        merged_ids: merge_ids,
+        total_votes,
+    }))
+}
+
+/// Tags a theme, creating the tag if it's new. Returns the theme's tags.
+async fn add_theme_tag(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(theme_id): Path<i32>,
+    Json(req): Json<AddTagRequest>,
+) -> Result<Json<Vec<String>>, AppError> {
+    verify_admin(&state, &headers).await?;
+
+    let tag = tags::normalize(&req.tag).map_err(AppError::BadRequest)?;
+
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM themes WHERE id = $1 AND deleted_at IS NULL)",
+    )
+    .bind(theme_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !exists {
+        return Err(AppError::NotFound("Theme not found".into()));
+    }
+
+    tag_theme(&state.db, theme_id, &tag).await?;
+
+    let tags: Vec<String> = sqlx::query_scalar(
+        "SELECT g.name FROM theme_tags tt
+         JOIN tags g ON g.id = tt.tag_id
+         WHERE tt.theme_id = $1
+         ORDER BY g.name",
+    )
+    .bind(theme_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(tags))
+}
+
+async fn remove_theme_tag(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path((theme_id, tag)): Path<(i32, String)>,
+) -> Result<StatusCode, AppError> {
+    verify_admin(&state, &headers).await?;
+
+    let result = sqlx::query(
+        "DELETE FROM theme_tags
+         WHERE theme_id = $1 AND tag_id = (SELECT id FROM tags WHERE name = lower($2))",
+    )
+    .bind(theme_id)
+    .bind(tag.trim())
+    .execute(&state.db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Theme doesn't have that tag".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn restore_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(theme_id): Path<i32>,
+) -> Result<Json<Theme>, AppError> {
+    verify_admin(&state, &headers).await?;
+
+    let theme: Option<Theme> = sqlx::query_as(
+        "UPDATE themes SET deleted_at = NULL
+         WHERE id = $1 AND deleted_at IS NOT NULL
+         RETURNING id, content, description",
+    )
+    .bind(theme_id)
+    .fetch_optional(&state.db)
+    .await?;
+    state.theme_count.invalidate();
+
+    theme
+        .map(Json)
+        .ok_or(AppError::NotFound("No deleted theme with this id".into()))
+}
+
+/// Exempts a theme from the low-quality filter, bringing it back if it was hidden.
+async fn always_show_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(theme_id): Path<i32>,
+) -> Result<Json<Theme>, AppError> {
+    verify_admin(&state, &headers).await?;
+
+    let theme: Option<Theme> = sqlx::query_as(
+        "UPDATE themes SET always_shown = TRUE
+         WHERE id = $1 AND deleted_at IS NULL
+         RETURNING id, content, description",
+    )
+    .bind(theme_id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    theme
+        .map(Json)
+        .ok_or(AppError::NotFound("Theme not found".into()))
+}
+
+/// Added to each theme's vote count in weighted selection, so unvoted themes
+/// don't get infinite weight and popular ones still show up occasionally.
+const WEIGHT_SMOOTHING: f64 = 1.0;
+
+async fn get_next_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<NextThemeQuery>,
+) -> Result<Json<ThemeResponse>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+    let tags = requested_tags(query.tags.as_deref())?;
+
+    let exclude_skips = query
+        .exclude_skips
+        .unwrap_or(state.config.progress_excludes_skips);
+    let (seen, total) = user_progress(&state.db, &user_id, exclude_skips).await?;
+
+    // Nothing left to serve once the voting window is over
+    if !state.config.is_voting_open(Utc::now()) {
+        return Ok(Json(ThemeResponse {
+            theme: None,
+            total,
+            seen,
+            seen_excludes_skips: exclude_skips,
+            closed: true,
+        }));
+    }
+
+    // Re-serve the theme handed out last time, as long as it's still up for a vote
+    let pending: Option<Theme> = if state.config.sticky_next_theme {
+        sqlx::query_as(&format!(
+            "SELECT t.id, t.content, t.description FROM pending_themes p
+             JOIN themes t ON t.id = p.theme_id
+             WHERE p.user_id = $1
+               AND t.deleted_at IS NULL
+               AND {UNVOTED_FILTER}
+               AND {TAG_FILTER}
+               AND {QUALITY_FILTER}",
+        ))
+        .bind(&user_id)
+        .bind(&tags)
+        .bind(query.tag_match == TagMatch::All)
+        .bind(state.config.resurface_skips)
+        .bind(state.config.skip_cooldown_votes)
+        .bind(state.config.skip_cooldown_minutes)
+        .bind(state.config.hide_skip_ratio)
+        .bind(state.config.hide_min_votes)
+        .fetch_optional(&state.db)
+        .await?
+    } else {
+        None
+    };
+    if pending.is_some() {
+        return Ok(Json(ThemeResponse {
+            theme: pending,
+            total,
+            seen,
+            seen_excludes_skips: exclude_skips,
+            closed: false,
+        }));
+    }
+
+    // Get an unvoted theme, letting the database do the exclusion. Higher priority
+    // themes go first; the strategy picks between those of equal priority.
+    let strategy = query.strategy.unwrap_or(state.config.default_strategy);
+    let theme: Option<Theme> = match strategy {
+        Strategy::Random => {
+            sqlx::query_as(&format!(
+                "SELECT t.id, t.content, t.description FROM themes t
+                 WHERE t.deleted_at IS NULL
+                   AND {UNVOTED_FILTER}
+                   AND {TAG_FILTER}
+                   AND {QUALITY_FILTER}
+                 ORDER BY t.priority DESC, RANDOM()
+                 LIMIT 1",
+            ))
+            .bind(&user_id)
+            .bind(&tags)
+            .bind(query.tag_match == TagMatch::All)
+            .bind(state.config.resurface_skips)
+            .bind(state.config.skip_cooldown_votes)
+            .bind(state.config.skip_cooldown_minutes)
+            .bind(state.config.hide_skip_ratio)
+            .bind(state.config.hide_min_votes)
+            .fetch_optional(&state.db)
+            .await?
+        }
+        Strategy::Weighted => {
+            // Weighted sampling (Efraimidis-Spirakis): the smallest -ln(u) / weight wins,
+            // with weight = 1 / (votes + smoothing).
+            sqlx::query_as(&format!(
+                "SELECT t.id, t.content, t.description FROM themes t
+                 LEFT JOIN (
+                     SELECT theme_id, COUNT(*) AS votes FROM votes GROUP BY theme_id
+                 ) c ON c.theme_id = t.id
+                 WHERE t.deleted_at IS NULL
+                   AND {UNVOTED_FILTER}
+                   AND {TAG_FILTER}
+                   AND {QUALITY_FILTER}
+                 ORDER BY t.priority DESC, -LN(1 - RANDOM()) * (COALESCE(c.votes, 0) + $9)
+                 LIMIT 1",
+            ))
+            .bind(&user_id)
+            .bind(&tags)
+            .bind(query.tag_match == TagMatch::All)
+            .bind(state.config.resurface_skips)
+            .bind(state.config.skip_cooldown_votes)
+            .bind(state.config.skip_cooldown_minutes)
+            .bind(state.config.hide_skip_ratio)
+            .bind(state.config.hide_min_votes)
+            .bind(WEIGHT_SMOOTHING)
+            .fetch_optional(&state.db)
+            .await?
+        }
+    };
+
+    if let Some(theme) = &theme {
+        if state.config.sticky_next_theme {
+            sqlx::query(
+                "INSERT INTO pending_themes (user_id, theme_id) VALUES ($1, $2)
+                 ON CONFLICT (user_id) DO UPDATE SET theme_id = $2, served_at = NOW()",
+            )
+            .bind(&user_id)
+            .bind(theme.id)
+            .execute(&state.db)
+            .await?;
+        }
+        record_impression(state.db.clone(), user_id, theme.id);
+    }
+
+    Ok(Json(ThemeResponse {
+        theme,
+        total,
+        seen,
+        seen_excludes_skips: exclude_skips,
+        closed: false,
+    }))
+}
+
+/// Every active theme the caller has yet to vote on, oldest first.
+async fn get_remaining_themes(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(page): Query<Pagination>,
+) -> Result<Json<Vec<Theme>>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    let themes: Vec<Theme> = sqlx::query_as(
+        "SELECT t.id, t.content, t.description FROM themes t
+         WHERE t.deleted_at IS NULL
+           AND NOT EXISTS (
+               SELECT 1 FROM votes v WHERE v.theme_id = t.id AND v.user_id = $1
+           )
+         ORDER BY t.id
+         LIMIT $2 OFFSET $3",
+    )
+    .bind(&user_id)
+    .bind(page.limit)
+    .bind(page.offset.unwrap_or(0))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(themes))
+}
+
+async fn get_remaining_by_tag(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Vec<RemainingByTag>>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    let counts: Vec<RemainingByTag> = sqlx::query_as(
+        "WITH unvoted AS (
+             SELECT t.id FROM themes t
+             WHERE t.deleted_at IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM votes v WHERE v.theme_id = t.id AND v.user_id = $1
+               )
+         )
+         SELECT * FROM (
+             SELECT g.name AS tag, COUNT(u.id) AS remaining
+             FROM tags g
+             LEFT JOIN theme_tags tt ON tt.tag_id = g.id
+             LEFT JOIN unvoted u ON u.id = tt.theme_id
+             GROUP BY g.name
+             UNION ALL
+             SELECT NULL, COUNT(*) FROM unvoted u
+             WHERE NOT EXISTS (SELECT 1 FROM theme_tags tt WHERE tt.theme_id = u.id)
+         ) counts
+         ORDER BY tag NULLS LAST",
+    )
+    .bind(&user_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(counts))
+}
+
+/// Unauthenticated preview: any random active theme, no per-user exclusion.
+async fn get_random_theme(State(state): State<AppState>) -> Result<Json<Theme>, AppError> {
+    let theme: Option<Theme> = sqlx::query_as(
+        "SELECT id, content, description FROM themes WHERE deleted_at IS NULL ORDER BY RANDOM() LIMIT 1",
+    )
+    .fetch_optional(&state.db)
+    .await?;
+
+    theme
+        .map(Json)
+        .ok_or(AppError::NotFound("No themes yet".into()))
+}
+
+/// Today's featured theme. Its vote counts are left out while results are hidden from the caller.
+async fn get_featured_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<FeaturedTheme>, AppError> {
+    let featured: Option<FeaturedTheme> = sqlx::query_as!(
+        FeaturedTheme,
+        r#"
+        SELECT
+            f.feature_date as date,
+            t.id as theme_id,
+            t.content,
+            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) as "yes_votes?",
+            COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) as "no_votes?",
+            COUNT(CASE WHEN v.vote_type = 'skip' THEN 1 END) as "skip_votes?",
+            COUNT(CASE WHEN v.vote_type = 'maybe' THEN 1 END) as "maybe_votes?",
+            COUNT(v.id) as "total_votes?"
+        FROM featured_themes f
+        JOIN themes t ON t.id = f.theme_id
+        LEFT JOIN votes v ON v.theme_id = t.id
+        WHERE f.feature_date = $1 AND t.deleted_at IS NULL
+        GROUP BY f.feature_date, t.id, t.content
+        "#,
+        Utc::now().date_naive()
+    )
+    .fetch_optional(&state.db)
+    .await?;
+
+    let mut featured = featured.ok_or(AppError::NotFound("No featured theme today".into()))?;
+    if !results_visible_to(&state, &headers).await {
+        featured.hide_votes();
+    }
+    Ok(Json(featured))
+}
+
+async fn set_featured_theme(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Json(req): Json<SetFeaturedRequest>,
+) -> Result<StatusCode, AppError> {
+    verify_admin(&state, &headers).await?;
+
+    let theme_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM themes WHERE id = $1 AND deleted_at IS NULL)",
+    )
+    .bind(req.theme_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !theme_exists {
+        return Err(AppError::NotFound("Theme not found".into()));
+    }
+
+    sqlx::query(
+        "INSERT INTO featured_themes (feature_date, theme_id) VALUES ($1, $2)
+         ON CONFLICT (feature_date) DO UPDATE SET theme_id = $2, created_at = NOW()",
+    )
+    .bind(req.date.unwrap_or_else(|| Utc::now().date_naive()))
+    .bind(req.theme_id)
+    .execute(&state.db)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Largest trust weight an admin may give one user.
+const MAX_TRUST_WEIGHT: f64 = 100.0;
+
+/// Sets how much a user's votes count in weighted stats.
+async fn set_user_trust(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Path(user_id): Path<String>,
+    Json(req): Json<SetTrustRequest>,
+) -> Result<StatusCode, AppError> {
+    verify_admin(&state, &headers).await?;
+
+    if !(0.0..=MAX_TRUST_WEIGHT).contains(&req.trust_weight) {
+        return Err(AppError::BadRequest(format!(
+            "Trust weight must be between 0 and {MAX_TRUST_WEIGHT}"
+        )));
+    }
+
+    sqlx::query(
+        "INSERT INTO users (user_id, trust_weight) VALUES ($1, $2)
+         ON CONFLICT (user_id) DO UPDATE SET trust_weight = $2, updated_at = NOW()",
+    )
+    .bind(&user_id)
+    .bind(req.trust_weight)
+    .execute(&state.db)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_my_votes(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Vec<MyVote>>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    let votes: Vec<MyVote> = sqlx::query_as(
+        "SELECT v.theme_id, t.content, v.vote_type, v.updated_at AS voted_at
+         FROM votes v
+         JOIN themes t ON t.id = v.theme_id
+         WHERE v.user_id = $1 AND t.deleted_at IS NULL
+         ORDER BY v.updated_at DESC",
+    )
+    .bind(&user_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(votes))
+}
+
+/// All of the caller's votes, including those on themes since removed.
+async fn export_my_data(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<MyDataExport>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    let votes: Vec<MyExportedVote> = sqlx::query_as(
+        "SELECT v.theme_id, t.content, v.vote_type, v.skip_reason,
+                v.created_at AS voted_at, t.deleted_at AS theme_deleted_at
+         FROM votes v
+         JOIN themes t ON t.id = v.theme_id
+         WHERE v.user_id = $1
+         ORDER BY v.created_at",
+    )
+    .bind(&user_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(MyDataExport {
+        user_id,
+        exported_at: Utc::now(),
+        votes,
+    }))
+}
+
+/// Who the token belongs to, so clients can check it's accepted without side effects.
+async fn get_current_user(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<CurrentUser>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+    Ok(Json(CurrentUser { user_id }))
+}
+
+async fn get_my_eligibility(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Eligibility>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    // The same checks `submit_vote` makes before looking at the theme
+    let now = Utc::now();
+    let usage = state.user_limiter.usage(&user_id);
+    let votes_remaining_today = match state.config.daily_vote_limit {
+        Some(limit) => Some((limit - votes_cast_today(&state.db, &user_id, None).await?).max(0)),
+        None => None,
+    };
+    let reason = if !state.config.is_voting_open(now) {
+        Some(match state.config.voting_opens_at {
+            Some(opens) if now < opens => {
+                format!("Voting opens at {}", opens.format("%Y-%m-%d %H:%M UTC"))
+            }
+            _ => "Voting is closed".to_string(),
+        })
+    } else if votes_remaining_today == Some(0) {
+        Some(DAILY_LIMIT_REACHED.to_string())
+    } else if usage.remaining == 0 {
+        Some(format!(
+            "Too many requests, try again in {} seconds",
+            usage.reset.as_secs_f64().ceil() as u64
+        ))
+    } else {
+        None
+    };
+
+    Ok(Json(Eligibility {
+        can_vote: reason.is_none(),
+        reason,
+        votes_remaining_today,
+    }))
+}
+
+async fn get_my_rank(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<VoterRank>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    let (votes, voters, behind): (i64, i64, i64) = sqlx::query_as(
+        "WITH counts AS (
+             SELECT user_id, COUNT(*) AS votes FROM votes GROUP BY user_id
+         ),
+         mine AS (
+             SELECT COALESCE((SELECT votes FROM counts WHERE user_id = $1), 0) AS votes
+         )
+         SELECT
+             mine.votes,
+             (SELECT COUNT(*) FROM counts),
+             (SELECT COUNT(*) FROM counts WHERE counts.votes < mine.votes)
+         FROM mine",
+    )
+    .bind(&user_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    let others = if votes > 0 { voters - 1 } else { voters };
+    let ahead_of_percent = if others > 0 {
+        behind as f64 * 100.0 / others as f64
+    } else {
+        0.0
+    };
+
+    Ok(Json(VoterRank {
+        votes,
+        voters,
+        ahead_of_percent,
+    }))
+}
+
+async fn get_progress(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<ProgressQuery>,
+) -> Result<Json<ProgressResponse>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    let exclude_skips = query
+        .exclude_skips
+        .unwrap_or(state.config.progress_excludes_skips);
+    let (seen, total) = user_progress(&state.db, &user_id, exclude_skips).await?;
+    let percent = if total > 0 {
+        seen as f64 * 100.0 / total as f64
+    } else {
+        0.0
+    };
+
+    Ok(Json(ProgressResponse {
+        seen,
+        total,
+        percent,
+        seen_excludes_skips: exclude_skips,
+    }))
+}
+
+/// Remembers that `theme_id` was served to `user_id`, without delaying the response.
+fn record_impression(db: PgPool, user_id: String, theme_id: i32) {
+    tokio::spawn(async move {
+        let result = sqlx::query(
+            "INSERT INTO theme_impressions (user_id, theme_id) VALUES ($1, $2)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(&user_id)
+        .bind(theme_id)
+        .execute(&db)
+        .await;
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to record impression: {:?}", err);
+        }
+    });
+}
+
+/// Returns `(seen, total)`: how many active themes `user_id` has voted on (leaving out skips
+/// when `exclude_skips` is set), out of all active themes.
+async fn user_progress(
+    db: &PgPool,
+    user_id: &str,
+    exclude_skips: bool,
+) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as(
+        "SELECT
+            (SELECT COUNT(*) FROM votes v
+             JOIN themes t ON t.id = v.theme_id
+             WHERE v.user_id = $1 AND t.deleted_at IS NULL
+               AND NOT ($2 AND v.vote_type = 'skip')),
+            (SELECT COUNT(*) FROM themes WHERE deleted_at IS NULL)",
+    )
+    .bind(user_id)
+    .bind(exclude_skips)
+    .fetch_one(db)
+    .await
+}
+
+const MAX_SKIP_REASON_LENGTH: usize = 200;
+/// Longer `X-Client-Version` values aren't stored.
+const MAX_CLIENT_VERSION_LENGTH: usize = 64;
+
+const DAILY_LIMIT_REACHED: &str = "You've reached today's vote limit, come back tomorrow";
+
+/// Votes the user has cast or changed since midnight UTC, leaving out `except_theme`.
+async fn votes_cast_today<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    user_id: &str,
+    except_theme: Option<i32>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM votes
+         WHERE user_id = $1
+           AND updated_at >= date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+           AND theme_id IS DISTINCT FROM $2",
+    )
+    .bind(user_id)
+    .bind(except_theme)
+    .fetch_one(executor)
+    .await
+}
+
+async fn submit_vote(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    JsonOrForm(vote_req): JsonOrForm<VoteRequest>,
+) -> Result<StatusCode, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    if !state.config.is_voting_open(Utc::now()) {
+        return Err(AppError::Forbidden("Voting is closed".into()));
+    }
+
+    // Validate vote type
+    if !vote_types(&state.config).contains(&vote_req.vote_type.as_str()) {
+        return Err(AppError::BadRequest("Invalid vote type".into()));
+    }
+
+    // Only kept for diagnostics, so a missing or odd version never gets a vote rejected
+    let client_version = headers
+        .get("x-client-version")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|version| !version.is_empty() && version.len() <= MAX_CLIENT_VERSION_LENGTH);
+
+    let reason = vote_req
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty());
+    if let Some(reason) = reason {
+        if vote_req.vote_type != "skip" {
+            return Err(AppError::BadRequest(
+                "A reason can only be given when skipping".into(),
+            ));
+        }
+        if reason.chars().count() > MAX_SKIP_REASON_LENGTH {
+            return Err(AppError::BadRequest(format!(
+                "Skip reason is too long (maximum is {MAX_SKIP_REASON_LENGTH} characters)"
+            )));
+        }
+    }
+
+    // The shared row lock keeps the theme from being removed until the vote is in,
+    // while still letting other votes on it through
+    let mut tx = state.db.begin().await?;
+    let active: Option<bool> =
+        sqlx::query_scalar("SELECT deleted_at IS NULL FROM themes WHERE id = $1 FOR SHARE")
+            .bind(vote_req.theme_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+    if active != Some(true) {
+        return Err(AppError::NotFound("Theme not found".into()));
+    }
+
+    if let Some(limit) = state.config.daily_vote_limit {
+        // Sending the vote this theme already has again doesn't count twice
+        if votes_cast_today(&mut *tx, &user_id, Some(vote_req.theme_id)).await? >= limit {
+            return Err(AppError::Forbidden(DAILY_LIMIT_REACHED.into()));
+        }
+    }
+
+    if !state.config.allow_revote {
+        // Votes are final: the UNIQUE (user_id, theme_id) constraint makes the
+        // first vote win, even against a concurrent second one.
+        let result = sqlx::query(
+            "INSERT INTO votes (user_id, theme_id, vote_type, skip_reason, client_version)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (user_id, theme_id) DO NOTHING",
+        )
+        .bind(&user_id)
+        .bind(vote_req.theme_id)
+        .bind(&vote_req.vote_type)
+        .bind(reason)
+        .bind(client_version)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict(
+                "You've already voted on this theme, and votes can't be changed".into(),
+            ));
+        }
+        tx.commit().await?;
+        return Ok(StatusCode::OK);
+    }
+
+    // The vote being replaced is locked first, so the type it had can go into
+    // vote_history: stats as of an earlier time still count it as it was then.
+    let previous: Option<(i32, String)> = sqlx::query_as(
+        "SELECT id, vote_type FROM votes WHERE user_id = $1 AND theme_id = $2 FOR UPDATE",
+    )
+    .bind(&user_id)
+    .bind(vote_req.theme_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    // Insert or update vote. The upsert is a single statement backed by the
+    // UNIQUE (user_id, theme_id) constraint, so concurrent votes from the same
+    // user on the same theme never produce duplicate rows: the conflicting
+    // insert waits on the row lock, and the last one to commit wins.
+    // A change within the cooldown of the last one updates nothing and is
+    // turned away. First votes are never held back, and neither is sending the
+    // same vote again (e.g. a client retrying). The cooldown is measured against
+    // the clock rather than NOW(): a change that waited on the row lock may find
+    // an updated_at later than its own start.
+    let result = sqlx::query(
+        "INSERT INTO votes (user_id, theme_id, vote_type, skip_reason, client_version) 
+         VALUES ($1, $2, $3, $4, $6)
+         ON CONFLICT (user_id, theme_id) 
+         DO UPDATE SET vote_type = $3, skip_reason = $4, client_version = $6, updated_at = NOW()
+         WHERE $5 = 0
+            OR votes.updated_at <= clock_timestamp() - make_interval(secs => $5)
+            OR (votes.vote_type = $3 AND votes.skip_reason IS NOT DISTINCT FROM $4)",
+    )
+    .bind(&user_id)
+    .bind(vote_req.theme_id)
+    .bind(&vote_req.vote_type)
+    .bind(reason)
+    .bind(state.config.revote_cooldown.as_secs_f64())
+    .bind(client_version)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::TooManyRequests);
+    }
+    if let Some((vote_id, previous_type)) = previous
+        && previous_type != vote_req.vote_type
+    {
+        sqlx::query("INSERT INTO vote_history (vote_id, vote_type) VALUES ($1, $2)")
+            .bind(vote_id)
+            .bind(previous_type)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// How many of the least compared themes `/themes/pair` picks a pair from.
+const PAIR_SAMPLE_SIZE: i64 = 50;
+
+/// Two themes for the optional bracket mode, where voters pick the better of two
+/// instead of rating themes one at a time.
+async fn get_theme_pair(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<PairResponse>, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    if !state.config.is_voting_open(Utc::now()) {
+        return Ok(Json(PairResponse {
+            pair: None,
+            closed: true,
+        }));
+    }
+
+    // Only pairs the user hasn't judged yet. Themes short of the minimum number of picks
+    // overall come first, the least compared first, then the themes this user has
+    // compared least. Pairing every theme with every other would grow with the square of
+    // the theme count, so pairs are only drawn from a sample of the most wanted themes.
+    // Once the user has judged every pair in it, the theme they've compared least is
+    // matched with a partner instead: with fewer picks than there are other themes, it
+    // still has one they haven't judged it against.
+    let pair: Option<(i32, i32)> = sqlx::query_as(
+        "WITH active AS (
+             SELECT id FROM themes WHERE deleted_at IS NULL
+         ),
+         picks AS (
+             SELECT p.user_id, p.winner_id AS theme_id FROM pairwise_votes p
+             JOIN active l ON l.id = p.loser_id
+             UNION ALL
+             SELECT p.user_id, p.loser_id FROM pairwise_votes p
+             JOIN active w ON w.id = p.winner_id
+         ),
+         compared AS (
+             SELECT
+                 t.id,
+                 COUNT(k.user_id) FILTER (WHERE k.user_id = $1) AS times,
+                 COUNT(k.user_id) AS overall
+             FROM active t
+             LEFT JOIN picks k ON k.theme_id = t.id
+             GROUP BY t.id
+         ),
+         sample AS (
+             SELECT * FROM compared
+             ORDER BY LEAST(overall, $2), times, RANDOM()
+             LIMIT $3
+         ),
+         judged AS (
+             SELECT LEAST(winner_id, loser_id) AS a, GREATEST(winner_id, loser_id) AS b
+             FROM pairwise_votes WHERE user_id = $1
+         ),
+         least_judged AS (
+             SELECT * FROM compared
+             WHERE times < (SELECT COUNT(*) - 1 FROM active)
+             ORDER BY times, RANDOM()
+             LIMIT 1
+         )
+         (SELECT a.id, b.id
+          FROM sample a
+          JOIN sample b ON a.id < b.id
+          WHERE NOT EXISTS (SELECT 1 FROM judged j WHERE j.a = a.id AND j.b = b.id)
+          ORDER BY LEAST(a.overall, b.overall, $2), a.times + b.times, RANDOM()
+          LIMIT 1)
+         UNION ALL
+         (SELECT LEAST(f.id, c.id), GREATEST(f.id, c.id)
+          FROM least_judged f
+          JOIN compared c ON c.id <> f.id
+          WHERE NOT EXISTS (
+              SELECT 1 FROM judged j
+              WHERE j.a = LEAST(f.id, c.id) AND j.b = GREATEST(f.id, c.id)
+          )
+          ORDER BY LEAST(c.overall, $2), c.times, RANDOM()
+          LIMIT 1)
+         LIMIT 1",
+    )
+    .bind(&user_id)
+    .bind(state.config.min_pairwise_comparisons)
+    .bind(PAIR_SAMPLE_SIZE)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let Some((a, b)) = pair else {
+        return Ok(Json(PairResponse {
+            pair: None,
+            closed: false,
+        }));
+    };
+    // Random order, so neither side is favored by where it's shown
+    let themes: Vec<Theme> = sqlx::query_as(
+        "SELECT id, content, description FROM themes WHERE id IN ($1, $2) ORDER BY RANDOM()",
+    )
+    .bind(a)
+    .bind(b)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(PairResponse {
+        pair: themes.try_into().ok(),
+        closed: false,
+    }))
+}
+
+async fn submit_pair_vote(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Json(vote_req): Json<PairVoteRequest>,
+) -> Result<StatusCode, AppError> {
+    let user_id = state.auth.verify(&headers).await?;
+
+    if !state.config.is_voting_open(Utc::now()) {
+        return Err(AppError::Forbidden("Voting is closed".into()));
+    }
+    if vote_req.winner_id == vote_req.loser_id {
+        return Err(AppError::BadRequest("Pick two different themes".into()));
+    }
+
+    // Same as single votes: neither theme can be removed until the pick is in
+    let mut tx = state.db.begin().await?;
+    let active: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM (
+             SELECT id FROM themes WHERE id IN ($1, $2) AND deleted_at IS NULL FOR SHARE
+         ) locked",
+    )
+    .bind(vote_req.winner_id)
+    .bind(vote_req.loser_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if active != 2 {
+        return Err(AppError::NotFound("Theme not found".into()));
+    }
+
+    // The unique index covers the pair in either order, so a user gets one pick per pair
+    let result = sqlx::query(
+        "INSERT INTO pairwise_votes (user_id, winner_id, loser_id)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (user_id, LEAST(winner_id, loser_id), GREATEST(winner_id, loser_id))
+         DO UPDATE SET winner_id = $2, loser_id = $3, created_at = NOW()
+         WHERE $4",
+    )
+    .bind(&user_id)
+    .bind(vote_req.winner_id)
+    .bind(vote_req.loser_id)
+    .bind(state.config.allow_revote)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::Conflict(
+            "You've already picked between these themes, and votes can't be changed".into(),
+        ));
+    }
+    tx.commit().await?;
+
+    Ok(StatusCode::OK)
+}
+
+async fn get_pairwise_ranking(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Vec<PairwiseRanking>>, AppError> {
+    ensure_results_visible(&state, &headers).await?;
+    Ok(Json(pairwise_ranking(&state.db).await?))
+}
+
+/// The pairwise ranking for organizers, as a CSV download or (`format=json`) JSON.
+async fn export_pairwise_ranking(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<PairwiseRankingQuery>,
+) -> Result<Response, AppError> {
+    verify_admin(&state, &headers).await?;
+    let ranking = pairwise_ranking(&state.db).await?;
+
+    match query.format {
+        ExportFormat::Json => Ok(Json(ranking).into_response()),
+        ExportFormat::Csv => {
+            let mut csv = String::from("theme_id,content,wins,losses,win_rate,elo\n");
+            for r in &ranking {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{:.1}\n",
+                    r.theme_id,
+                    csv_field(&r.content),
+                    r.wins,
+                    r.losses,
+                    r.win_rate
+                        .map(|rate| format!("{rate:.1}"))
+                        .unwrap_or_default(),
+                    r.elo
+                ));
+            }
+            Ok(csv_attachment("pairwise_ranking.csv", csv))
+        }
+    }
+}
+
+/// Whether every theme has been in enough head-to-head picks for the pairwise ranking to
+/// be trusted, and which ones still need more.
+async fn get_pairwise_coverage(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<PairwiseCoverage>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let min_comparisons = state.config.min_pairwise_comparisons;
+
+    let themes: Vec<PairwiseComparisons> = sqlx::query_as!(
+        PairwiseComparisons,
+        r#"
+        WITH picks AS (
+            SELECT p.id, p.winner_id, p.loser_id
+            FROM pairwise_votes p
+            JOIN themes w ON w.id = p.winner_id AND w.deleted_at IS NULL
+            JOIN themes l ON l.id = p.loser_id AND l.deleted_at IS NULL
+        )
+        SELECT
+            t.id as theme_id,
+            t.content,
+            COUNT(p.id) as "comparisons!",
+            COUNT(p.id) < $1 as "below_minimum!"
+        FROM themes t
+        LEFT JOIN picks p ON t.id IN (p.winner_id, p.loser_id)
+        WHERE t.deleted_at IS NULL
+        GROUP BY t.id, t.content
+        ORDER BY 3, t.id
+        "#,
+        min_comparisons
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let below_minimum = themes.iter().filter(|t| t.below_minimum).count() as i64;
+    Ok(Json(PairwiseCoverage {
+        min_comparisons,
+        ready: below_minimum == 0,
+        below_minimum,
+        themes,
+    }))
+}
+
+/// Rating every theme starts from, and how far one pick can move it.
+const ELO_START: f64 = 1500.0;
+const ELO_K: f64 = 32.0;
+
+/// Active themes ranked by how often they won their head-to-head picks, then by wins.
+/// Themes never compared come last. Picks against removed themes don't count.
+async fn pairwise_ranking(db: &PgPool) -> Result<Vec<PairwiseRanking>, sqlx::Error> {
+    let picks = sqlx::query!(
+        r#"
+        SELECT p.winner_id, p.loser_id
+        FROM pairwise_votes p
+        JOIN themes w ON w.id = p.winner_id AND w.deleted_at IS NULL
+        JOIN themes l ON l.id = p.loser_id AND l.deleted_at IS NULL
+        ORDER BY p.created_at, p.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut elo: HashMap<i32, f64> = HashMap::new();
+    for pick in &picks {
+        let winner = *elo.get(&pick.winner_id).unwrap_or(&ELO_START);
+        let loser = *elo.get(&pick.loser_id).unwrap_or(&ELO_START);
+        let expected = 1.0 / (1.0 + 10f64.powf((loser - winner) / 400.0));
+        let change = ELO_K * (1.0 - expected);
+        elo.insert(pick.winner_id, winner + change);
+        elo.insert(pick.loser_id, loser - change);
+    }
+
+    let tallies = sqlx::query!(
+        r#"
+        WITH picks AS (
+            SELECT p.winner_id, p.loser_id
+            FROM pairwise_votes p
+            JOIN themes w ON w.id = p.winner_id AND w.deleted_at IS NULL
+            JOIN themes l ON l.id = p.loser_id AND l.deleted_at IS NULL
+        ),
+        tallies AS (
+            SELECT
+                t.id,
+                t.content,
+                COUNT(CASE WHEN p.winner_id = t.id THEN 1 END) AS wins,
+                COUNT(CASE WHEN p.loser_id = t.id THEN 1 END) AS losses
+            FROM themes t
+            LEFT JOIN picks p ON t.id IN (p.winner_id, p.loser_id)
+            WHERE t.deleted_at IS NULL
+            GROUP BY t.id, t.content
+        )
+        SELECT
+            id as theme_id,
+            content,
+            wins as "wins!",
+            losses as "losses!",
+            (wins * 100.0 / NULLIF(wins + losses, 0))::FLOAT8 as win_rate
+        FROM tallies
+        ORDER BY 5 DESC NULLS LAST, wins DESC, id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(tallies
+        .into_iter()
+        .map(|t| PairwiseRanking {
+            elo: *elo.get(&t.theme_id).unwrap_or(&ELO_START),
+            theme_id: t.theme_id,
+            content: t.content,
+            wins: t.wins,
+            losses: t.losses,
+            win_rate: t.win_rate,
+        })
+        .collect())
+}
+
+/// Most theme ids a single stats request may ask for.
+const MAX_STATS_IDS: usize = 100;
+
+async fn get_stats(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(page): Query<Pagination>,
+    Query(filter): Query<StatsFilter>,
+) -> Result<Json<Vec<VoteStats>>, AppError> {
+    ensure_results_visible(&state, &headers).await?;
+    let ids = filter.ids.as_deref().map(parse_theme_ids).transpose()?;
+    let stats = vote_stats(&state.db, &page, ids.as_deref(), 0, filter.sort, None).await?;
+    Ok(Json(stats))
+}
+
+/// Stats as they stood at `ts`, e.g. to see how the ranking looked yesterday.
+/// A vote changed since then counts with the type it had at `ts`.
+async fn get_stats_at(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(page): Query<Pagination>,
+    Query(filter): Query<StatsFilter>,
+    Query(at): Query<StatsAtQuery>,
+) -> Result<Json<Vec<VoteStats>>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let ids = filter.ids.as_deref().map(parse_theme_ids).transpose()?;
+    let stats = vote_stats(
+        &state.db,
+        &page,
+        ids.as_deref(),
+        0,
+        filter.sort,
+        Some(at.ts),
+    )
+    .await?;
+    Ok(Json(stats))
+}
+
+/// Each theme's rank and votes now (or at `to`) against an earlier time, to see which
+/// themes are rising.
+async fn get_stats_diff(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<StatsDiffQuery>,
+) -> Result<Json<Vec<StatsDiff>>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let to = query.to.unwrap_or_else(Utc::now);
+    if query.from >= to {
+        return Err(AppError::BadRequest(
+            "\"from\" must be before \"to\"".into(),
+        ));
+    }
+
+    let all = Pagination {
+        limit: None,
+        offset: None,
+    };
+    let before = vote_stats(&state.db, &all, None, 0, query.sort, Some(query.from)).await?;
+    let after = vote_stats(&state.db, &all, None, 0, query.sort, Some(to)).await?;
+
+    let before: HashMap<i32, (i64, &VoteStats)> = before
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.theme_id, (i as i64 + 1, s)))
+        .collect();
+    let diff = after
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let rank = i as i64 + 1;
+            let previous = before.get(&s.theme_id);
+            let change =
+                |now: i64, then: fn(&VoteStats) -> i64| previous.map(|(_, prev)| now - then(prev));
+            StatsDiff {
+                rank,
+                previous_rank: previous.map(|(prev_rank, _)| *prev_rank),
+                rank_change: previous.map(|(prev_rank, _)| prev_rank - rank),
+                yes_change: change(s.yes_votes, |p| p.yes_votes),
+                net_change: change(s.net_score, |p| p.net_score),
+                total_change: change(s.total_votes, |p| p.total_votes),
+                theme_id: s.theme_id,
+                content: s.content,
+                yes_votes: s.yes_votes,
+                net_score: s.net_score,
+                total_votes: s.total_votes,
+            }
+        })
+        .collect();
+    Ok(Json(diff))
+}
+
+/// Results where each vote counts for its voter's trust weight (1.0 unless an admin set
+/// it), highest weighted yes first.
+async fn get_weighted_stats(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(page): Query<Pagination>,
+) -> Result<Json<Vec<WeightedVoteStats>>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let stats: Vec<WeightedVoteStats> = sqlx::query_as!(
+        WeightedVoteStats,
+        r#"
+        WITH weighted AS (
+            SELECT v.theme_id, v.vote_type, COALESCE(u.trust_weight, 1.0) AS weight
+            FROM votes v
+            LEFT JOIN users u ON u.user_id = v.user_id
+        )
+        SELECT
+            t.id as theme_id,
+            t.content,
+            COALESCE(SUM(w.weight) FILTER (WHERE w.vote_type = 'yes'), 0) as "yes_weight!",
+            COALESCE(SUM(w.weight) FILTER (WHERE w.vote_type = 'no'), 0) as "no_weight!",
+            COALESCE(SUM(w.weight) FILTER (WHERE w.vote_type = 'skip'), 0) as "skip_weight!",
+            COALESCE(SUM(w.weight) FILTER (WHERE w.vote_type = 'maybe'), 0) as "maybe_weight!",
+            COALESCE(SUM(w.weight), 0) as "total_weight!",
+            COALESCE(SUM(w.weight) FILTER (WHERE w.vote_type = 'yes'), 0)
+                - COALESCE(SUM(w.weight) FILTER (WHERE w.vote_type = 'no'), 0) as "net_weight!",
+            COUNT(w.theme_id) as "total_votes!"
+        FROM themes t
+        LEFT JOIN weighted w ON w.theme_id = t.id
+        WHERE t.deleted_at IS NULL
+        GROUP BY t.id, t.content
+        ORDER BY 3 DESC, t.id
+        LIMIT $1 OFFSET $2
+        "#,
+        page.limit,
+        page.offset.unwrap_or(0)
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(stats))
+}
+
+/// Every theme's results as a shareable HTML page.
+async fn get_report(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Response, AppError> {
+    verify_admin(&state, &headers).await?;
+    let all = Pagination {
+        limit: None,
+        offset: None,
+    };
+    let stats = vote_stats(&state.db, &all, None, 0, StatsSort::Yes, None).await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        report::render(&stats, Utc::now()),
+    )
+        .into_response())
+}
+
+/// Results as shown to voters: themes only rank once they have enough votes to mean something.
+async fn get_public_results(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(page): Query<Pagination>,
+) -> Result<Json<Vec<VoteStats>>, AppError> {
+    ensure_results_visible(&state, &headers).await?;
+    let stats = vote_stats(
+        &state.db,
+        &page,
+        None,
+        state.config.public_results_min_votes,
+        StatsSort::Yes,
+        None,
+    )
+    .await?;
+    Ok(Json(stats))
+}
+
+/// Parses a comma-separated id list, dropping repeats but keeping the given order.
+fn parse_theme_ids(list: &str) -> Result<Vec<i32>, AppError> {
+    let mut ids = Vec::new();
+    for id in list.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        let id: i32 = id
+            .parse()
+            .map_err(|_| AppError::BadRequest(format!("Invalid theme id: {}", id)))?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if ids.is_empty() {
+        return Err(AppError::BadRequest(
+            "ids must list at least one theme id".into(),
+        ));
+    }
+    if ids.len() > MAX_STATS_IDS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} theme ids can be requested at once",
+            MAX_STATS_IDS
+        )));
+    }
+    Ok(ids)
+}
+
+async fn export_stats(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<StatsExportQuery>,
+) -> Result<Response, AppError> {
+    verify_admin(&state, &headers).await?;
+    let all = Pagination {
+        limit: None,
+        offset: None,
+    };
+    let stats = vote_stats(&state.db, &all, None, 0, StatsSort::Yes, None).await?;
+
+    match query.format {
+        ExportFormat::Json => Ok(Json(stats).into_response()),
+        ExportFormat::Csv => {
+            let mut csv = String::from(
+                "theme_id,content,yes_votes,no_votes,skip_votes,maybe_votes,total_votes\n",
+            );
+            for s in &stats {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    s.theme_id,
+                    csv_field(&s.content),
+                    s.yes_votes,
+                    s.no_votes,
+                    s.skip_votes,
+                    s.maybe_votes,
+                    s.total_votes
+                ));
+            }
+            Ok(csv_attachment("theme_stats.csv", csv))
+        }
+    }
+}
+
+/// `body` as a CSV file download called `filename`.
+fn csv_attachment(filename: &str, body: String) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Matches themes user `$1` hasn't voted on. When `$4` is true, a skip stops counting
+/// once the user has cast `$5` other votes since, or `$6` minutes have passed.
+const UNVOTED_FILTER: &str = "NOT EXISTS (
+        SELECT 1 FROM votes v
+        WHERE v.theme_id = t.id AND v.user_id = $1
+          AND NOT (
+              v.vote_type = 'skip' AND $4::BOOL AND (
+                  v.updated_at < NOW() - $6::BIGINT * INTERVAL '1 minute'
+                  OR (
+                      SELECT COUNT(*) FROM votes w
+                      WHERE w.user_id = $1 AND w.updated_at > v.updated_at
+                  ) >= $5::BIGINT
+              )
+          )
+    )";
+
+/// Matches themes tagged with any (or, when `$3` is true, all) of the tags in `$2`.
+/// An empty tag list matches everything.
+const TAG_FILTER: &str = "(cardinality($2::TEXT[]) = 0 OR (
+        SELECT COUNT(*) FROM theme_tags tt
+        JOIN tags g ON g.id = tt.tag_id
+        WHERE tt.theme_id = t.id AND g.name = ANY($2)
+    ) >= CASE WHEN $3 THEN cardinality($2) ELSE 1 END)";
+
+/// Leaves out themes skipped more often than a `$7` share of their votes, once they have
+/// at least `$8` votes, unless an admin has marked them `always_shown`. A null `$7` hides nothing.
+const QUALITY_FILTER: &str = "(t.always_shown OR $7::FLOAT8 IS NULL OR (
+        SELECT COUNT(*) < $8::BIGINT
+            OR COUNT(CASE WHEN vote_type = 'skip' THEN 1 END)::FLOAT8 / NULLIF(COUNT(*), 0) <= $7
+        FROM votes WHERE theme_id = t.id
+    ))";
+
+fn requested_tags(list: Option<&str>) -> Result<Vec<String>, AppError> {
+    tags::parse_list(list.unwrap_or_default()).map_err(AppError::BadRequest)
+}
+
+/// Attaches an already-normalized tag to a theme; tagging twice is a no-op.
+async fn tag_theme(db: &PgPool, theme_id: i32, tag: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "WITH new_tag AS (
+             INSERT INTO tags (name) VALUES ($2)
+             ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+             RETURNING id
+         )
+         INSERT INTO theme_tags (theme_id, tag_id)
+         SELECT $1, id FROM new_tag
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(theme_id)
+    .bind(tag)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Per-theme vote counts, best first by `sort`, leaving out themes with fewer than
+/// `min_votes`. When `ids` is given only those themes are included, in the order listed;
+/// unknown ids are left out. With `as_of`, only themes and votes that existed by then count.
+async fn vote_stats(
+    db: &PgPool,
+    page: &Pagination,
+    ids: Option<&[i32]>,
+    min_votes: i64,
+    sort: StatsSort,
+    as_of: Option<DateTime<Utc>>,
+) -> Result<Vec<VoteStats>, sqlx::Error> {
+    sqlx::query_as!(
+        VoteStats,
+        r#"
+        SELECT 
+            t.id as theme_id,
+            t.content,
+            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) as "yes_votes!",
+            COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) as "no_votes!",
+            COUNT(CASE WHEN v.vote_type = 'skip' THEN 1 END) as "skip_votes!",
+            COUNT(CASE WHEN v.vote_type = 'maybe' THEN 1 END) as "maybe_votes!",
+            COUNT(v.id) as "total_votes!",
+            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END)
+                - COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) as "net_score!",
+            s.seen as "seen_count!",
+            COALESCE(COUNT(v.id)::FLOAT8 / NULLIF(s.seen, 0), 0) as "participation_rate!"
+        FROM themes t
+        -- As of $6, a vote counts with the type it had then: the first one it has
+        -- since replaced, or else its current type
+        LEFT JOIN LATERAL (
+            SELECT v.id, COALESCE(
+                (SELECT h.vote_type FROM vote_history h
+                 WHERE h.vote_id = v.id AND h.replaced_at > $6
+                 ORDER BY h.replaced_at, h.id
+                 LIMIT 1),
+                v.vote_type
+            ) AS vote_type
+            FROM votes v
+            WHERE v.theme_id = t.id AND ($6::TIMESTAMPTZ IS NULL OR v.created_at <= $6)
+        ) v ON TRUE
+        -- Anyone who voted has seen the theme, even before impressions were recorded
+        CROSS JOIN LATERAL (
+            SELECT COUNT(*) AS seen FROM (
+                SELECT user_id FROM theme_impressions
+                WHERE theme_id = t.id AND ($6 IS NULL OR first_seen_at <= $6)
+                UNION
+                SELECT user_id FROM votes
+                WHERE theme_id = t.id AND ($6 IS NULL OR created_at <= $6)
+            ) viewers
+        ) s
+        WHERE ($3::INT4[] IS NULL OR t.id = ANY($3))
+          AND ($6 IS NULL OR t.created_at <= $6)
+          -- Deleted themes only count for the time before they were deleted
+          AND (t.deleted_at IS NULL OR ($6 IS NOT NULL AND t.deleted_at > $6))
+        GROUP BY t.id, t.content, s.seen
+        HAVING COUNT(v.id) >= $4
+        ORDER BY
+            array_position($3, t.id),
+            CASE WHEN $5 THEN
+                COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END)
+                    - COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END)
+            END DESC,
+            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) DESC,
+            t.id
+        LIMIT $1 OFFSET $2
+        "#,
+        page.limit,
+        page.offset.unwrap_or(0),
+        ids,
+        min_votes,
+        sort == StatsSort::Net,
+        as_of
+    )
+    .fetch_all(db)
+    .await
+}
+
+const DEFAULT_RECENT_VOTES: i64 = 50;
+const MAX_RECENT_VOTES: i64 = 500;
+
+/// Newest votes first, for a live activity feed.
+async fn get_recent_votes(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<RecentVotesQuery>,
+) -> Result<Json<Vec<RecentVote>>, AppError> {
+    let admin_id = verify_admin(&state, &headers).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RECENT_VOTES)
+        .clamp(1, MAX_RECENT_VOTES);
+
+    // Ordering on (created_at, id) keeps pages gap-free even when timestamps tie
+    let mut votes: Vec<RecentVote> = sqlx::query_as!(
+        RecentVote,
+        r#"
+        SELECT
+            v.id,
+            v.user_id,
+            v.theme_id,
+            t.content as theme_content,
+            v.vote_type,
+            v.created_at as voted_at
+        FROM votes v
+        JOIN themes t ON v.theme_id = t.id
+        WHERE $1::TIMESTAMPTZ IS NULL
+           OR (v.created_at, v.id) < ($1, COALESCE($2, 2147483647))
+        ORDER BY v.created_at DESC, v.id DESC
+        LIMIT $3
+        "#,
+        query.before,
+        query.before_id,
+        limit
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    if let Some(salt) = &state.config.export_salt
+        && !state.config.is_super_admin(&admin_id)
+    {
+        for vote in &mut votes {
+            vote.user_id = pseudonymize(salt, &vote.user_id);
+        }
+    }
+
+    Ok(Json(votes))
+}
+
+const DEFAULT_LEADERBOARD_SIZE: i64 = 10;
+const MAX_LEADERBOARD_SIZE: i64 = 100;
+
+async fn get_leaderboard(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<Vec<LeaderboardEntry>>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LEADERBOARD_SIZE)
+        .clamp(1, MAX_LEADERBOARD_SIZE);
+
+    let entries: Vec<LeaderboardEntry> = sqlx::query_as!(
+        LeaderboardEntry,
+        r#"
+        WITH tallies AS (
+            SELECT
+                t.id,
+                t.content,
+                COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) AS yes_votes,
+                COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) AS no_votes,
+                COUNT(v.id) AS total_votes
+            FROM themes t
+            LEFT JOIN votes v ON t.id = v.theme_id
+            WHERE t.deleted_at IS NULL
+            GROUP BY t.id, t.content
+        )
+        SELECT
+            RANK() OVER (ORDER BY yes_votes DESC) as "rank!",
+            id as theme_id,
+            content,
+            yes_votes as "yes_votes!",
+            no_votes as "no_votes!",
+            total_votes as "total_votes!",
+            COALESCE(yes_votes * 100.0 / NULLIF(yes_votes + no_votes, 0), 0)::FLOAT8 as "yes_percent!"
+        FROM tallies
+        ORDER BY 1, id
+        LIMIT $1
+        "#,
+        limit
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(entries))
+}
+
+async fn get_skip_reasons(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Vec<SkipReasonCount>>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let reasons: Vec<SkipReasonCount> = sqlx::query_as!(
+        SkipReasonCount,
+        r#"
+        SELECT
+            t.id as theme_id,
+            t.content,
+            v.skip_reason as "reason!",
+            COUNT(*) as "count!"
+        FROM votes v
+        JOIN themes t ON v.theme_id = t.id
+        WHERE v.vote_type = 'skip' AND v.skip_reason IS NOT NULL
+        GROUP BY t.id, t.content, v.skip_reason
+        ORDER BY t.id, COUNT(*) DESC
+        "#
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(reasons))
+}
+
+/// Votes per client version, most recently seen first, to spot patterns tied to one release.
+async fn get_client_versions(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Vec<ClientVersionCount>>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let versions: Vec<ClientVersionCount> = sqlx::query_as!(
+        ClientVersionCount,
+        r#"
+        SELECT
+            client_version,
+            COUNT(*) as "votes!",
+            COUNT(DISTINCT user_id) as "voters!"
+        FROM votes
+        GROUP BY client_version
+        ORDER BY MAX(updated_at) DESC
+        "#
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(versions))
+}
+
+async fn compare_themes(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<CompareQuery>,
+) -> Result<Json<CompareResponse>, AppError> {
+    verify_admin(&state, &headers).await?;
+    if query.a == query.b {
+        return Err(AppError::BadRequest("Pick two different themes".into()));
+    }
+
+    let a = theme_tally(&state.db, query.a).await?;
+    let b = theme_tally(&state.db, query.b).await?;
+
+    let overlap = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) as "voted_both!",
+            COUNT(*) FILTER (WHERE pairs.a > pairs.b) as "preferred_a!",
+            COUNT(*) FILTER (WHERE pairs.a < pairs.b) as "preferred_b!",
+            COUNT(*) FILTER (WHERE pairs.a = pairs.b) as "same!",
+            COUNT(*) FILTER (WHERE pairs.a IS NULL OR pairs.b IS NULL) as "no_opinion!"
+        FROM (
+            -- A skip has no score, so it's never compared either way
+            SELECT
+                CASE va.vote_type WHEN 'yes' THEN 2 WHEN 'maybe' THEN 1 WHEN 'no' THEN 0 END AS a,
+                CASE vb.vote_type WHEN 'yes' THEN 2 WHEN 'maybe' THEN 1 WHEN 'no' THEN 0 END AS b
+            FROM votes va
+            JOIN votes vb ON vb.user_id = va.user_id AND vb.theme_id = $2
+            WHERE va.theme_id = $1
+        ) pairs
+        "#,
+        query.a,
+        query.b
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(CompareResponse {
+        a,
+        b,
+        voted_both: overlap.voted_both,
+        preferred_a: overlap.preferred_a,
+        preferred_b: overlap.preferred_b,
+        same: overlap.same,
+        no_opinion: overlap.no_opinion,
+    }))
+}
+
+async fn theme_tally(db: &PgPool, theme_id: i32) -> Result<ThemeTally, AppError> {
+    sqlx::query_as!(
+        ThemeTally,
+        r#"
+        SELECT
+            t.id as theme_id,
+            t.content,
+            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) as "yes_votes!",
+            COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) as "no_votes!",
+            COUNT(CASE WHEN v.vote_type = 'skip' THEN 1 END) as "skip_votes!",
+            COUNT(CASE WHEN v.vote_type = 'maybe' THEN 1 END) as "maybe_votes!",
+            COUNT(v.id) as "total_votes!"
+        FROM themes t
+        LEFT JOIN votes v ON v.theme_id = t.id
+        WHERE t.id = $1 AND t.deleted_at IS NULL
+        GROUP BY t.id, t.content
+        "#,
+        theme_id
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Theme {theme_id} not found")))
+}
+
+/// Per-tag theme counts and vote tallies, most-used tags first.
+async fn get_tag_stats(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Vec<TagStats>>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let stats: Vec<TagStats> = sqlx::query_as!(
+        TagStats,
+        r#"
+        SELECT
+            g.name as tag,
+            COUNT(DISTINCT t.id) as "theme_count!",
+            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) as "yes_votes!",
+            COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) as "no_votes!",
+            COUNT(v.id) as "total_votes!"
+        FROM tags g
+        JOIN theme_tags tt ON tt.tag_id = g.id
+        JOIN themes t ON t.id = tt.theme_id AND t.deleted_at IS NULL
+        LEFT JOIN votes v ON v.theme_id = t.id
+        GROUP BY g.name
+        ORDER BY COUNT(DISTINCT t.id) DESC, g.name
+        "#
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(stats))
+}
+
+async fn get_summary(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<VoteSummary>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let summary = sqlx::query_as!(
+        VoteSummary,
+        r#"
+        SELECT
+            COUNT(v.id) as "total_votes!",
+            COUNT(CASE WHEN v.vote_type = 'yes' THEN 1 END) as "yes!",
+            COUNT(CASE WHEN v.vote_type = 'no' THEN 1 END) as "no!",
+            COUNT(CASE WHEN v.vote_type = 'skip' THEN 1 END) as "skip!",
+            COUNT(CASE WHEN v.vote_type = 'maybe' THEN 1 END) as "maybe!",
+            (SELECT COUNT(*) FROM themes WHERE deleted_at IS NULL) as "themes!",
+            COUNT(DISTINCT v.user_id) as "voters!"
+        FROM votes v
+        JOIN themes t ON t.id = v.theme_id AND t.deleted_at IS NULL
+        "#
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(summary))
+}
+
+async fn get_coverage(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+) -> Result<Json<Coverage>, AppError> {
+    verify_admin(&state, &headers).await?;
+    let coverage = sqlx::query_as!(
+        Coverage,
+        r#"
+        SELECT
+            COUNT(*) as "themes!",
+            COALESCE(MIN(c.votes), 0) as "min_votes!",
+            COALESCE(MAX(c.votes), 0) as "max_votes!",
+            COALESCE(AVG(c.votes)::FLOAT8, 0) as "mean_votes!",
+            COALESCE(STDDEV_POP(c.votes)::FLOAT8, 0) as "stddev_votes!",
+            COUNT(*) FILTER (WHERE c.votes = 0) as "themes_without_votes!"
+        FROM (
+            SELECT t.id, COUNT(v.id) AS votes
+            FROM themes t
+            LEFT JOIN votes v ON v.theme_id = t.id
+            WHERE t.deleted_at IS NULL
+            GROUP BY t.id
+        ) c
+        "#
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(coverage))
+}
+
+async fn export_votes(
+    State(state): State<AppState>,
+    headers: AuthHeaders,
+    Query(query): Query<RecentVotesQuery>,
+) -> Result<Json<Vec<ExportVote>>, AppError> {
+    let user_id = verify_admin(&state, &headers).await?;
+    // Paged like `/admin/recent`, so votes cast mid-export don't shift later pages
+    let mut votes: Vec<ExportVote> = sqlx::query_as!(
+        ExportVote,
+        r#"
+        SELECT 
+            v.id,
+            v.user_id,
+            v.theme_id,
+            t.content as theme_content,
+            v.vote_type,
+            v.created_at as voted_at
+        FROM votes v 
+        JOIN themes t ON v.theme_id = t.id 
+        WHERE $1::TIMESTAMPTZ IS NULL
+           OR (v.created_at, v.id) < ($1, COALESCE($2, 2147483647))
+        ORDER BY v.created_at DESC, v.id DESC
+        LIMIT $3
+        "#,
+        query.before,
+        query.before_id,
+        query.limit
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    // Only super-admins see the raw user ids once a salt is set
+    if let Some(salt) = &state.config.export_salt
+        && !state.config.is_super_admin(&user_id)
+    {
+        for vote in &mut votes {
+            vote.user_id = pseudonymize(salt, &vote.user_id);
+        }
+    }
+
+    Ok(Json(votes))
+}
+
+/// Stable pseudonym for a user id: the same user always maps to the same value for a given salt.
+fn pseudonymize(salt: &str, user_id: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update(b":")
+        .chain_update(user_id.as_bytes())
+        .finalize();
+    hex::encode(&digest[..16])
+}
+
+// ===== Error Handling =====
+
+#[derive(Debug)]
+enum AppError {
+    Unauthorized,
+    Forbidden(String),
+    NotFound(String),
+    BadRequest(String),
+    Conflict(String),
+    TooManyRequests,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        AppError::Database(err)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            AppError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "Unauthorized - Invalid or missing JWT token".to_string(),
+            ),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::TooManyRequests => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many requests - slow down".to_string(),
+            ),
+            AppError::Database(err) if is_transient(&err) => {
+                if matches!(err, sqlx::Error::PoolTimedOut) {
+                    POOL_TIMEOUTS.fetch_add(1, Ordering::Relaxed);
+                }
+                tracing::warn!("Database unavailable: {:?}", err);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Database temporarily unavailable - please retry".to_string(),
+                )
+            }
+            AppError::Database(err) => {
+                tracing::error!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database error".to_string(),
+                )
+            }
+        };
+
+        (status, message).into_response()
+    }
+}
+
+/// Requests turned away since startup because no database connection freed up in time.
+static POOL_TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+
+fn is_unique_violation(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::Database(db_err) if db_err.is_unique_violation())
+}
+
+/// Connection-level failures that are worth retrying, as opposed to errors in the query itself.
+fn is_transient(err: &sqlx::Error) -> bool {
+    matches!(
+        err,
+        sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::Io(_)
+            | sqlx::Error::WorkerCrashed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VOTER: &str = "voter";
+
+    fn test_state(db: PgPool, config: Config, user_id: &str) -> AppState {
+        AppState {
+            db,
+            auth: Authenticator::Fixed(user_id.to_string()),
+            public_limiter: Arc::new(RateLimiter::new(u32::MAX, Duration::from_secs(60))),
+            user_limiter: Arc::new(RateLimiter::new(u32::MAX, Duration::from_secs(60))),
+            theme_count: Arc::new(Cached::new(Duration::ZERO)),
+            blocklist: Arc::new(Blocklist::default()),
+            config: Arc::new(config),
+        }
+    }
+
+    /// Headers for a signed-in request; `Authenticator::Fixed` doesn't look at the token.
+    fn signed_in() -> AuthHeaders {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", HeaderValue::from_static("Bearer test"));
+        headers.into()
+    }
+
+    async fn add_theme(db: &PgPool, content: &str) -> i32 {
+        sqlx::query_scalar("INSERT INTO themes (content) VALUES ($1) RETURNING id")
+            .bind(content)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    async fn vote(
+        state: &AppState,
+        theme_id: i32,
+        vote_type: &str,
+    ) -> Result<StatusCode, AppError> {
+        let request = VoteRequest {
+            theme_id,
+            vote_type: vote_type.to_string(),
+            reason: None,
+        };
+        submit_vote(State(state.clone()), signed_in(), JsonOrForm(request)).await
+    }
+
+    async fn next_theme(state: &AppState) -> ThemeResponse {
+        let query = NextThemeQuery {
+            strategy: None,
+            tags: None,
+            tag_match: TagMatch::Any,
+            exclude_skips: None,
+        };
+        let Json(response) = get_next_theme(State(state.clone()), signed_in(), Query(query))
+            .await
+            .unwrap();
+        response
+    }
+
+    #[sqlx::test]
+    async fn submit_vote_records_the_callers_vote(db: PgPool) {
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        let theme_id = add_theme(&db, "Bees").await;
+
+        assert_eq!(vote(&state, theme_id, "yes").await.unwrap(), StatusCode::OK);
+
+        let votes: Vec<(String, String)> =
+            sqlx::query_as("SELECT user_id, vote_type FROM votes WHERE theme_id = $1")
+                .bind(theme_id)
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(votes, [(VOTER.to_string(), "yes".to_string())]);
+    }
+
+    #[sqlx::test]
+    async fn submit_vote_rejects_bad_votes(db: PgPool) {
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        let theme_id = add_theme(&db, "Bees").await;
+
+        assert!(matches!(
+            vote(&state, theme_id, "perhaps").await,
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            vote(&state, theme_id + 1, "yes").await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn submit_vote_refuses_once_voting_closes(db: PgPool) {
+        let config = Config {
+            voting_closes_at: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        let theme_id = add_theme(&db, "Bees").await;
+
+        assert!(matches!(
+            vote(&state, theme_id, "yes").await,
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    async fn eligibility(state: &AppState) -> Eligibility {
+        let Json(eligibility) = get_my_eligibility(State(state.clone()), signed_in())
+            .await
+            .unwrap();
+        eligibility
+    }
+
+    #[sqlx::test]
+    async fn daily_vote_limit_stops_new_votes_and_shows_in_eligibility(db: PgPool) {
+        let config = Config {
+            daily_vote_limit: Some(2),
+            allow_revote: true,
+            revote_cooldown: Duration::ZERO,
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+
+        let fresh = eligibility(&state).await;
+        assert!(fresh.can_vote);
+        assert_eq!(fresh.votes_remaining_today, Some(2));
+
+        vote(&state, bees, "yes").await.unwrap();
+        vote(&state, wasps, "no").await.unwrap();
+        assert!(matches!(
+            vote(&state, ants, "yes").await,
+            Err(AppError::Forbidden(_))
+        ));
+        // Sending a theme's vote again doesn't count as another one
+        vote(&state, wasps, "no").await.unwrap();
+
+        let spent = eligibility(&state).await;
+        assert!(!spent.can_vote);
+        assert_eq!(spent.reason.as_deref(), Some(DAILY_LIMIT_REACHED));
+        assert_eq!(spent.votes_remaining_today, Some(0));
+    }
+
+    #[sqlx::test]
+    async fn eligibility_explains_a_closed_window(db: PgPool) {
+        let opens = Utc::now() + chrono::Duration::hours(1);
+        let not_yet = test_state(
+            db.clone(),
+            Config {
+                voting_opens_at: Some(opens),
+                ..Config::default()
+            },
+            VOTER,
+        );
+        let over = test_state(
+            db.clone(),
+            Config {
+                voting_closes_at: Some(Utc::now() - chrono::Duration::hours(1)),
+                ..Config::default()
+            },
+            VOTER,
+        );
+
+        let early = eligibility(&not_yet).await;
+        assert!(!early.can_vote);
+        assert!(early.reason.unwrap().starts_with("Voting opens at"));
+        let late = eligibility(&over).await;
+        assert!(!late.can_vote);
+        assert_eq!(late.reason.as_deref(), Some("Voting is closed"));
+        assert_eq!(late.votes_remaining_today, None);
+    }
+
+    #[sqlx::test]
+    async fn concurrent_votes_leave_one_row_with_the_last_write(db: PgPool) {
+        const WRITERS: usize = 20;
+        let config = Config {
+            allow_revote: true,
+            revote_cooldown: Duration::ZERO,
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        let theme_id = add_theme(&db, "Bees").await;
+
+        // Writes to one vote row are applied one at a time under its row lock, so a
+        // trigger can log the order they land in
+        sqlx::raw_sql(
+            "CREATE TABLE vote_writes (id SERIAL PRIMARY KEY, vote_type TEXT NOT NULL);
+             CREATE FUNCTION log_vote_write() RETURNS TRIGGER AS $$
+             BEGIN
+                 INSERT INTO vote_writes (vote_type) VALUES (NEW.vote_type);
+                 RETURN NEW;
+             END
+             $$ LANGUAGE plpgsql;
+             CREATE TRIGGER log_vote_write AFTER INSERT OR UPDATE ON votes
+             FOR EACH ROW EXECUTE FUNCTION log_vote_write();",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|i| {
+                let state = state.clone();
+                let vote_type = ["yes", "no", "maybe", "skip"][i % 4];
+                tokio::spawn(async move { vote(&state, theme_id, vote_type).await })
+            })
+            .collect();
+        for writer in writers {
+            if let Err(e) = writer.await.unwrap() {
+                panic!("vote failed: {e:?}");
+            }
+        }
+
+        let stored: Vec<String> =
+            sqlx::query_scalar("SELECT vote_type FROM votes WHERE user_id = $1 AND theme_id = $2")
+                .bind(VOTER)
+                .bind(theme_id)
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        let writes: Vec<String> =
+            sqlx::query_scalar("SELECT vote_type FROM vote_writes ORDER BY id")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(writes.len(), WRITERS);
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored.last(), writes.last());
+    }
+
+    #[test]
+    fn negative_paging_is_rejected() {
+        let parse = |query: &str| {
+            let uri: axum::http::Uri = format!("/results?{query}").parse().unwrap();
+            Query::<Pagination>::try_from_uri(&uri).map(|Query(page)| (page.limit, page.offset))
+        };
+        assert_eq!(parse("").unwrap(), (None, None));
+        assert_eq!(parse("limit=0&offset=20").unwrap(), (Some(0), Some(20)));
+        assert!(parse("limit=-1").is_err());
+        assert!(parse("offset=-5").is_err());
+
+        let uri: axum::http::Uri = "/admin/export?limit=-1".parse().unwrap();
+        assert!(Query::<RecentVotesQuery>::try_from_uri(&uri).is_err());
+    }
+
+    async fn try_export(state: &AppState, headers: AuthHeaders) -> Result<Vec<String>, AppError> {
+        let query = RecentVotesQuery {
+            limit: None,
+            before: None,
+            before_id: None,
+        };
+        let Json(votes) = export_votes(State(state.clone()), headers, Query(query)).await?;
+        Ok(votes.into_iter().map(|vote| vote.user_id).collect())
+    }
+
+    async fn exported_user_ids(state: &AppState, headers: AuthHeaders) -> Vec<String> {
+        try_export(state, headers).await.unwrap()
+    }
+
+    fn export_config() -> Config {
+        Config {
+            export_salt: Some("salt".to_string()),
+            admin_user_ids: vec!["admin".to_string(), "super".to_string()],
+            super_admin_user_ids: vec!["super".to_string()],
+            ..Config::default()
+        }
+    }
+
+    #[sqlx::test]
+    async fn export_votes_pseudonymizes_for_all_but_super_admins(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let voter = test_state(db.clone(), Config::default(), VOTER);
+        vote(&voter, bees, "yes").await.unwrap();
+        vote(&voter, wasps, "no").await.unwrap();
+        let other = test_state(db.clone(), Config::default(), "other");
+        vote(&other, bees, "yes").await.unwrap();
+
+        let admin = test_state(db.clone(), export_config(), "admin");
+        let mut pseudonymized = exported_user_ids(&admin, signed_in()).await;
+        pseudonymized.sort();
+        let mut expected = vec![
+            pseudonymize("salt", VOTER),
+            pseudonymize("salt", VOTER),
+            pseudonymize("salt", "other"),
+        ];
+        expected.sort();
+        // The same user always gets the same pseudonym, and different users different ones
+        assert_eq!(pseudonymized, expected);
+        assert_ne!(pseudonymize("salt", VOTER), pseudonymize("salt", "other"));
+
+        let super_admin = test_state(db.clone(), export_config(), "super");
+        let mut raw = exported_user_ids(&super_admin, signed_in()).await;
+        raw.sort();
+        assert_eq!(raw, ["other", VOTER, VOTER]);
+    }
+
+    #[sqlx::test]
+    async fn export_votes_is_for_admins_only(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        let voter = test_state(db.clone(), Config::default(), VOTER);
+        vote(&voter, theme_id, "yes").await.unwrap();
+
+        // Even without a salt set, voters can't pull everyone's user ids
+        assert!(matches!(
+            try_export(&voter, signed_in()).await,
+            Err(AppError::Forbidden(_))
+        ));
+        let salted = test_state(db.clone(), export_config(), VOTER);
+        assert!(matches!(
+            try_export(&salted, signed_in()).await,
+            Err(AppError::Forbidden(_))
+        ));
+
+        let state = AppState {
+            auth: Authenticator::Jwks(Jwks::spawn(
+                "http://127.0.0.1:9/jwks.json",
+                Duration::from_secs(3600),
+            )),
+            ..test_state(db.clone(), export_config(), "super")
+        };
+        assert!(try_export(&state, HeaderMap::new().into()).await.is_err());
+        for value in ["Basic dm90ZXI=", "Bearer not-a-jwt"] {
+            let mut headers = HeaderMap::new();
+            headers.insert("Authorization", HeaderValue::from_static(value));
+            assert!(try_export(&state, headers.into()).await.is_err(), "{value}");
+        }
+    }
+
+    /// Runs every migration except the one described as `skipped` (e.g. "create users").
+    async fn migrate_all_but(db: &PgPool, skipped: &str) {
+        let mut migrator = sqlx::migrate!();
+        let migrations: Vec<_> = migrator
+            .migrations
+            .iter()
+            .filter(|migration| migration.description != skipped)
+            .cloned()
+            .collect();
+        assert!(
+            migrations.len() < migrator.migrations.len(),
+            "no migration {skipped:?}"
+        );
+        migrator.migrations = migrations.into();
+        migrator.run(db).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn create_theme_applies_the_configured_length_limits(db: PgPool) {
+        let config = Config {
+            admin_user_ids: vec!["admin".to_string()],
+            theme_length: theme_content::LengthLimits { min: 4, max: 6 },
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, "admin");
+        let create = |content: &str| {
+            let request = CreateThemeRequest {
+                content: content.to_string(),
+                description: None,
+            };
+            create_theme(State(state.clone()), signed_in(), Json(request))
+        };
+
+        assert!(matches!(create("Bee").await, Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            create("Wasps!!").await,
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(create("Bees").await.is_ok());
+        assert!(create("Wasps!").await.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn blocked_themes_are_rejected(db: PgPool) {
+        let state = AppState {
+            blocklist: Arc::new(Blocklist::parse("kill all")),
+            ..admin_state(&db)
+        };
+        let create = |content: &str, description: Option<&str>| {
+            let request = CreateThemeRequest {
+                content: content.to_string(),
+                description: description.map(str::to_string),
+            };
+            create_theme(State(state.clone()), signed_in(), Json(request))
+        };
+
+        let Err(AppError::BadRequest(reason)) = create("Kill all humans", None).await else {
+            panic!("a blocked theme was accepted");
+        };
+        assert!(reason.contains("blocked word"), "{reason}");
+        assert!(matches!(
+            create("Bees", Some("Kill ALL the bees")).await,
+            Err(AppError::BadRequest(_))
+        ));
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM themes")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        let theme_id = create("Skill all day", None).await.unwrap().1.id;
+        assert!(matches!(
+            edit(&state, theme_id, "Kill all bees").await,
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn concurrent_creates_of_one_theme_make_one_row(db: PgPool) {
+        let config = Config {
+            admin_user_ids: vec!["admin".to_string()],
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, "admin");
+
+        let creates: Vec<_> = ["Bees", " Bees\u{200B} "]
+            .into_iter()
+            .map(|content| {
+                let state = state.clone();
+                let request = CreateThemeRequest {
+                    content: content.to_string(),
+                    description: None,
+                };
+                tokio::spawn(
+                    async move { create_theme(State(state), signed_in(), Json(request)).await },
+                )
+            })
+            .collect();
+        let mut created = 0;
+        let mut conflicts = 0;
+        for create in creates {
+            match create.await.unwrap() {
+                Ok((StatusCode::CREATED, _)) => created += 1,
+                Err(AppError::Conflict(_)) => conflicts += 1,
+                other => panic!("unexpected result: {:?}", other.map(|(status, _)| status)),
+            }
+        }
+        assert_eq!((created, conflicts), (1, 1));
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM themes")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn unique_content_migration_merges_old_duplicates(db: PgPool) {
+        let mut migrator = sqlx::migrate!();
+        let unique = migrator
+            .iter()
+            .find(|migration| migration.description == "add themes content unique")
+            .unwrap()
+            .version;
+        let earlier: Vec<_> = migrator
+            .iter()
+            .filter(|migration| migration.version < unique)
+            .cloned()
+            .collect();
+        let all = std::mem::replace(&mut migrator.migrations, earlier.into());
+        migrator.run(&db).await.unwrap();
+
+        // Copies stored before content was normalized
+        let kept = add_theme(&db, "Bees").await;
+        let copy = add_theme(&db, " Bees\t").await;
+        let other_copy = add_theme(&db, "Bees  ").await;
+        sqlx::raw_sql(&format!(
+            "INSERT INTO votes (user_id, theme_id, vote_type, created_at) VALUES
+                 ('alice', {kept}, 'yes', NOW() - INTERVAL '1 day'),
+                 ('alice', {copy}, 'no', NOW()),
+                 ('bob', {copy}, 'no', NOW() - INTERVAL '1 day'),
+                 ('bob', {other_copy}, 'skip', NOW());
+             INSERT INTO tags (name) VALUES ('nature');
+             INSERT INTO theme_tags (theme_id, tag_id) SELECT {copy}, id FROM tags;",
+        ))
+        .execute(&db)
+        .await
+        .unwrap();
+
+        migrator.migrations = all;
+        migrator.run(&db).await.unwrap();
+
+        let themes: Vec<(i32, String)> = sqlx::query_as("SELECT id, content FROM themes")
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(themes, [(kept, "Bees".to_string())]);
+        let votes: Vec<(String, i32, String)> =
+            sqlx::query_as("SELECT user_id, theme_id, vote_type FROM votes ORDER BY user_id")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(
+            votes,
+            [
+                ("alice".to_string(), kept, "yes".to_string()),
+                ("bob".to_string(), kept, "skip".to_string()),
+            ]
+        );
+        let tagged: Vec<i32> = sqlx::query_scalar("SELECT theme_id FROM theme_tags")
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(tagged, [kept]);
+    }
+
+    #[sqlx::test]
+    async fn check_schema_passes_when_fully_migrated(db: PgPool) {
+        check_schema(&db).await.unwrap();
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn check_schema_fails_without_a_table_migration(db: PgPool) {
+        migrate_all_but(&db, "create users").await;
+        let err = check_schema(&db).await.unwrap_err();
+        assert!(err.to_string().contains("users"), "{err:#}");
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn check_schema_fails_without_a_column_migration(db: PgPool) {
+        migrate_all_but(&db, "add votes client version").await;
+        let err = check_schema(&db).await.unwrap_err();
+        assert!(err.to_string().contains("client_version"), "{err:#}");
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn check_schema_fails_without_an_index_migration(db: PgPool) {
+        migrate_all_but(&db, "add themes content unique").await;
+        let err = check_schema(&db).await.unwrap_err();
+        assert!(err.to_string().contains("themes_content_key"), "{err:#}");
+    }
+
+    #[sqlx::test]
+    async fn rapid_vote_changes_are_throttled_but_first_votes_are_not(db: PgPool) {
+        let config = Config {
+            allow_revote: true,
+            revote_cooldown: Duration::from_secs(60),
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        let bees = add_theme(&db, "Bees").await;
+        let knees = add_theme(&db, "Knees").await;
+
+        assert!(vote(&state, bees, "yes").await.is_ok());
+        assert!(matches!(
+            vote(&state, bees, "no").await,
+            Err(AppError::TooManyRequests)
+        ));
+        // Resending the same vote is a retry, not a change
+        assert!(vote(&state, bees, "yes").await.is_ok());
+        // The cooldown is per theme
+        assert!(vote(&state, knees, "no").await.is_ok());
+
+        let stored: String = sqlx::query_scalar("SELECT vote_type FROM votes WHERE theme_id = $1")
+            .bind(bees)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(stored, "yes");
+    }
+
+    #[sqlx::test]
+    async fn the_cooldown_runs_from_the_last_change_not_the_first_vote(db: PgPool) {
+        let config = Config {
+            allow_revote: true,
+            revote_cooldown: Duration::from_secs(60),
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        let theme_id = add_theme(&db, "Bees").await;
+        vote(&state, theme_id, "yes").await.unwrap();
+        sqlx::query(
+            "UPDATE votes SET created_at = NOW() - INTERVAL '1 day',
+                              updated_at = NOW() - INTERVAL '2 minutes'",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+
+        vote(&state, theme_id, "no").await.unwrap();
+        let (first_cast, just_changed): (bool, bool) = sqlx::query_as(
+            "SELECT created_at < NOW() - INTERVAL '23 hours', updated_at > NOW() - INTERVAL '1 minute'
+             FROM votes",
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert!(first_cast && just_changed);
+
+        // The first vote was long ago, but the change just now still counts
+        assert!(matches!(
+            vote(&state, theme_id, "yes").await,
+            Err(AppError::TooManyRequests)
+        ));
+    }
+
+    #[sqlx::test]
+    async fn vote_changes_are_free_without_a_cooldown(db: PgPool) {
+        let config = Config {
+            allow_revote: true,
+            revote_cooldown: Duration::ZERO,
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        let theme_id = add_theme(&db, "Bees").await;
+
+        for vote_type in ["yes", "no", "maybe", "skip", "yes"] {
+            assert!(
+                vote(&state, theme_id, vote_type).await.is_ok(),
+                "{vote_type}"
+            );
+        }
+    }
+
+    #[sqlx::test]
+    async fn get_next_theme_serves_themes_not_voted_on(db: PgPool) {
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        let voted = add_theme(&db, "Bees").await;
+        let left = add_theme(&db, "Knees").await;
+        vote(&state, voted, "no").await.unwrap();
+
+        let response = next_theme(&state).await;
+        assert_eq!(response.theme.map(|theme| theme.id), Some(left));
+        assert_eq!((response.seen, response.total), (1, 2));
+        assert!(!response.closed);
+
+        vote(&state, left, "yes").await.unwrap();
+        let response = next_theme(&state).await;
+        assert!(response.theme.is_none());
+        assert_eq!((response.seen, response.total), (2, 2));
+    }
+
+    #[sqlx::test]
+    async fn get_next_theme_is_per_user(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        let first = test_state(db.clone(), Config::default(), "first");
+        vote(&first, theme_id, "yes").await.unwrap();
+
+        let second = test_state(db.clone(), Config::default(), "second");
+        let response = next_theme(&second).await;
+        assert_eq!(response.theme.map(|theme| theme.id), Some(theme_id));
+        assert_eq!(response.seen, 0);
+    }
+
+    #[sqlx::test]
+    async fn higher_priority_themes_are_served_first(db: PgPool) {
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        add_theme(&db, "Bees").await;
+        let urgent = add_theme(&db, "Wasps").await;
+        add_theme(&db, "Ants").await;
+        sqlx::query("UPDATE themes SET priority = 1 WHERE id = $1")
+            .bind(urgent)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let served = next_theme(&state).await.theme.unwrap();
+        assert_eq!(served.id, urgent);
+    }
+
+    #[sqlx::test]
+    async fn get_next_theme_reports_closed_voting(db: PgPool) {
+        let config = Config {
+            voting_closes_at: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        add_theme(&db, "Bees").await;
+
+        let response = next_theme(&state).await;
+        assert!(response.closed);
+        assert!(response.theme.is_none());
+    }
+
+    const ADMIN: &str = "admin";
+
+    /// State for an admin of the default config.
+    fn admin_state(db: &PgPool) -> AppState {
+        let config = Config {
+            admin_user_ids: vec![ADMIN.to_string()],
+            ..Config::default()
+        };
+        test_state(db.clone(), config, ADMIN)
+    }
+
+    async fn export(state: &AppState) -> Vec<ExportVote> {
+        let query = RecentVotesQuery {
+            limit: None,
+            before: None,
+            before_id: None,
+        };
+        let Json(votes) = export_votes(State(state.clone()), signed_in(), Query(query))
+            .await
+            .unwrap();
+        votes
+    }
+
+    #[sqlx::test]
+    async fn deleted_themes_leave_the_pool_but_keep_their_votes(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        vote(
+            &test_state(db.clone(), Config::default(), VOTER),
+            bees,
+            "yes",
+        )
+        .await
+        .unwrap();
+
+        let admin = admin_state(&db);
+        let status = delete_theme(State(admin.clone()), signed_in(), Path(bees))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let newcomer = test_state(db.clone(), Config::default(), "newcomer");
+        let served = next_theme(&newcomer).await;
+        assert_eq!(served.theme.map(|theme| theme.id), Some(wasps));
+        assert_eq!(served.total, 1);
+
+        let exported = export(&admin).await;
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].theme_id, bees);
+        assert_eq!(exported[0].user_id, VOTER);
+    }
+
+    async fn progress(state: &AppState, exclude_skips: Option<bool>) -> ProgressResponse {
+        let query = ProgressQuery { exclude_skips };
+        let Json(progress) = get_progress(State(state.clone()), signed_in(), Query(query))
+            .await
+            .unwrap();
+        progress
+    }
+
+    #[sqlx::test]
+    async fn progress_follows_votes(db: PgPool) {
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        let mut themes = Vec::new();
+        for content in ["Bees", "Wasps", "Ants", "Moths"] {
+            themes.push(add_theme(&db, content).await);
+        }
+
+        let start = progress(&state, None).await;
+        assert_eq!((start.seen, start.total, start.percent), (0, 4, 0.0));
+
+        for (theme_id, vote_type) in themes.iter().zip(["yes", "no", "skip"]) {
+            vote(&state, *theme_id, vote_type).await.unwrap();
+        }
+        let after = progress(&state, None).await;
+        assert_eq!((after.seen, after.total, after.percent), (3, 4, 75.0));
+
+        // The same figures come along with the next theme
+        let next = next_theme(&state).await;
+        assert_eq!((next.seen, next.total), (after.seen, after.total));
+    }
+
+    #[sqlx::test]
+    async fn next_theme_draws_from_the_same_themes_as_filtering_voted_ids(db: PgPool) {
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        let other = test_state(db.clone(), Config::default(), "other");
+        let mut themes = Vec::new();
+        for i in 0..8 {
+            themes.push(add_theme(&db, &format!("Theme {i}")).await);
+        }
+        for theme_id in &themes[..3] {
+            vote(&state, *theme_id, "yes").await.unwrap();
+        }
+        vote(&other, themes[3], "no").await.unwrap();
+        sqlx::query("UPDATE themes SET deleted_at = NOW() WHERE id = $1")
+            .bind(themes[7])
+            .execute(&db)
+            .await
+            .unwrap();
+
+        // What the old code did: fetch the caller's voted ids, then pick from the rest
+        let voted: Vec<i32> = sqlx::query_scalar("SELECT theme_id FROM votes WHERE user_id = $1")
+            .bind(VOTER)
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        let active: Vec<i32> =
+            sqlx::query_scalar("SELECT id FROM themes WHERE deleted_at IS NULL ORDER BY id")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        let expected: std::collections::BTreeSet<i32> = active
+            .into_iter()
+            .filter(|id| !voted.contains(id))
+            .collect();
+
+        for strategy in Strategy::ALL {
+            let mut served = std::collections::BTreeSet::new();
+            for _ in 0..100 {
+                let query = NextThemeQuery {
+                    strategy: Some(*strategy),
+                    tags: None,
+                    tag_match: TagMatch::Any,
+                    exclude_skips: None,
+                };
+                let Json(response) =
+                    get_next_theme(State(state.clone()), signed_in(), Query(query))
+                        .await
+                        .unwrap();
+                served.insert(response.theme.unwrap().id);
+            }
+            assert_eq!(served, expected, "{strategy:?}");
+        }
+    }
+
+    async fn all_stats(db: &PgPool) -> Vec<VoteStats> {
+        let page = Pagination {
+            limit: None,
+            offset: None,
+        };
+        vote_stats(db, &page, None, 0, StatsSort::Yes, None)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn serving_a_theme_records_an_impression_but_no_vote(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        for user in ["first", "second"] {
+            let state = test_state(db.clone(), Config::default(), user);
+            next_theme(&state).await;
+            // Served twice, still seen once
+            next_theme(&state).await;
+        }
+
+        // Impressions are written in the background
+        let mut impressions = 0;
+        for _ in 0..50 {
+            impressions = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM theme_impressions")
+                .fetch_one(&db)
+                .await
+                .unwrap();
+            if impressions == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(impressions, 2);
+
+        let votes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM votes")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(votes, 0);
+        let stats = all_stats(&db).await;
+        assert_eq!(stats[0].theme_id, theme_id);
+        assert_eq!((stats[0].seen_count, stats[0].total_votes), (2, 0));
+    }
+
+    /// Sends `request` through the full router, as if from `peer`.
+    async fn call_from(state: &AppState, peer: [u8; 4], mut request: Request) -> Response {
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((peer, 40000))));
+        // The router is always ready, so there's no need to wait on poll_ready
+        tower::Service::call(&mut router(state.clone()), request)
+            .await
+            .unwrap()
+    }
+
+    async fn call(state: &AppState, request: Request) -> Response {
+        call_from(state, [127, 0, 0, 1], request).await
+    }
+
+    fn get_request(uri: &str) -> Request {
+        Request::builder()
+            .uri(uri)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    }
+
+    /// A GET carrying a token, which `Authenticator::Fixed` accepts as its user.
+    fn signed_in_request(uri: &str) -> Request {
+        let mut request = get_request(uri);
+        request
+            .headers_mut()
+            .insert("Authorization", HeaderValue::from_static("Bearer test"));
+        request
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn random_theme_needs_no_auth_header(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        let state = test_state(db, Config::default(), VOTER);
+
+        let response = call(&state, get_request("/themes/random")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let theme = json_body(response).await;
+        assert_eq!(theme["id"], theme_id);
+        assert_eq!(theme["content"], "Bees");
+    }
+
+    /// The `X-RateLimit-Remaining` a response reports.
+    fn remaining(response: &Response) -> u32 {
+        response.headers()["x-ratelimit-remaining"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn public_routes_answer_429_once_a_client_goes_over_the_limit(db: PgPool) {
+        let state = AppState {
+            public_limiter: Arc::new(RateLimiter::new(2, Duration::from_secs(60))),
+            ..test_state(db, Config::default(), VOTER)
+        };
+        let client = [203, 0, 113, 1];
+
+        for expected in [1, 0] {
+            let response = call_from(&state, client, get_request("/")).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(remaining(&response), expected);
+            assert_eq!(response.headers()["x-ratelimit-limit"], "2");
+        }
+        let response = call_from(&state, client, get_request("/")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(remaining(&response), 0);
+        let reset: u64 = response.headers()["x-ratelimit-reset"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&reset), "{reset}");
+
+        // Other clients have their own allowance
+        let other = call_from(&state, [203, 0, 113, 2], get_request("/")).await;
+        assert_eq!(other.status(), StatusCode::OK);
+    }
+
+    #[sqlx::test]
+    async fn signed_in_requests_count_down_the_users_allowance(db: PgPool) {
+        add_theme(&db, "Bees").await;
+        let state = AppState {
+            user_limiter: Arc::new(RateLimiter::new(3, Duration::from_secs(60))),
+            ..test_state(db, Config::default(), VOTER)
+        };
+
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            let response = call(&state, signed_in_request("/themes/progress")).await;
+            seen.push((response.status(), remaining(&response)));
+        }
+        assert_eq!(
+            seen,
+            [
+                (StatusCode::OK, 2),
+                (StatusCode::OK, 1),
+                (StatusCode::OK, 0),
+                (StatusCode::TOO_MANY_REQUESTS, 0),
+            ]
+        );
+
+        // The allowance is the user's, wherever they call from
+        let elsewhere = call_from(
+            &state,
+            [203, 0, 113, 1],
+            signed_in_request("/themes/progress"),
+        )
+        .await;
+        assert_eq!(elsewhere.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[sqlx::test]
+    async fn the_theme_count_is_cached_until_themes_change(db: PgPool) {
+        add_theme(&db, "Bees").await;
+        let state = AppState {
+            theme_count: Arc::new(Cached::new(Duration::from_secs(60))),
+            ..admin_state(&db)
+        };
+        let count = || async { json_body(call(&state, get_request("/themes/count")).await).await };
+        assert_eq!(count().await["total"], 1);
+
+        // Added behind the server's back, so only a fresh query would see it
+        add_theme(&db, "Wasps").await;
+        assert_eq!(count().await["total"], 1);
+
+        let request = CreateThemeRequest {
+            content: "Ants".to_string(),
+            description: None,
+        };
+        let (status, _) = create_theme(State(state.clone()), signed_in(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(count().await["total"], 3);
+    }
+
+    #[tokio::test]
+    async fn each_auth_failure_has_its_reason() {
+        // Nothing listens here, which these failures never get far enough to notice
+        let jwks = Jwks::spawn("http://127.0.0.1:9/jwks", Duration::from_secs(3600));
+        let reason = |value: Option<&'static str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(value) = value {
+                headers.insert("Authorization", HeaderValue::from_static(value));
+            }
+            let jwks = jwks.clone();
+            async move { verify_jwt(&jwks, &headers).await.unwrap_err().reason() }
+        };
+        assert_eq!(reason(None).await, "missing_header");
+        assert_eq!(reason(Some("Token abc")).await, "no_bearer_prefix");
+        assert_eq!(reason(Some("Bearer not-a-jwt")).await, "malformed");
+
+        for (err, expected) in [
+            (AuthError::NoMatchingKey, "unknown_key"),
+            (AuthError::NetworkError("down".into()), "keys_unavailable"),
+            (AuthError::JwksError("empty".into()), "keys_unavailable"),
+            (AuthError::Verification, "verification_failed"),
+            (AuthError::InvalidClaims, "invalid_claims"),
+            (AuthError::DecodeHeader, "malformed"),
+            (AuthError::InvalidAlgorithm, "malformed"),
+        ] {
+            assert_eq!(
+                AuthFailure::from_jwt_error(&err).reason(),
+                expected,
+                "{err:?}"
+            );
+        }
+        assert_eq!(AuthFailure::Expired.reason(), "expired");
+    }
+
+    /// Casts a vote as `user_id`.
+    async fn vote_as(db: &PgPool, user_id: &str, theme_id: i32, vote_type: &str) {
+        let state = test_state(db.clone(), Config::default(), user_id);
+        vote(&state, theme_id, vote_type).await.unwrap();
+    }
+
+    /// Casts the votes in `votes`, one voter per vote type listed.
+    async fn seed_votes(db: &PgPool, theme_id: i32, votes: &[&str]) {
+        for (i, vote_type) in votes.iter().enumerate() {
+            vote_as(db, &format!("voter {i}"), theme_id, vote_type).await;
+        }
+    }
+
+    #[sqlx::test]
+    async fn leaderboard_ranks_ties_together_with_percentages(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        add_theme(&db, "Moths").await;
+        seed_votes(&db, bees, &["yes", "yes", "yes", "no"]).await;
+        seed_votes(&db, wasps, &["yes", "yes", "yes", "skip"]).await;
+        seed_votes(&db, ants, &["yes", "no"]).await;
+
+        let query = LeaderboardQuery { limit: Some(3) };
+        let Json(entries) = get_leaderboard(State(admin_state(&db)), signed_in(), Query(query))
+            .await
+            .unwrap();
+        let ranked: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.rank, entry.theme_id, entry.yes_percent))
+            .collect();
+        assert_eq!(
+            ranked,
+            [(1, bees, 75.0), (1, wasps, 100.0), (3, ants, 50.0)]
+        );
+    }
+
+    async fn skip_as(db: &PgPool, user_id: &str, theme_id: i32, reason: &str) {
+        let request = VoteRequest {
+            theme_id,
+            vote_type: "skip".to_string(),
+            reason: Some(reason.to_string()),
+        };
+        let state = test_state(db.clone(), Config::default(), user_id);
+        submit_vote(State(state), signed_in(), JsonOrForm(request))
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn skip_reasons_are_stored_and_summarized(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        skip_as(&db, "first", theme_id, "  not interested ").await;
+        skip_as(&db, "second", theme_id, "not interested").await;
+        skip_as(&db, "third", theme_id, "duplicate").await;
+        vote_as(&db, "fourth", theme_id, "skip").await;
+
+        let stored: Option<String> =
+            sqlx::query_scalar("SELECT skip_reason FROM votes WHERE user_id = 'first'")
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        assert_eq!(stored.as_deref(), Some("not interested"));
+
+        let Json(reasons) = get_skip_reasons(State(admin_state(&db)), signed_in())
+            .await
+            .unwrap();
+        let summary: Vec<_> = reasons
+            .iter()
+            .map(|r| (r.theme_id, r.reason.as_str(), r.count))
+            .collect();
+        assert_eq!(
+            summary,
+            [(theme_id, "not interested", 2), (theme_id, "duplicate", 1)]
+        );
+    }
+
+    #[sqlx::test]
+    async fn database_errors_map_to_503_or_500(db: PgPool) {
+        let timed_out = AppError::Database(sqlx::Error::PoolTimedOut).into_response();
+        assert_eq!(timed_out.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let missing_column = sqlx::query("SELECT no_such_column FROM themes")
+            .execute(&db)
+            .await
+            .unwrap_err();
+        let response = AppError::from(missing_column).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    async fn next_theme_by(state: &AppState, strategy: Strategy) -> Option<Theme> {
+        let query = NextThemeQuery {
+            strategy: Some(strategy),
+            tags: None,
+            tag_match: TagMatch::Any,
+            exclude_skips: None,
+        };
+        let Json(response) = get_next_theme(State(state.clone()), signed_in(), Query(query))
+            .await
+            .unwrap();
+        response.theme
+    }
+
+    #[sqlx::test]
+    async fn weighted_selection_favors_themes_with_fewer_votes(db: PgPool) {
+        const DRAWS: usize = 300;
+        let popular = add_theme(&db, "Bees").await;
+        let fresh = add_theme(&db, "Wasps").await;
+        seed_votes(&db, popular, &["yes"; 9]).await;
+        let state = test_state(db.clone(), Config::default(), VOTER);
+
+        let mut fresh_draws = 0;
+        for _ in 0..DRAWS {
+            if next_theme_by(&state, Strategy::Weighted).await.unwrap().id == fresh {
+                fresh_draws += 1;
+            }
+        }
+        // Weights 1/10 and 1/1 give the fresh theme 10 draws in 11, about 273 of 300;
+        // a fair coin would need to be more than 13 standard deviations off to pass
+        assert!(fresh_draws > 2 * DRAWS / 3, "{fresh_draws} of {DRAWS}");
+    }
+
+    #[sqlx::test]
+    async fn config_reflects_the_server_settings(db: PgPool) {
+        let closes_at = Utc::now() - chrono::Duration::hours(1);
+        let config = Config {
+            voting_closes_at: Some(closes_at),
+            allow_revote: true,
+            theme_length: theme_content::LengthLimits { min: 4, max: 60 },
+            results_page_size: 25,
+            ..Config::default()
+        };
+        let state = test_state(db, config, VOTER);
+
+        // No auth header: the config is public
+        let response = call(&state, get_request("/config")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let config = json_body(response).await;
+        assert_eq!(config["vote_types"], serde_json::json!(VOTE_TYPES));
+        assert_eq!(config["voting_open"], false);
+        assert_eq!(
+            config["voting_closes_at"]
+                .as_str()
+                .unwrap()
+                .parse::<DateTime<Utc>>()
+                .unwrap(),
+            closes_at
+        );
+        assert_eq!(config["allow_revote"], true);
+        assert_eq!(config["min_theme_length"], 4);
+        assert_eq!(config["max_theme_length"], 60);
+        assert_eq!(config["page_size"], 25);
+    }
+
+    async fn featured(state: &AppState) -> Result<FeaturedTheme, AppError> {
+        get_featured_theme(State(state.clone()), signed_in())
+            .await
+            .map(|Json(featured)| featured)
+    }
+
+    #[sqlx::test]
+    async fn featured_theme_is_set_by_an_admin_and_served_with_stats(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        seed_votes(&db, theme_id, &["yes", "yes", "no"]).await;
+        let state = admin_state(&db);
+
+        assert!(matches!(featured(&state).await, Err(AppError::NotFound(_))));
+
+        let request = SetFeaturedRequest {
+            theme_id,
+            date: None,
+        };
+        let status = set_featured_theme(State(state.clone()), signed_in(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let featured = featured(&state).await.unwrap();
+        assert_eq!(featured.date, Utc::now().date_naive());
+        assert_eq!(featured.theme_id, theme_id);
+        assert_eq!(featured.content, "Bees");
+        assert_eq!((featured.yes_votes, featured.no_votes), (Some(2), Some(1)));
+        assert_eq!(featured.total_votes, Some(3));
+    }
+
+    /// Status of `uri` for `state`'s user, sent with a token when `signed_in`.
+    async fn status_of(state: &AppState, uri: &str, signed_in: bool) -> StatusCode {
+        let request = if signed_in {
+            signed_in_request(uri)
+        } else {
+            get_request(uri)
+        };
+        call(state, request).await.status()
+    }
+
+    #[sqlx::test]
+    async fn hidden_results_are_only_shown_to_admins(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        seed_votes(&db, theme_id, &["yes", "no"]).await;
+        sqlx::query("INSERT INTO featured_themes (feature_date, theme_id) VALUES ($1, $2)")
+            .bind(Utc::now().date_naive())
+            .bind(theme_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let hidden = Config {
+            hide_results_until_close: true,
+            admin_user_ids: vec![ADMIN.to_string()],
+            ..Config::default()
+        };
+        let results = [
+            "/admin/stats".to_string(),
+            "/results/public".to_string(),
+            "/results/pairwise".to_string(),
+            format!("/themes/{theme_id}/stats"),
+        ];
+
+        let voter = test_state(db.clone(), hidden.clone(), VOTER);
+        let admin = test_state(db.clone(), hidden, ADMIN);
+        let open = test_state(db, Config::default(), VOTER);
+        for uri in &results {
+            assert_eq!(
+                status_of(&voter, uri, false).await,
+                StatusCode::FORBIDDEN,
+                "{uri}"
+            );
+            assert_eq!(
+                status_of(&voter, uri, true).await,
+                StatusCode::FORBIDDEN,
+                "{uri}"
+            );
+            assert_eq!(status_of(&admin, uri, true).await, StatusCode::OK, "{uri}");
+            assert_eq!(status_of(&open, uri, false).await, StatusCode::OK, "{uri}");
+        }
+
+        // The featured theme is still shown, only without its counts
+        let featured = json_body(call(&voter, get_request("/themes/featured")).await).await;
+        assert_eq!(featured["theme_id"], theme_id);
+        assert!(featured["yes_votes"].is_null() && featured["total_votes"].is_null());
+        let featured = json_body(call(&open, get_request("/themes/featured")).await).await;
+        assert_eq!(featured["yes_votes"], 1);
+        assert_eq!(featured["total_votes"], 2);
+    }
+
+    async fn edit(state: &AppState, theme_id: i32, content: &str) -> Result<Theme, AppError> {
+        let request = UpdateThemeRequest {
+            content: content.to_string(),
+        };
+        update_theme(
+            State(state.clone()),
+            signed_in(),
+            Path(theme_id),
+            Json(request),
+        )
+        .await
+        .map(|Json(theme)| theme)
+    }
+
+    #[sqlx::test]
+    async fn editing_a_theme_keeps_its_votes_and_records_the_change(db: PgPool) {
+        let theme_id = add_theme(&db, "Bess").await;
+        seed_votes(&db, theme_id, &["yes"]).await;
+        let state = admin_state(&db);
+
+        let theme = edit(&state, theme_id, "Bees").await.unwrap();
+        assert_eq!((theme.id, theme.content.as_str()), (theme_id, "Bees"));
+
+        let votes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM votes WHERE theme_id = $1")
+            .bind(theme_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(votes, 1);
+
+        let edits: Vec<(i32, String, String, String)> = sqlx::query_as(
+            "SELECT theme_id, previous_content, new_content, editor_user_id FROM theme_edits",
+        )
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(
+            edits,
+            [(theme_id, "Bess".into(), "Bees".into(), ADMIN.into())]
+        );
+    }
+
+    #[sqlx::test]
+    async fn editing_a_theme_into_another_is_a_conflict(db: PgPool) {
+        add_theme(&db, "Bees").await;
+        let theme_id = add_theme(&db, "Wasps").await;
+        let state = admin_state(&db);
+
+        assert!(matches!(
+            edit(&state, theme_id, "Bees").await,
+            Err(AppError::Conflict(_))
+        ));
+        let edits: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM theme_edits")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(edits, 0);
+    }
+
+    #[sqlx::test]
+    async fn stats_csv_export_aggregates_and_escapes(db: PgPool) {
+        let plain = add_theme(&db, "Bees").await;
+        let commas = add_theme(&db, "Small, fast, \"loud\"").await;
+        seed_votes(&db, plain, &["yes", "yes", "no"]).await;
+        seed_votes(&db, commas, &["yes", "skip"]).await;
+        let state = admin_state(&db);
+
+        let query = StatsExportQuery {
+            format: ExportFormat::Csv,
+        };
+        let response = export_stats(State(state), signed_in(), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"theme_stats.csv\""
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            format!(
+                "theme_id,content,yes_votes,no_votes,skip_votes,maybe_votes,total_votes\n\
+                 {plain},Bees,2,1,0,0,3\n\
+                 {commas},\"Small, fast, \"\"loud\"\"\",1,0,1,0,2\n"
+            )
+        );
+    }
+
+    #[sqlx::test]
+    async fn pairwise_ranking_export_defaults_to_csv_like_the_stats_export(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        pick(&db, VOTER, bees, wasps).await;
+        let query: Query<PairwiseRankingQuery> =
+            Query::try_from_uri(&"/admin/pairwise/ranking".parse().unwrap()).unwrap();
+
+        let response = export_pairwise_ranking(State(admin_state(&db)), signed_in(), query)
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"pairwise_ranking.csv\""
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            format!(
+                "theme_id,content,wins,losses,win_rate,elo\n\
+                 {bees},Bees,1,0,100.0,1516.0\n\
+                 {wasps},Wasps,0,1,0.0,1484.0\n"
+            )
+        );
+    }
+
+    async fn remaining_ids(state: &AppState) -> Vec<i32> {
+        let all = Pagination {
+            limit: None,
+            offset: None,
+        };
+        let Json(themes) = get_remaining_themes(State(state.clone()), signed_in(), Query(all))
+            .await
+            .unwrap();
+        themes.into_iter().map(|theme| theme.id).collect()
+    }
+
+    #[sqlx::test]
+    async fn remaining_themes_shrink_as_votes_are_cast(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let state = test_state(db, Config::default(), VOTER);
+
+        assert_eq!(remaining_ids(&state).await, [bees, wasps]);
+        vote(&state, bees, "yes").await.unwrap();
+        assert_eq!(remaining_ids(&state).await, [wasps]);
+        vote(&state, wasps, "skip").await.unwrap();
+        assert!(remaining_ids(&state).await.is_empty());
+    }
+
+    async fn tag(state: &AppState, theme_id: i32, tag: &str) {
+        let request = AddTagRequest {
+            tag: tag.to_string(),
+        };
+        let Json(tags) = add_theme_tag(
+            State(state.clone()),
+            signed_in(),
+            Path(theme_id),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        assert!(tags.iter().any(|t| t == tag));
+    }
+
+    async fn listed_with_tags(state: &AppState, tags: &str, tag_match: TagMatch) -> Vec<i32> {
+        let query = ThemeListQuery {
+            search: None,
+            tags: Some(tags.to_string()),
+            tag_match,
+        };
+        let Json(themes) = list_themes(State(state.clone()), Query(query))
+            .await
+            .unwrap();
+        themes.into_iter().map(|theme| theme.id).collect()
+    }
+
+    #[sqlx::test]
+    async fn tag_filters_match_any_or_all_tags(db: PgPool) {
+        let horror = add_theme(&db, "Haunted house").await;
+        let coop = add_theme(&db, "Two keys").await;
+        let both = add_theme(&db, "Zombies together").await;
+        add_theme(&db, "Untagged").await;
+        let state = admin_state(&db);
+        tag(&state, horror, "horror").await;
+        tag(&state, coop, "co-op").await;
+        tag(&state, both, "horror").await;
+        tag(&state, both, "co-op").await;
+
+        let mut any = listed_with_tags(&state, "horror,co-op", TagMatch::Any).await;
+        any.sort();
+        assert_eq!(any, [horror, coop, both]);
+        assert_eq!(
+            listed_with_tags(&state, "horror,co-op", TagMatch::All).await,
+            [both]
+        );
+        assert_eq!(
+            listed_with_tags(&state, "horror", TagMatch::All)
+                .await
+                .len(),
+            2
+        );
+
+        // The next-theme filter applies the same rules
+        let query = NextThemeQuery {
+            strategy: None,
+            tags: Some("co-op,horror".to_string()),
+            tag_match: TagMatch::All,
+            exclude_skips: None,
+        };
+        let Json(next) = get_next_theme(State(state), signed_in(), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(next.theme.unwrap().id, both);
+    }
+
+    #[sqlx::test]
+    async fn merging_keeps_each_voters_latest_vote_once(db: PgPool) {
+        let keep = add_theme(&db, "Bees").await;
+        let merged = add_theme(&db, "Bees!").await;
+        // "both" voted on each copy, the merged one last
+        vote_as(&db, "both", keep, "yes").await;
+        vote_as(&db, "both", merged, "no").await;
+        vote_as(&db, "kept only", keep, "maybe").await;
+        vote_as(&db, "merged only", merged, "yes").await;
+        let state = admin_state(&db);
+
+        let request = MergeThemesRequest {
+            keep_id: keep,
+            merge_ids: vec![merged],
+        };
+        let Json(response) = merge_themes(State(state), signed_in(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(response.merged_ids, [merged]);
+        assert_eq!(response.total_votes, 3);
+
+        let votes: Vec<(String, i32, String)> =
+            sqlx::query_as("SELECT user_id, theme_id, vote_type FROM votes ORDER BY user_id")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(
+            votes,
+            [
+                ("both".into(), keep, "no".into()),
+                ("kept only".into(), keep, "maybe".into()),
+                ("merged only".into(), keep, "yes".into()),
+            ]
+        );
+        let deleted: bool =
+            sqlx::query_scalar("SELECT deleted_at IS NOT NULL FROM themes WHERE id = $1")
+                .bind(merged)
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        assert!(deleted);
+    }
+
+    #[sqlx::test]
+    async fn compare_splits_the_shared_voters(db: PgPool) {
+        let a = add_theme(&db, "Bees").await;
+        let b = add_theme(&db, "Wasps").await;
+        for (user, on_a, on_b) in [
+            ("prefers a", "yes", "no"),
+            ("prefers a too", "maybe", "no"),
+            ("prefers b", "no", "maybe"),
+            ("likes both", "yes", "yes"),
+            ("skipped a", "skip", "no"),
+            ("skipped b", "maybe", "skip"),
+        ] {
+            vote_as(&db, user, a, on_a).await;
+            vote_as(&db, user, b, on_b).await;
+        }
+        vote_as(&db, "only a", a, "yes").await;
+        vote_as(&db, "only b", b, "no").await;
+        let state = admin_state(&db);
+
+        let Json(compared) =
+            compare_themes(State(state), signed_in(), Query(CompareQuery { a, b }))
+                .await
+                .unwrap();
+        assert_eq!((compared.a.theme_id, compared.a.total_votes), (a, 7));
+        assert_eq!((compared.b.theme_id, compared.b.total_votes), (b, 7));
+        assert_eq!(compared.a.yes_votes, 3);
+        assert_eq!(compared.voted_both, 6);
+        // Skipping either theme is no opinion, not a preference for the other
+        assert_eq!(
+            (compared.preferred_a, compared.preferred_b, compared.same),
+            (2, 1, 1)
+        );
+        assert_eq!(compared.no_opinion, 2);
+    }
+
+    async fn vote_type_of(db: &PgPool, theme_id: i32) -> String {
+        sqlx::query_scalar("SELECT vote_type FROM votes WHERE user_id = $1 AND theme_id = $2")
+            .bind(VOTER)
+            .bind(theme_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn revoting_updates_the_vote_only_when_allowed(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+
+        let config = Config {
+            allow_revote: false,
+            ..Config::default()
+        };
+        let final_votes = test_state(db.clone(), config, VOTER);
+        vote(&final_votes, theme_id, "yes").await.unwrap();
+        assert!(matches!(
+            vote(&final_votes, theme_id, "no").await,
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(vote_type_of(&db, theme_id).await, "yes");
+
+        // Votes may be changed by default
+        let changeable = test_state(db.clone(), Config::default(), VOTER);
+        vote(&changeable, theme_id, "no").await.unwrap();
+        assert_eq!(vote_type_of(&db, theme_id).await, "no");
+    }
+
+    #[sqlx::test]
+    async fn theme_stats_cover_one_theme_or_are_not_found(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        let other = add_theme(&db, "Wasps").await;
+        seed_votes(&db, theme_id, &["yes", "no", "skip", "maybe", "yes"]).await;
+        seed_votes(&db, other, &["no"]).await;
+        let state = test_state(db, Config::default(), VOTER);
+
+        let Json(tally) = get_theme_stats(State(state.clone()), signed_in(), Path(theme_id))
+            .await
+            .unwrap();
+        assert_eq!((tally.theme_id, tally.content.as_str()), (theme_id, "Bees"));
+        assert_eq!(
+            (
+                tally.yes_votes,
+                tally.no_votes,
+                tally.skip_votes,
+                tally.maybe_votes
+            ),
+            (2, 1, 1, 1)
+        );
+        assert_eq!(tally.total_votes, 5);
+
+        assert!(matches!(
+            get_theme_stats(State(state), signed_in(), Path(other + 1)).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    async fn remaining_by_tag(state: &AppState) -> Vec<(Option<String>, i64)> {
+        let Json(counts) = get_remaining_by_tag(State(state.clone()), signed_in())
+            .await
+            .unwrap();
+        counts.into_iter().map(|c| (c.tag, c.remaining)).collect()
+    }
+
+    #[sqlx::test]
+    async fn remaining_counts_per_tag_drop_as_the_user_votes(db: PgPool) {
+        let haunted = add_theme(&db, "Haunted house").await;
+        let zombies = add_theme(&db, "Zombies").await;
+        let untagged = add_theme(&db, "Bees").await;
+        let state = admin_state(&db);
+        tag(&state, haunted, "horror").await;
+        tag(&state, zombies, "horror").await;
+        tag(&state, zombies, "co-op").await;
+
+        let counts = |horror, coop, untagged| {
+            vec![
+                (Some("co-op".to_string()), coop),
+                (Some("horror".to_string()), horror),
+                (None, untagged),
+            ]
+        };
+        assert_eq!(remaining_by_tag(&state).await, counts(2, 1, 1));
+        vote(&state, haunted, "yes").await.unwrap();
+        assert_eq!(remaining_by_tag(&state).await, counts(1, 1, 1));
+        vote(&state, zombies, "no").await.unwrap();
+        assert_eq!(remaining_by_tag(&state).await, counts(0, 0, 1));
+        vote(&state, untagged, "skip").await.unwrap();
+        assert_eq!(remaining_by_tag(&state).await, counts(0, 0, 0));
+    }
+
+    /// Log output collected in memory.
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_logs_are_one_parseable_object_per_line() {
+        let buffer = LogBuffer::default();
+        let writer = buffer.clone();
+        tracing::subscriber::with_default(json_logs(move || writer.clone()), || {
+            tracing::info!(request_id = 7, status = 200, "request done");
+            tracing::warn!("second line");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["fields"]["message"], "request done");
+        assert_eq!(lines[0]["fields"]["request_id"], 7);
+        assert_eq!(lines[0]["fields"]["status"], 200);
+        assert_eq!(lines[1]["level"], "WARN");
+    }
+
+    fn resurfacing_config() -> Config {
+        Config {
+            resurface_skips: true,
+            skip_cooldown_votes: 2,
+            skip_cooldown_minutes: 60,
+            ..Config::default()
+        }
+    }
+
+    #[sqlx::test]
+    async fn skipped_themes_resurface_after_enough_other_votes(db: PgPool) {
+        let skipped = add_theme(&db, "Bees").await;
+        let second = add_theme(&db, "Wasps").await;
+        let third = add_theme(&db, "Ants").await;
+        let state = test_state(db, resurfacing_config(), VOTER);
+        vote(&state, skipped, "skip").await.unwrap();
+
+        // Only the other themes are served until two votes have gone by
+        for theme_id in [second, third] {
+            let served = next_theme(&state).await.theme.unwrap().id;
+            assert_ne!(served, skipped);
+            vote(&state, theme_id, "yes").await.unwrap();
+        }
+        assert_eq!(next_theme(&state).await.theme.unwrap().id, skipped);
+    }
+
+    #[sqlx::test]
+    async fn skipped_themes_resurface_once_the_cooldown_has_passed(db: PgPool) {
+        let skipped = add_theme(&db, "Bees").await;
+        let state = test_state(db.clone(), resurfacing_config(), VOTER);
+        vote(&state, skipped, "skip").await.unwrap();
+        assert!(next_theme(&state).await.theme.is_none());
+
+        sqlx::query("UPDATE votes SET updated_at = NOW() - INTERVAL '59 minutes'")
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(next_theme(&state).await.theme.is_none());
+
+        sqlx::query("UPDATE votes SET updated_at = NOW() - INTERVAL '61 minutes'")
+            .execute(&db)
+            .await
+            .unwrap();
+        assert_eq!(next_theme(&state).await.theme.unwrap().id, skipped);
+    }
+
+    #[sqlx::test]
+    async fn reset_theme_reenters_the_pool_of_users_who_voted(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        let voter = test_state(db.clone(), Config::default(), VOTER);
+        vote(&voter, theme_id, "yes").await.unwrap();
+        seed_votes(&db, theme_id, &["no"]).await;
+        assert!(next_theme(&voter).await.theme.is_none());
+        let admin = admin_state(&db);
+
+        let reset = |confirm| {
+            reset_theme_votes(
+                State(admin.clone()),
+                signed_in(),
+                Path(theme_id),
+                Query(ResetVotesQuery { confirm }),
+            )
+        };
+        assert!(matches!(reset(false).await, Err(AppError::BadRequest(_))));
+        assert!(next_theme(&voter).await.theme.is_none());
+
+        let Json(response) = reset(true).await.unwrap();
+        assert_eq!((response.theme_id, response.deleted), (theme_id, 2));
+        assert_eq!(next_theme(&voter).await.theme.unwrap().id, theme_id);
+    }
+
+    async fn stats_for_ids(state: &AppState, ids: &str) -> Result<Vec<i32>, AppError> {
+        let all = Pagination {
+            limit: None,
+            offset: None,
+        };
+        let filter = StatsFilter {
+            ids: Some(ids.to_string()),
+            sort: StatsSort::default(),
+        };
+        let Json(stats) =
+            get_stats(State(state.clone()), signed_in(), Query(all), Query(filter)).await?;
+        Ok(stats.into_iter().map(|s| s.theme_id).collect())
+    }
+
+    #[sqlx::test]
+    async fn stats_for_ids_keep_only_those_in_the_order_asked(db: PgPool) {
+        let mut themes = Vec::new();
+        for content in ["Bees", "Wasps", "Ants", "Moths"] {
+            themes.push(add_theme(&db, content).await);
+        }
+        // Vote counts that would sort differently from the order asked
+        seed_votes(&db, themes[1], &["yes", "yes"]).await;
+        let state = admin_state(&db);
+
+        let ids = format!("{},{},{},{}", themes[2], themes[0], themes[1], themes[2]);
+        assert_eq!(
+            stats_for_ids(&state, &ids).await.unwrap(),
+            [themes[2], themes[0], themes[1]]
+        );
+
+        assert!(matches!(
+            stats_for_ids(&state, "1,two").await,
+            Err(AppError::BadRequest(_))
+        ));
+        let too_many = (1..=MAX_STATS_IDS as i32 + 1)
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        assert!(matches!(
+            stats_for_ids(&state, &too_many).await,
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn sticky_mode_serves_the_same_theme_until_it_gets_a_vote(db: PgPool) {
+        for i in 0..20 {
+            add_theme(&db, &format!("Theme {i}")).await;
+        }
+        let config = Config {
+            sticky_next_theme: true,
+            ..Config::default()
+        };
+        let state = test_state(db, config, VOTER);
+
+        let current = next_theme(&state).await.theme.unwrap().id;
+        for _ in 0..5 {
+            assert_eq!(next_theme(&state).await.theme.unwrap().id, current);
+        }
+
+        vote(&state, current, "skip").await.unwrap();
+        let next = next_theme(&state).await.theme.unwrap().id;
+        assert_ne!(next, current);
+        assert_eq!(next_theme(&state).await.theme.unwrap().id, next);
+    }
+
+    #[sqlx::test]
+    async fn my_export_holds_only_the_callers_votes(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        vote(&state, bees, "yes").await.unwrap();
+        skip_as(&db, VOTER, wasps, "not interested").await;
+        seed_votes(&db, bees, &["no", "maybe"]).await;
+        vote_as(&db, "other", wasps, "yes").await;
+
+        let Json(export) = export_my_data(State(state), signed_in()).await.unwrap();
+        assert_eq!(export.user_id, VOTER);
+        let votes: Vec<_> = export
+            .votes
+            .iter()
+            .map(|v| {
+                (
+                    v.theme_id,
+                    v.content.as_str(),
+                    v.vote_type.as_str(),
+                    v.skip_reason.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            votes,
+            [
+                (bees, "Bees", "yes", None),
+                (wasps, "Wasps", "skip", Some("not interested")),
+            ]
+        );
+    }
+
+    #[sqlx::test]
+    async fn a_vote_racing_a_theme_removal_is_not_found(db: PgPool) {
+        for (content, removal) in [
+            ("Bees", "UPDATE themes SET deleted_at = NOW() WHERE id = $1"),
+            ("Wasps", "DELETE FROM themes WHERE id = $1"),
+        ] {
+            let theme_id = add_theme(&db, content).await;
+            let state = test_state(db.clone(), Config::default(), VOTER);
+
+            // The removal holds the row while the vote comes in
+            let mut removing = db.begin().await.unwrap();
+            sqlx::query(removal)
+                .bind(theme_id)
+                .execute(&mut *removing)
+                .await
+                .unwrap();
+            let voting = tokio::spawn(async move { vote(&state, theme_id, "yes").await });
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(
+                !voting.is_finished(),
+                "the vote should wait for the removal"
+            );
+            removing.commit().await.unwrap();
+
+            assert!(
+                matches!(voting.await.unwrap(), Err(AppError::NotFound(_))),
+                "{removal}"
+            );
+            let votes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM votes")
+                .fetch_one(&db)
+                .await
+                .unwrap();
+            assert_eq!(votes, 0, "{removal}");
+        }
+    }
+
+    #[sqlx::test]
+    async fn unknown_strategies_are_rejected(db: PgPool) {
+        add_theme(&db, "Bees").await;
+        let state = test_state(db, Config::default(), VOTER);
+
+        let next = |strategy: &str| {
+            Request::builder()
+                .uri(format!("/themes/next?strategy={strategy}"))
+                .header(header::AUTHORIZATION, "Bearer token")
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+        assert_eq!(
+            call(&state, next("bogus")).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            call(&state, next("weighted")).await.status(),
+            StatusCode::OK
+        );
+    }
+
+    #[sqlx::test]
+    async fn the_configured_strategy_applies_when_none_is_asked_for(db: PgPool) {
+        const DRAWS: usize = 300;
+        let popular = add_theme(&db, "Bees").await;
+        let fresh = add_theme(&db, "Wasps").await;
+        seed_votes(&db, popular, &["yes"; 9]).await;
+        let config = Config {
+            default_strategy: Strategy::Weighted,
+            ..Config::default()
+        };
+        let state = test_state(db, config, VOTER);
+
+        let config = json_body(call(&state, get_request("/config")).await).await;
+        assert_eq!(config["default_strategy"], "weighted");
+
+        let mut fresh_draws = 0;
+        for _ in 0..DRAWS {
+            if next_theme(&state).await.theme.unwrap().id == fresh {
+                fresh_draws += 1;
+            }
+        }
+        // Random picks would give the fresh theme about half the draws, weighted ones 10 in 11
+        assert!(fresh_draws > 2 * DRAWS / 3, "{fresh_draws} of {DRAWS}");
+    }
+
+    #[sqlx::test]
+    async fn summary_matches_the_seeded_totals(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        add_theme(&db, "Ants").await;
+        let removed = add_theme(&db, "Moths").await;
+        // "voter 0" to "voter 3" on bees; "voter 0" and "voter 1" on wasps
+        seed_votes(&db, bees, &["yes", "yes", "no", "maybe"]).await;
+        seed_votes(&db, wasps, &["skip", "yes"]).await;
+        vote_as(&db, "gone", removed, "no").await;
+        sqlx::query("UPDATE themes SET deleted_at = NOW() WHERE id = $1")
+            .bind(removed)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let Json(summary) = get_summary(State(admin_state(&db)), signed_in())
+            .await
+            .unwrap();
+        assert_eq!(summary.total_votes, 6);
+        assert_eq!(
+            (summary.yes, summary.no, summary.skip, summary.maybe),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(summary.themes, 3);
+        assert_eq!(summary.voters, 4);
+
+        // The same numbers the per-theme stats add up to
+        let stats = all_stats(&db).await;
+        assert_eq!(
+            stats.iter().map(|s| s.total_votes).sum::<i64>(),
+            summary.total_votes
+        );
+        assert_eq!(stats.iter().map(|s| s.yes_votes).sum::<i64>(), summary.yes);
+    }
+
+    #[sqlx::test]
+    async fn heavily_skipped_themes_stop_being_served(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        let config = Config {
+            admin_user_ids: vec![ADMIN.to_string()],
+            hide_skip_ratio: Some(0.5),
+            hide_min_votes: 4,
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, ADMIN);
+
+        // Too few votes to judge, however many are skips
+        seed_votes(&db, theme_id, &["skip", "skip", "yes"]).await;
+        assert_eq!(next_theme(&state).await.theme.unwrap().id, theme_id);
+
+        vote_as(&db, "fourth voter", theme_id, "skip").await;
+        assert!(next_theme(&state).await.theme.is_none());
+
+        let Json(shown) = always_show_theme(State(state.clone()), signed_in(), Path(theme_id))
+            .await
+            .unwrap();
+        assert_eq!(shown.id, theme_id);
+        assert_eq!(next_theme(&state).await.theme.unwrap().id, theme_id);
+    }
+
+    #[sqlx::test]
+    async fn an_exhausted_pool_gives_503s_instead_of_hanging(
+        pool: PgPoolOptions,
+        connect: sqlx::postgres::PgConnectOptions,
+    ) {
+        let db = pool
+            .max_connections(1)
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_with(connect)
+            .await
+            .unwrap();
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        let timeouts_before = POOL_TIMEOUTS.load(Ordering::Relaxed);
+
+        // Another request holds the only connection
+        let held = db.acquire().await.unwrap();
+        let requests: Vec<_> = (0..4)
+            .map(|_| {
+                let state = state.clone();
+                tokio::spawn(async move { call(&state, get_request("/themes")).await.status() })
+            })
+            .collect();
+        for request in requests {
+            let status = tokio::time::timeout(Duration::from_secs(5), request)
+                .await
+                .expect("a request waited on the pool for too long")
+                .unwrap();
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        }
+        assert!(POOL_TIMEOUTS.load(Ordering::Relaxed) >= timeouts_before + 4);
+
+        drop(held);
+        let status = call(&state, get_request("/themes")).await.status();
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    /// The database's clock, between two votes, so stats can be asked for as of then.
+    async fn db_now(db: &PgPool) -> DateTime<Utc> {
+        let now = sqlx::query_scalar("SELECT NOW()")
+            .fetch_one(db)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        now
+    }
+
+    fn revoting_admin_state(db: &PgPool) -> AppState {
+        let config = Config {
+            admin_user_ids: vec![ADMIN.to_string()],
+            allow_revote: true,
+            revote_cooldown: Duration::ZERO,
+            ..Config::default()
+        };
+        test_state(db.clone(), config, ADMIN)
+    }
+
+    #[sqlx::test]
+    async fn stats_at_a_time_count_changed_votes_as_they_were_then(db: PgPool) {
+        let state = revoting_admin_state(&db);
+        let bees = add_theme(&db, "Bees").await;
+        let before_any = db_now(&db).await;
+        vote(&state, bees, "yes").await.unwrap();
+        let while_yes = db_now(&db).await;
+        vote(&state, bees, "no").await.unwrap();
+        let while_no = db_now(&db).await;
+        vote(&state, bees, "skip").await.unwrap();
+
+        let stats_at = |ts| {
+            let state = state.clone();
+            async move {
+                let page = Pagination {
+                    limit: None,
+                    offset: None,
+                };
+                let filter = StatsFilter {
+                    ids: None,
+                    sort: StatsSort::Yes,
+                };
+                let Json(stats) = get_stats_at(
+                    State(state),
+                    signed_in(),
+                    Query(page),
+                    Query(filter),
+                    Query(StatsAtQuery { ts }),
+                )
+                .await
+                .unwrap();
+                let bees = &stats[0];
+                (
+                    bees.yes_votes,
+                    bees.no_votes,
+                    bees.skip_votes,
+                    bees.total_votes,
+                )
+            }
+        };
+        assert_eq!(stats_at(before_any).await, (0, 0, 0, 0));
+        assert_eq!(stats_at(while_yes).await, (1, 0, 0, 1));
+        assert_eq!(stats_at(while_no).await, (0, 1, 0, 1));
+        assert_eq!(stats_at(Utc::now()).await, (0, 0, 1, 1));
+
+        let now = &all_stats(&db).await[0];
+        assert_eq!((now.skip_votes, now.total_votes), (1, 1));
+    }
+
+    #[sqlx::test]
+    async fn the_stats_diff_sees_votes_changed_in_between(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let voters: Vec<AppState> = ["first", "second", "third"]
+            .into_iter()
+            .map(|user| {
+                let config = Config {
+                    allow_revote: true,
+                    revote_cooldown: Duration::ZERO,
+                    ..Config::default()
+                };
+                test_state(db.clone(), config, user)
+            })
+            .collect();
+        vote(&voters[0], wasps, "yes").await.unwrap();
+        vote(&voters[1], wasps, "yes").await.unwrap();
+        vote(&voters[2], bees, "yes").await.unwrap();
+        let from = db_now(&db).await;
+        vote(&voters[0], wasps, "no").await.unwrap();
+        vote(&voters[1], wasps, "no").await.unwrap();
+
+        let query = StatsDiffQuery {
+            from,
+            to: None,
+            sort: StatsSort::Yes,
+        };
+        let Json(diff) =
+            get_stats_diff(State(revoting_admin_state(&db)), signed_in(), Query(query))
+                .await
+                .unwrap();
+
+        assert_eq!(diff[0].theme_id, bees);
+        assert_eq!(diff[0].previous_rank, Some(2));
+        assert_eq!(diff[0].rank_change, Some(1));
+        assert_eq!(diff[0].yes_change, Some(0));
+
+        let wasps_diff = &diff[1];
+        assert_eq!(wasps_diff.theme_id, wasps);
+        assert_eq!(wasps_diff.previous_rank, Some(1));
+        assert_eq!(wasps_diff.rank_change, Some(-1));
+        assert_eq!(wasps_diff.yes_change, Some(-2));
+        assert_eq!(wasps_diff.net_change, Some(-4));
+        // The same votes, only changed
+        assert_eq!(wasps_diff.total_change, Some(0));
+    }
+
+    #[sqlx::test]
+    async fn voting_metrics_count_recent_votes_and_voters(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        vote_as(&db, "old", bees, "yes").await;
+        sqlx::query("UPDATE votes SET updated_at = NOW() - INTERVAL '2 minutes'")
+            .execute(&db)
+            .await
+            .unwrap();
+        vote_as(&db, "first", bees, "yes").await;
+        vote_as(&db, "first", wasps, "no").await;
+        vote_as(&db, "second", wasps, "skip").await;
+
+        let metrics = voting_metrics(&db, Duration::from_secs(60)).await.unwrap();
+        assert_eq!(metrics.total_votes, 4);
+        assert_eq!(metrics.recent_votes, 3);
+        assert_eq!(metrics.active_voters, 2);
+    }
+
+    #[sqlx::test]
+    async fn the_metrics_heartbeat_keeps_going_when_a_query_fails(db: PgPool) {
+        db.close().await;
+        let heartbeat = tokio::spawn(log_metrics(db, Duration::from_millis(10)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!heartbeat.is_finished());
+        heartbeat.abort();
+    }
+
+    #[sqlx::test]
+    async fn tag_stats_are_for_admins_only(db: PgPool) {
+        let voter = test_state(db.clone(), Config::default(), VOTER);
+        assert_eq!(
+            status_of(&voter, "/admin/tags", true).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status_of(&admin_state(&db), "/admin/tags", true).await,
+            StatusCode::OK
+        );
+    }
+
+    /// The theme ids `/results/public` lists, in order.
+    async fn public_result_ids(state: &AppState) -> Vec<i64> {
+        let results = json_body(call(state, get_request("/results/public")).await).await;
+        results
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|theme| theme["theme_id"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn public_results_leave_out_themes_below_the_vote_floor(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        seed_votes(&db, bees, &["yes", "yes", "no"]).await;
+        seed_votes(&db, wasps, &["yes", "no"]).await;
+        seed_votes(&db, ants, &["yes"]).await;
+
+        let config = Config {
+            public_results_min_votes: 2,
+            ..Config::default()
+        };
+        let floored = test_state(db.clone(), config, VOTER);
+        let (bees, wasps, ants) = (bees as i64, wasps as i64, ants as i64);
+        assert_eq!(public_result_ids(&floored).await, [bees, wasps]);
+
+        let unfloored = test_state(db, Config::default(), VOTER);
+        assert_eq!(public_result_ids(&unfloored).await, [bees, wasps, ants]);
+    }
+
+    #[sqlx::test]
+    async fn coverage_summarizes_votes_per_theme(db: PgPool) {
+        let state = admin_state(&db);
+        let Json(empty) = get_coverage(State(state.clone()), signed_in())
+            .await
+            .unwrap();
+        assert_eq!((empty.themes, empty.min_votes, empty.max_votes), (0, 0, 0));
+        assert_eq!((empty.mean_votes, empty.stddev_votes), (0.0, 0.0));
+
+        add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        let deleted = add_theme(&db, "Moths").await;
+        seed_votes(&db, wasps, &["yes", "no"]).await;
+        seed_votes(&db, ants, &["yes", "no", "skip", "maybe"]).await;
+        seed_votes(&db, deleted, &["yes", "yes", "yes", "yes", "yes"]).await;
+        sqlx::query("UPDATE themes SET deleted_at = NOW() WHERE id = $1")
+            .bind(deleted)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        // 0, 2 and 4 votes; the deleted theme doesn't count
+        let Json(coverage) = get_coverage(State(state), signed_in()).await.unwrap();
+        assert_eq!(
+            (
+                coverage.themes,
+                coverage.min_votes,
+                coverage.max_votes,
+                coverage.themes_without_votes
+            ),
+            (3, 0, 4, 1)
+        );
+        assert!((coverage.mean_votes - 2.0).abs() < 1e-9);
+        assert!((coverage.stddev_votes - (8.0f64 / 3.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[sqlx::test]
+    async fn next_theme_reports_a_total_of_zero_before_any_themes_exist(db: PgPool) {
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        let empty = next_theme(&state).await;
+        assert!(empty.theme.is_none());
+        assert_eq!((empty.seen, empty.total), (0, 0));
+        assert!(!empty.closed);
+
+        // Deleted themes don't count either
+        let theme_id = add_theme(&db, "Bees").await;
+        sqlx::query("UPDATE themes SET deleted_at = NOW() WHERE id = $1")
+            .bind(theme_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let deleted = next_theme(&state).await;
+        assert!(deleted.theme.is_none());
+        assert_eq!((deleted.seen, deleted.total), (0, 0));
+    }
+
+    async fn rank_of(db: &PgPool, user_id: &str) -> VoterRank {
+        let state = test_state(db.clone(), Config::default(), user_id);
+        let Json(rank) = get_my_rank(State(state), signed_in()).await.unwrap();
+        rank
+    }
+
+    #[sqlx::test]
+    async fn a_lone_voter_is_ahead_of_nobody(db: PgPool) {
+        let nobody = rank_of(&db, VOTER).await;
+        assert_eq!((nobody.votes, nobody.voters), (0, 0));
+        assert_eq!(nobody.ahead_of_percent, 0.0);
+
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        vote_as(&db, VOTER, bees, "yes").await;
+        vote_as(&db, VOTER, wasps, "no").await;
+        let alone = rank_of(&db, VOTER).await;
+        assert_eq!((alone.votes, alone.voters), (2, 1));
+        assert_eq!(alone.ahead_of_percent, 0.0);
+    }
+
+    #[sqlx::test]
+    async fn voters_tied_on_votes_are_not_ahead_of_each_other(db: PgPool) {
+        let themes = [
+            add_theme(&db, "Bees").await,
+            add_theme(&db, "Wasps").await,
+            add_theme(&db, "Ants").await,
+        ];
+        for (user, votes) in [(VOTER, 2), ("tied", 2), ("behind", 1), ("ahead", 3)] {
+            for &theme_id in &themes[..votes] {
+                vote_as(&db, user, theme_id, "yes").await;
+            }
+        }
+
+        let mine = rank_of(&db, VOTER).await;
+        assert_eq!((mine.votes, mine.voters), (2, 4));
+        // Only "behind" of the three others
+        assert!((mine.ahead_of_percent - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(rank_of(&db, "ahead").await.ahead_of_percent, 100.0);
+        assert_eq!(rank_of(&db, "behind").await.ahead_of_percent, 0.0);
+    }
+
+    #[sqlx::test]
+    async fn recent_votes_page_through_identical_timestamps_without_gaps(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        // One statement, so the first seven votes share NOW()
+        sqlx::query(
+            "INSERT INTO votes (user_id, theme_id, vote_type, created_at)
+             SELECT 'voter ' || i, $1, 'yes',
+                    CASE WHEN i <= 7 THEN NOW() ELSE NOW() - INTERVAL '1 hour' END
+             FROM generate_series(1, 9) i",
+        )
+        .bind(theme_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let state = admin_state(&db);
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let query = RecentVotesQuery {
+                limit: Some(3),
+                before: cursor.map(|(at, _)| at),
+                before_id: cursor.map(|(_, id)| id),
+            };
+            let Json(page) = get_recent_votes(State(state.clone()), signed_in(), Query(query))
+                .await
+                .unwrap();
+            let Some(last) = page.last() else { break };
+            cursor = Some((last.voted_at, last.id));
+            seen.extend(page.iter().map(|vote| vote.id));
+        }
+
+        let expected: Vec<i32> =
+            sqlx::query_scalar("SELECT id FROM votes ORDER BY created_at DESC, id DESC")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(expected.len(), 9);
+        // Every vote once, newest first
+        assert_eq!(seen, expected);
+    }
+
+    #[sqlx::test]
+    async fn maybe_votes_count_apart_unless_turned_off(db: PgPool) {
+        let theme_id = add_theme(&db, "Bees").await;
+        let state = test_state(db.clone(), Config::default(), VOTER);
+        vote(&state, theme_id, "maybe").await.unwrap();
+        vote_as(&db, "other", theme_id, "yes").await;
+        let stats = &all_stats(&db).await[0];
+        assert_eq!(
+            (stats.yes_votes, stats.maybe_votes, stats.total_votes),
+            (1, 1, 2)
+        );
+        let config = json_body(call(&state, get_request("/config")).await).await;
+        assert_eq!(
+            config["vote_types"],
+            serde_json::json!(["yes", "no", "skip", "maybe"])
+        );
+
+        let config = Config {
+            allow_maybe: false,
+            ..Config::default()
+        };
+        let no_maybe = test_state(db.clone(), config, "third");
+        assert!(matches!(
+            vote(&no_maybe, theme_id, "maybe").await,
+            Err(AppError::BadRequest(_))
+        ));
+        vote(&no_maybe, theme_id, "no").await.unwrap();
+        let config = json_body(call(&no_maybe, get_request("/config")).await).await;
+        assert_eq!(
+            config["vote_types"],
+            serde_json::json!(["yes", "no", "skip"])
+        );
+    }
+
+    #[sqlx::test]
+    async fn theme_descriptions_are_cleaned_up_and_served(db: PgPool) {
+        let state = admin_state(&db);
+        let create = |content: &str, description: &str| {
+            let request = CreateThemeRequest {
+                content: content.to_string(),
+                description: Some(description.to_string()),
+            };
+            create_theme(State(state.clone()), signed_in(), Json(request))
+        };
+
+        let (_, Json(bees)) = create("Bees", "  Buzzing\n  all   day ").await.unwrap();
+        assert_eq!(bees.description.as_deref(), Some("Buzzing all day"));
+        let (_, Json(wasps)) = create("Wasps", "   ").await.unwrap();
+        assert_eq!(wasps.description, None);
+        let too_long = "a".repeat(theme_content::MAX_DESCRIPTION_LENGTH + 1);
+        assert!(matches!(
+            create("Ants", &too_long).await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        let served =
+            json_body(call(&state, get_request(&format!("/themes/{}", bees.id))).await).await;
+        assert_eq!(served["description"], "Buzzing all day");
+        // Left out rather than null when there is none
+        let served =
+            json_body(call(&state, get_request(&format!("/themes/{}", wasps.id))).await).await;
+        assert!(served.get("description").is_none(), "{served}");
+
+        vote(&state, wasps.id, "yes").await.unwrap();
+        let next = next_theme(&state).await.theme.unwrap();
+        assert_eq!(
+            (next.id, next.description.as_deref()),
+            (bees.id, Some("Buzzing all day"))
+        );
+    }
+
+    #[sqlx::test]
+    async fn stats_sort_by_net_score_then_yes_votes(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        let moths = add_theme(&db, "Moths").await;
+        let flies = add_theme(&db, "Flies").await;
+        seed_votes(&db, bees, &["yes", "yes", "yes", "no", "no", "no"]).await;
+        seed_votes(&db, wasps, &["yes", "yes"]).await;
+        seed_votes(&db, ants, &["yes", "no", "no"]).await;
+        seed_votes(&db, moths, &["yes", "skip"]).await;
+        seed_votes(&db, flies, &["yes", "yes", "yes", "no"]).await;
+        let state = test_state(db, Config::default(), VOTER);
+
+        let order = |uri: &'static str| {
+            let state = state.clone();
+            async move {
+                let stats = json_body(call(&state, get_request(uri)).await).await;
+                stats
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|theme| theme["theme_id"].as_i64().unwrap() as i32)
+                    .collect::<Vec<_>>()
+            }
+        };
+        // Flies and Wasps tie on +2, so the one with more yes votes comes first
+        assert_eq!(
+            order("/admin/stats?sort=net").await,
+            [flies, wasps, moths, bees, ants]
+        );
+        assert_eq!(
+            order("/admin/stats").await,
+            [bees, flies, wasps, ants, moths]
+        );
+    }
+
+    /// Votes as `user_id` from a client reporting `version`.
+    async fn vote_from_client(db: &PgPool, user_id: &str, theme_id: i32, version: &str) {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", HeaderValue::from_static("Bearer test"));
+        headers.insert("X-Client-Version", HeaderValue::from_str(version).unwrap());
+        let request = VoteRequest {
+            theme_id,
+            vote_type: "yes".to_string(),
+            reason: None,
+        };
+        let state = test_state(db.clone(), Config::default(), user_id);
+        submit_vote(State(state), headers.into(), JsonOrForm(request))
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn client_versions_are_recorded_and_counted(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        vote_from_client(&db, "first", bees, "0.1.0").await;
+        vote_from_client(&db, "first", wasps, " 0.1.0 ").await;
+        // Blank or overlong versions are stored as unknown, never rejected
+        vote_from_client(&db, "second", bees, "  ").await;
+        vote_from_client(
+            &db,
+            "third",
+            bees,
+            &"9".repeat(MAX_CLIENT_VERSION_LENGTH + 1),
+        )
+        .await;
+        vote_from_client(&db, "fourth", bees, "0.2.0").await;
+
+        let Json(versions) = get_client_versions(State(admin_state(&db)), signed_in())
+            .await
+            .unwrap();
+        let versions: Vec<_> = versions
+            .into_iter()
+            .map(|v| (v.client_version, v.votes, v.voters))
+            .collect();
+        // Most recently seen first
+        assert_eq!(
+            versions,
+            [
+                (Some("0.2.0".to_string()), 1, 1),
+                (None, 2, 2),
+                (Some("0.1.0".to_string()), 2, 1),
+            ]
+        );
+    }
+
+    /// The pair `/themes/pair` serves `state`'s user, lowest id first.
+    async fn pair_for(state: &AppState) -> Option<(i32, i32)> {
+        let Json(response) = get_theme_pair(State(state.clone()), signed_in())
+            .await
+            .unwrap();
+        let [a, b] = response.pair?;
+        Some((a.id.min(b.id), a.id.max(b.id)))
+    }
+
+    async fn pick(db: &PgPool, user_id: &str, winner_id: i32, loser_id: i32) {
+        let state = test_state(db.clone(), Config::default(), user_id);
+        let request = PairVoteRequest {
+            winner_id,
+            loser_id,
+        };
+        submit_pair_vote(State(state), signed_in(), Json(request))
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn a_user_is_never_served_the_same_pair_twice(db: PgPool) {
+        for theme in ["Bees", "Wasps", "Ants", "Moths"] {
+            add_theme(&db, theme).await;
+        }
+        let state = test_state(db.clone(), Config::default(), VOTER);
+
+        let mut served = Vec::new();
+        while let Some((a, b)) = pair_for(&state).await {
+            assert!(!served.contains(&(a, b)), "({a}, {b}) was served again");
+            served.push((a, b));
+            pick(&db, VOTER, b, a).await;
+        }
+        // Every pair of the four, then nothing left
+        assert_eq!(served.len(), 6);
+    }
+
+    #[sqlx::test]
+    async fn win_rates_are_the_share_of_picks_won(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        let moths = add_theme(&db, "Moths").await;
+        pick(&db, "first", bees, wasps).await;
+        pick(&db, "second", bees, ants).await;
+        pick(&db, "third", wasps, bees).await;
+
+        let ranking: Vec<_> = pairwise_ranking(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.theme_id, r.wins, r.losses, r.win_rate))
+            .collect();
+        let (theme_id, wins, losses, win_rate) = ranking[0];
+        assert_eq!((theme_id, wins, losses), (bees, 2, 1));
+        assert!((win_rate.unwrap() - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            ranking[1..],
+            [
+                (wasps, 1, 1, Some(50.0)),
+                (ants, 0, 1, Some(0.0)),
+                // Never compared: no rate rather than 0%
+                (moths, 0, 0, None),
+            ]
+        );
+    }
+
+    #[sqlx::test]
+    async fn merging_folds_head_to_head_picks_into_the_kept_theme(db: PgPool) {
+        let keep = add_theme(&db, "Bees").await;
+        let merged = add_theme(&db, "Bees!").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        pick(&db, "first", merged, wasps).await;
+        // Picked both copies against Ants, the merged one last
+        pick(&db, "second", keep, ants).await;
+        pick(&db, "second", ants, merged).await;
+        // A pick between the two copies has nothing left to compare
+        pick(&db, "third", keep, merged).await;
+
+        let request = MergeThemesRequest {
+            keep_id: keep,
+            merge_ids: vec![merged],
+        };
+        merge_themes(State(admin_state(&db)), signed_in(), Json(request))
+            .await
+            .unwrap();
+
+        let ranking: Vec<_> = pairwise_ranking(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.theme_id, r.wins, r.losses))
+            .collect();
+        assert_eq!(ranking, [(ants, 1, 0), (keep, 1, 1), (wasps, 0, 1)]);
+        let picks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pairwise_votes")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(picks, 2);
+    }
+
+    /// Each theme's Elo rating, rounded to two decimals, by theme id.
+    async fn elo_ratings(db: &PgPool) -> HashMap<i32, f64> {
+        pairwise_ranking(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.theme_id, (r.elo * 100.0).round() / 100.0))
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn elo_ratings_replay_picks_in_the_order_they_were_made(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        let moths = add_theme(&db, "Moths").await;
+        pick(&db, "first", bees, wasps).await;
+        // An upset: beating the higher-rated Bees is worth more than the 16 an even
+        // match gives
+        pick(&db, "second", ants, bees).await;
+
+        let elo = elo_ratings(&db).await;
+        assert_eq!(elo[&bees], 1499.26);
+        assert_eq!(elo[&wasps], 1484.0);
+        assert_eq!(elo[&ants], 1516.74);
+        assert_eq!(elo[&moths], ELO_START);
+
+        // The same picks the other way round end up elsewhere
+        sqlx::query(
+            "UPDATE pairwise_votes SET created_at = NOW() - INTERVAL '1 hour'
+             WHERE user_id = 'second'",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+        let elo = elo_ratings(&db).await;
+        assert_eq!(elo[&bees], 1500.74);
+        assert_eq!(elo[&wasps], 1483.26);
+        assert_eq!(elo[&ants], 1516.0);
+    }
+
+    #[sqlx::test]
+    async fn under_compared_themes_are_reported_and_served_first(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        let ants = add_theme(&db, "Ants").await;
+        let moths = add_theme(&db, "Moths").await;
+        pick(&db, "first", bees, wasps).await;
+        pick(&db, "second", bees, ants).await;
+        let config = Config {
+            admin_user_ids: vec![ADMIN.to_string()],
+            min_pairwise_comparisons: 2,
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, ADMIN);
+
+        let Json(coverage) = get_pairwise_coverage(State(state.clone()), signed_in())
+            .await
+            .unwrap();
+        assert!(!coverage.ready);
+        assert_eq!(coverage.below_minimum, 3);
+        let counts: Vec<_> = coverage
+            .themes
+            .iter()
+            .map(|t| (t.theme_id, t.comparisons, t.below_minimum))
+            .collect();
+        // Least compared first
+        assert_eq!(
+            counts,
+            [
+                (moths, 0, true),
+                (wasps, 1, true),
+                (ants, 1, true),
+                (bees, 2, false),
+            ]
+        );
+
+        // Moths has never been compared, so it's in every new voter's first pair
+        for user in ["third", "fourth", "fifth"] {
+            let voter = test_state(db.clone(), state.config.as_ref().clone(), user);
+            let (a, b) = pair_for(&voter).await.unwrap();
+            assert!(a == moths || b == moths, "({a}, {b})");
+        }
+
+        pick(&db, "third", moths, wasps).await;
+        pick(&db, "fourth", ants, moths).await;
+        let Json(coverage) = get_pairwise_coverage(State(state), signed_in())
+            .await
+            .unwrap();
+        assert!(coverage.ready);
+        assert_eq!(coverage.below_minimum, 0);
+    }
+
+    #[sqlx::test]
+    async fn weighted_stats_count_each_vote_by_its_voters_trust(db: PgPool) {
+        let bees = add_theme(&db, "Bees").await;
+        let wasps = add_theme(&db, "Wasps").await;
+        seed_votes(&db, bees, &["yes", "yes", "no"]).await;
+        seed_votes(&db, wasps, &["skip", "yes", "yes"]).await;
+        let state = admin_state(&db);
+        let set_trust = |user_id: &str, trust_weight: f64| {
+            set_user_trust(
+                State(state.clone()),
+                signed_in(),
+                Path(user_id.to_string()),
+                Json(SetTrustRequest { trust_weight }),
+            )
+        };
+        set_trust("voter 0", 2.5).await.unwrap();
+        set_trust("voter 1", 0.0).await.unwrap();
+        assert!(matches!(
+            set_trust("voter 2", MAX_TRUST_WEIGHT + 1.0).await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        let page = Pagination {
+            limit: None,
+            offset: None,
+        };
+        let Json(stats) = get_weighted_stats(State(state), signed_in(), Query(page))
+            .await
+            .unwrap();
+        let weights: Vec<_> = stats
+            .iter()
+            .map(|s| {
+                (
+                    s.theme_id,
+                    s.yes_weight,
+                    s.no_weight,
+                    s.skip_weight,
+                    s.net_weight,
+                    s.total_weight,
+                    s.total_votes,
+                )
+            })
+            .collect();
+        // Voter 2 keeps the default weight of 1
+        assert_eq!(
+            weights,
+            [
+                (bees, 2.5, 1.0, 0.0, 1.5, 3.5, 3),
+                (wasps, 1.0, 0.0, 2.5, 1.0, 3.5, 3),
+            ]
+        );
+    }
+
+    #[sqlx::test]
+    async fn seen_can_leave_out_skipped_themes(db: PgPool) {
+        let config = Config {
+            progress_excludes_skips: true,
+            ..Config::default()
+        };
+        let state = test_state(db.clone(), config, VOTER);
+        let mut themes = Vec::new();
+        for content in ["Bees", "Wasps", "Ants", "Moths"] {
+            themes.push(add_theme(&db, content).await);
+        }
+        for (theme_id, vote_type) in themes.iter().zip(["yes", "no", "skip"]) {
+            vote(&state, *theme_id, vote_type).await.unwrap();
+        }
+
+        let by_default = progress(&state, None).await;
+        assert_eq!((by_default.seen, by_default.percent), (2, 50.0));
+        assert!(by_default.seen_excludes_skips);
+        // A request can still ask for skips to count
+        let with_skips = progress(&state, Some(false)).await;
+        assert_eq!((with_skips.seen, with_skips.percent), (3, 75.0));
+        assert!(!with_skips.seen_excludes_skips);
+
+        // The skipped theme isn't served again for all that
+        let next = next_theme(&state).await;
+        assert_eq!(next.theme.unwrap().id, themes[3]);
+        assert_eq!((next.seen, next.total), (2, 4));
+        assert!(next.seen_excludes_skips);
+    }
+
+    #[sqlx::test]
+    async fn auth_me_names_the_signed_in_user(db: PgPool) {
+        let state = test_state(db, Config::default(), VOTER);
+        let response = call(&state, signed_in_request("/auth/me")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            json_body(response).await,
+            serde_json::json!({ "user_id": VOTER })
+        );
     }
 }
@@ -4,8 +4,11 @@ use serde::{Deserialize, Serialize};
 pub struct Theme {
     pub id: i32,
     pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Vote {
     pub id: i32,
@@ -18,14 +21,232 @@ pub struct Vote {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VoteRequest {
     pub theme_id: i32,
-    pub vote_type: String, // "yes", "no", "skip"
+    pub vote_type: String, // "yes", "no", "skip", "maybe"
+    /// Only accepted with a "skip" vote
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThemeListQuery {
+    /// Case-insensitive substring to match against theme content
+    pub search: Option<String>,
+    /// Comma-separated tags to filter on
+    pub tags: Option<String>,
+    #[serde(default, rename = "match")]
+    pub tag_match: TagMatch,
+}
+
+/// Whether a `tags` filter needs themes to carry any or all of the listed tags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+/// How `/themes/next` picks among the themes a user hasn't voted on yet.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Uniformly random
+    #[default]
+    Random,
+    /// Random, but favoring themes with fewer votes
+    Weighted,
+}
+
+impl Strategy {
+    pub const ALL: &'static [Strategy] = &[Strategy::Random, Strategy::Weighted];
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = serde::de::value::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use serde::de::IntoDeserializer;
+        Self::deserialize(s.into_deserializer())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NextThemeQuery {
+    /// Falls back to the server's configured default
+    pub strategy: Option<Strategy>,
+    /// Comma-separated tags to filter on
+    pub tags: Option<String>,
+    #[serde(default, rename = "match")]
+    pub tag_match: TagMatch,
+    /// Falls back to the server's configured default
+    pub exclude_skips: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProgressQuery {
+    /// Falls back to the server's configured default
+    pub exclude_skips: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateThemeRequest {
+    pub content: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateThemeRequest {
+    pub content: String,
+}
+
+/// Non-sensitive settings clients use to adapt their UI.
+#[derive(Debug, Serialize)]
+pub struct PublicConfig {
+    pub vote_types: &'static [&'static str],
+    pub voting_open: bool,
+    pub voting_opens_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub voting_closes_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub min_theme_length: usize,
+    pub max_theme_length: usize,
+    pub page_size: usize,
+    /// Themes with fewer votes are left out of `/results/public`
+    pub public_results_min_votes: i64,
+    pub allow_revote: bool,
+    /// Results endpoints refuse non-admins until voting closes
+    pub results_hidden: bool,
+    /// Strategies `/themes/next` accepts, and the one used when none is given
+    pub strategies: &'static [Strategy],
+    pub default_strategy: Strategy,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThemeCount {
+    pub total: i64,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ThemeResponse {
     pub theme: Option<Theme>,
+    /// Active themes overall; 0 means none have been added yet, rather than all voted on.
     pub total: i64,
+    /// Active themes the user has voted on
+    pub seen: i64,
+    /// True when `seen` leaves out themes the user skipped
+    pub seen_excludes_skips: bool,
+    /// True when the voting window has closed; `theme` is then always `None`.
+    pub closed: bool,
+}
+
+/// Two themes to pick between, in random order. `pair` is `None` once the caller has
+/// compared every pair.
+#[derive(Debug, Serialize)]
+pub struct PairResponse {
+    pub pair: Option<[Theme; 2]>,
+    /// True when the voting window has closed; `pair` is then always `None`.
+    pub closed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairVoteRequest {
+    pub winner_id: i32,
+    pub loser_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairwiseRanking {
+    pub theme_id: i32,
+    pub content: String,
+    pub wins: i64,
+    pub losses: i64,
+    /// Share of its head-to-head picks the theme won, 0-100; null if it was never in a pair
+    pub win_rate: Option<f64>,
+    /// Starts at 1500 and moves with every pick, replayed oldest first
+    pub elo: f64,
+}
+
+/// How many head-to-head picks each active theme has, fewest first.
+#[derive(Debug, Serialize)]
+pub struct PairwiseCoverage {
+    pub min_comparisons: i64,
+    /// True once every theme has at least `min_comparisons` picks
+    pub ready: bool,
+    pub below_minimum: i64,
+    pub themes: Vec<PairwiseComparisons>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairwiseComparisons {
+    pub theme_id: i32,
+    pub content: String,
+    pub comparisons: i64,
+    pub below_minimum: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairwiseRankingQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MyVote {
+    pub theme_id: i32,
+    pub content: String,
+    pub vote_type: String,
+    pub voted_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Everything stored about one voter, for `GET /me/export`.
+#[derive(Debug, Serialize)]
+pub struct MyDataExport {
+    pub user_id: String,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub votes: Vec<MyExportedVote>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MyExportedVote {
+    pub theme_id: i32,
+    pub content: String,
+    pub vote_type: String,
+    pub skip_reason: Option<String>,
+    pub voted_at: chrono::DateTime<chrono::Utc>,
+    /// Set when the theme has since been removed from voting
+    pub theme_deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VoterRank {
+    pub votes: i64,
+    /// Everyone who has voted at least once, including the caller
+    pub voters: i64,
+    /// Share of the other voters who have cast fewer votes than the caller, 0-100
+    pub ahead_of_percent: f64,
+}
+
+/// Who the caller is signed in as.
+#[derive(Debug, Serialize)]
+pub struct CurrentUser {
+    pub user_id: String,
+}
+
+/// Whether the caller may vote right now, so clients can say why not before a vote
+/// gets rejected.
+#[derive(Debug, Serialize)]
+pub struct Eligibility {
+    pub can_vote: bool,
+    pub reason: Option<String>,
+    /// `None` when there is no daily limit
+    pub votes_remaining_today: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressResponse {
     pub seen: i64,
+    pub total: i64,
+    pub percent: f64,
+    /// True when `seen` leaves out themes the user skipped
+    pub seen_excludes_skips: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -35,13 +256,321 @@ pub struct VoteStats {
     pub yes_votes: i64,
     pub no_votes: i64,
     pub skip_votes: i64,
+    pub maybe_votes: i64,
+    pub total_votes: i64,
+    /// `yes_votes - no_votes`; skips and maybes don't count either way
+    pub net_score: i64,
+    /// Distinct users the theme was served to (or who voted on it)
+    pub seen_count: i64,
+    /// `total_votes / seen_count`, 0 when nobody has seen it yet
+    pub participation_rate: f64,
+}
+
+/// Like [`VoteStats`], but each vote counts for its voter's trust weight.
+#[derive(Debug, Serialize)]
+pub struct WeightedVoteStats {
+    pub theme_id: i32,
+    pub content: String,
+    pub yes_weight: f64,
+    pub no_weight: f64,
+    pub skip_weight: f64,
+    pub maybe_weight: f64,
+    pub total_weight: f64,
+    /// `yes_weight - no_weight`
+    pub net_weight: f64,
+    /// Unweighted vote count
     pub total_votes: i64,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetTrustRequest {
+    pub trust_weight: f64,
+}
+
+/// Optional paging for list endpoints; everything is returned when omitted.
+#[derive(Debug, Deserialize)]
+pub struct Pagination {
+    #[serde(default, deserialize_with = "non_negative")]
+    pub limit: Option<i64>,
+    #[serde(default, deserialize_with = "non_negative")]
+    pub offset: Option<i64>,
+}
+
+/// Turns away negative counts while parsing the query, so they get a 400 rather than
+/// reaching the database as an invalid LIMIT or OFFSET.
+fn non_negative<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Option::<i64>::deserialize(deserializer)? {
+        Some(n) if n < 0 => Err(serde::de::Error::custom(format!(
+            "expected a non-negative number, got {n}"
+        ))),
+        n => Ok(n),
+    }
+}
+
+/// Restricts stats to a comma-separated list of theme ids, e.g. `ids=4,1,7`,
+/// and picks their order.
+#[derive(Debug, Deserialize)]
+pub struct StatsFilter {
+    pub ids: Option<String>,
+    #[serde(default)]
+    pub sort: StatsSort,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsAtQuery {
+    pub ts: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsDiffQuery {
+    pub from: chrono::DateTime<chrono::Utc>,
+    /// Defaults to now
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub sort: StatsSort,
+}
+
+/// How a theme moved between two points in time. Ranks are 1-based positions in the
+/// chosen sort; `previous_rank` and the changes are null for themes added since `from`.
+#[derive(Debug, Serialize)]
+pub struct StatsDiff {
+    pub theme_id: i32,
+    pub content: String,
+    pub rank: i64,
+    pub previous_rank: Option<i64>,
+    /// Places climbed since `from`; negative when the theme fell
+    pub rank_change: Option<i64>,
+    pub yes_votes: i64,
+    pub yes_change: Option<i64>,
+    pub net_score: i64,
+    pub net_change: Option<i64>,
+    pub total_votes: i64,
+    pub total_change: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsSort {
+    /// Most yes votes first
+    #[default]
+    Yes,
+    /// Highest `yes - no` first
+    Net,
+}
+
+/// How the admin exports are sent, CSV unless `format=json` is asked for.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardEntry {
+    /// Themes with the same number of yes votes share a rank
+    pub rank: i64,
+    pub theme_id: i32,
+    pub content: String,
+    pub yes_votes: i64,
+    pub no_votes: i64,
+    pub total_votes: i64,
+    /// Share of yes among yes/no votes (skips ignored), 0-100
+    pub yes_percent: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    pub a: i32,
+    pub b: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThemeTally {
+    pub theme_id: i32,
+    pub content: String,
+    pub yes_votes: i64,
+    pub no_votes: i64,
+    pub skip_votes: i64,
+    pub maybe_votes: i64,
+    pub total_votes: i64,
+}
+
+/// Two themes side by side, plus how users who voted on both split between them.
+/// A "yes" beats a "maybe", which beats a "no". Voters who skipped either theme have no
+/// opinion between them, and are only counted in `no_opinion`.
+#[derive(Debug, Serialize)]
+pub struct CompareResponse {
+    pub a: ThemeTally,
+    pub b: ThemeTally,
+    pub voted_both: i64,
+    pub preferred_a: i64,
+    pub preferred_b: i64,
+    pub same: i64,
+    pub no_opinion: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkipReasonCount {
+    pub theme_id: i32,
+    pub content: String,
+    pub reason: String,
+    pub count: i64,
+}
+
+/// Votes last set by one client version; `client_version` is null for clients that don't
+/// send one.
+#[derive(Debug, Serialize)]
+pub struct ClientVersionCount {
+    pub client_version: Option<String>,
+    pub votes: i64,
+    pub voters: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetVotesQuery {
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResetVotesResponse {
+    pub theme_id: i32,
+    pub deleted: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeThemesRequest {
+    pub keep_id: i32,
+    pub merge_ids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeThemesResponse {
+    pub theme_id: i32,
+    pub merged_ids: Vec<i32>,
+    /// Votes on the kept theme once the merge is done
+    pub total_votes: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddTagRequest {
+    pub tag: String,
+}
+
+/// How many themes under a tag the caller hasn't voted on; `tag` is null for untagged themes.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RemainingByTag {
+    pub tag: Option<String>,
+    pub remaining: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagStats {
+    pub tag: String,
+    pub theme_count: i64,
+    pub yes_votes: i64,
+    pub no_votes: i64,
+    pub total_votes: i64,
+}
+
+/// Headline numbers across all active themes.
+#[derive(Debug, Serialize)]
+pub struct VoteSummary {
+    pub total_votes: i64,
+    pub yes: i64,
+    pub no: i64,
+    pub skip: i64,
+    pub maybe: i64,
+    pub themes: i64,
+    /// Distinct users who have cast at least one vote
+    pub voters: i64,
+}
+
+/// How evenly votes are spread across active themes.
+#[derive(Debug, Serialize)]
+pub struct Coverage {
+    pub themes: i64,
+    pub min_votes: i64,
+    pub max_votes: i64,
+    pub mean_votes: f64,
+    /// Population standard deviation of votes per theme
+    pub stddev_votes: f64,
+    pub themes_without_votes: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetFeaturedRequest {
+    pub theme_id: i32,
+    /// Defaults to today (UTC)
+    pub date: Option<chrono::NaiveDate>,
+}
+
+/// The vote counts are `None` while results are hidden from the caller.
+#[derive(Debug, Serialize)]
+pub struct FeaturedTheme {
+    pub date: chrono::NaiveDate,
+    pub theme_id: i32,
+    pub content: String,
+    pub yes_votes: Option<i64>,
+    pub no_votes: Option<i64>,
+    pub skip_votes: Option<i64>,
+    pub maybe_votes: Option<i64>,
+    pub total_votes: Option<i64>,
+}
+
+impl FeaturedTheme {
+    pub fn hide_votes(&mut self) {
+        self.yes_votes = None;
+        self.no_votes = None;
+        self.skip_votes = None;
+        self.maybe_votes = None;
+        self.total_votes = None;
+    }
+}
+
+/// Keyset paging for `/admin/recent` and `/admin/export`: pass the `voted_at` and `id` of the last vote
+/// seen as `before` and `before_id` to get the next (older) page.
+#[derive(Debug, Deserialize)]
+pub struct RecentVotesQuery {
+    #[serde(default, deserialize_with = "non_negative")]
+    pub limit: Option<i64>,
+    pub before: Option<chrono::DateTime<chrono::Utc>>,
+    pub before_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentVote {
+    pub id: i32,
+    /// Anonymized like exports when an export salt is set
+    pub user_id: String,
+    pub theme_id: i32,
+    pub theme_content: String,
+    pub vote_type: String,
+    pub voted_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ExportVote {
+    pub id: i32,
     pub user_id: String,
     pub theme_id: i32,
     pub theme_content: String,
     pub vote_type: String,
+    pub voted_at: chrono::DateTime<chrono::Utc>,
 }
@@ -0,0 +1,123 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Fixed-window request counter keyed by client (IP address, user id...).
+pub struct RateLimiter<K> {
+    limit: u32,
+    window: Duration,
+    hits: Mutex<HashMap<K, (Instant, u32)>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a request from `key`; it is allowed until the key goes over the limit for the
+    /// current window.
+    pub fn check(&self, key: K) -> Usage {
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap();
+
+        // Keep memory bounded by forgetting clients whose window has passed
+        if hits.len() > 10_000 {
+            hits.retain(|_, (started, _)| now.duration_since(*started) < self.window);
+        }
+
+        let (started, count) = hits.entry(key).or_insert((now, 0));
+        if now.duration_since(*started) >= self.window {
+            *started = now;
+            *count = 0;
+        }
+        *count += 1;
+        Usage {
+            allowed: *count <= self.limit,
+            limit: self.limit,
+            remaining: self.limit.saturating_sub(*count),
+            reset: self.window.saturating_sub(now.duration_since(*started)),
+        }
+    }
+
+    /// Where `key` stands, without counting a request.
+    pub fn usage(&self, key: &K) -> Usage {
+        let now = Instant::now();
+        let hits = self.hits.lock().unwrap();
+        let (count, reset) = match hits.get(key) {
+            Some((started, count)) if now.duration_since(*started) < self.window => {
+                (*count, self.window - now.duration_since(*started))
+            }
+            _ => (0, self.window),
+        };
+        Usage {
+            allowed: count <= self.limit,
+            limit: self.limit,
+            remaining: self.limit.saturating_sub(count),
+            reset,
+        }
+    }
+}
+
+/// Where a client stands after a request, as reported in the `X-RateLimit-*` headers.
+pub struct Usage {
+    pub allowed: bool,
+    pub limit: u32,
+    /// Requests left in the current window
+    pub remaining: u32,
+    /// Time until the window starts over
+    pub reset: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn requests_are_allowed_until_the_window_fills() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+        for remaining in [2, 1, 0] {
+            let usage = limiter.check("client");
+            assert!(usage.allowed);
+            assert_eq!((usage.limit, usage.remaining), (3, remaining));
+            assert!(usage.reset <= Duration::from_secs(60));
+        }
+
+        let usage = limiter.check("client");
+        assert!(!usage.allowed);
+        assert_eq!(usage.remaining, 0);
+        assert!(!limiter.usage(&"client").allowed);
+        // Every client has its own window
+        assert!(limiter.check("other").allowed);
+    }
+
+    #[test]
+    fn the_count_starts_over_once_the_window_passes() {
+        let window = Duration::from_millis(50);
+        let limiter = RateLimiter::new(1, window);
+        assert!(limiter.check("client").allowed);
+        assert!(!limiter.check("client").allowed);
+
+        thread::sleep(window);
+        assert_eq!(limiter.usage(&"client").remaining, 1);
+        let usage = limiter.check("client");
+        assert!(usage.allowed);
+        assert_eq!(usage.remaining, 0);
+    }
+
+    #[test]
+    fn usage_does_not_count_a_request() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        assert_eq!(limiter.usage(&"client").remaining, 2);
+        limiter.check("client");
+        assert_eq!(limiter.usage(&"client").remaining, 1);
+        assert_eq!(limiter.usage(&"client").remaining, 1);
+    }
+}
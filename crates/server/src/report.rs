@@ -0,0 +1,219 @@
+use crate::models::VoteStats;
+use chrono::{DateTime, Utc};
+
+/// A standalone HTML page of the results, for organizers who just want to open a file.
+/// Clicking a column header sorts the table; the bars show each theme's vote split.
+pub fn render(stats: &[VoteStats], generated_at: DateTime<Utc>) -> String {
+    let most_votes = stats
+        .iter()
+        .map(|s| s.total_votes)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let total_votes: i64 = stats.iter().map(|s| s.total_votes).sum();
+
+    let mut rows = String::new();
+    for (rank, s) in stats.iter().enumerate() {
+        // Bar length is relative to the most-voted theme, split by vote type
+        let width = |votes: i64| votes as f64 * 100.0 / most_votes as f64;
+        rows.push_str(&format!(
+            r#"
+                <tr>
+                    <td data-value="{rank}">{rank}</td>
+                    <td>{content}</td>
+                    <td data-value="{yes}">{yes}</td>
+                    <td data-value="{no}">{no}</td>
+                    <td data-value="{skip}">{skip}</td>
+                    <td data-value="{maybe}">{maybe}</td>
+                    <td data-value="{total}">{total}</td>
+                    <td data-value="{yes}" class="bar">
+                        <span class="yes" style="width: {yes_width:.1}%"></span><span class="maybe" style="width: {maybe_width:.1}%"></span><span class="no" style="width: {no_width:.1}%"></span><span class="skip" style="width: {skip_width:.1}%"></span>
+                    </td>
+                </tr>"#,
+            rank = rank + 1,
+            content = escape(&s.content),
+            yes = s.yes_votes,
+            no = s.no_votes,
+            skip = s.skip_votes,
+            maybe = s.maybe_votes,
+            total = s.total_votes,
+            yes_width = width(s.yes_votes),
+            no_width = width(s.no_votes),
+            skip_width = width(s.skip_votes),
+            maybe_width = width(s.maybe_votes),
+        ));
+    }
+    if stats.is_empty() {
+        rows.push_str(
+            r#"<tr><td colspan="8" class="empty">No themes have been added yet.</td></tr>"#,
+        );
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Slaughter Vote Results</title>
+    <style>
+        body {{
+            font-family: Arial, sans-serif;
+            margin: 0;
+            padding: 2rem;
+            background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+            min-height: 100vh;
+            box-sizing: border-box;
+        }}
+        .container {{
+            background: white;
+            padding: 2rem 3rem;
+            border-radius: 1rem;
+            box-shadow: 0 10px 40px rgba(0,0,0,0.3);
+            max-width: 1000px;
+            margin: 0 auto;
+        }}
+        .meta {{ color: #666; }}
+        table {{ border-collapse: collapse; width: 100%; margin-top: 1.5rem; }}
+        th, td {{ padding: 0.5rem; border-bottom: 1px solid #eee; text-align: left; }}
+        th {{ cursor: pointer; user-select: none; color: #667eea; }}
+        th:hover {{ text-decoration: underline; }}
+        td.bar {{ width: 30%; white-space: nowrap; }}
+        .bar span {{ display: inline-block; height: 0.9rem; }}
+        .yes {{ background: #38a169; }}
+        .maybe {{ background: #ecc94b; }}
+        .no {{ background: #e53e3e; }}
+        .skip {{ background: #cbd5e0; }}
+        .empty {{ color: #999; text-align: center; }}
+        .legend span {{ display: inline-block; width: 0.8rem; height: 0.8rem; margin: 0 0.3rem 0 1rem; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>🗳️ Slaughter Vote Results</h1>
+        <p class="meta">{themes} themes, {total_votes} votes. Generated {generated}.</p>
+        <p class="meta legend"><span class="yes"></span>Yes<span class="maybe"></span>Maybe<span class="no"></span>No<span class="skip"></span>Skip</p>
+        <table>
+            <thead>
+                <tr>
+                    <th>#</th>
+                    <th>Theme</th>
+                    <th>Yes</th>
+                    <th>No</th>
+                    <th>Skip</th>
+                    <th>Maybe</th>
+                    <th>Total</th>
+                    <th>Votes</th>
+                </tr>
+            </thead>
+            <tbody>{rows}
+            </tbody>
+        </table>
+    </div>
+    <script>
+        // Sort by the clicked column; clicking it again reverses the order
+        document.querySelectorAll('th').forEach((th, column) => {{
+            th.addEventListener('click', () => {{
+                const body = document.querySelector('tbody');
+                const descending = th.dataset.order !== 'desc';
+                th.dataset.order = descending ? 'desc' : 'asc';
+                const key = row => {{
+                    const cell = row.children[column];
+                    return cell.dataset.value !== undefined ? Number(cell.dataset.value) : cell.textContent;
+                }};
+                Array.from(body.querySelectorAll('tr'))
+                    .sort((a, b) => {{
+                        const [x, y] = [key(a), key(b)];
+                        const order = typeof x === 'number' ? x - y : x.localeCompare(y);
+                        return descending ? -order : order;
+                    }})
+                    .forEach(row => body.appendChild(row));
+            }});
+        }});
+    </script>
+</body>
+</html>
+"#,
+        themes = stats.len(),
+        generated = generated_at.format("%Y-%m-%d %H:%M UTC"),
+    )
+}
+
+/// Escapes text for use inside HTML elements and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(theme_id: i32, content: &str, yes: i64, no: i64) -> VoteStats {
+        VoteStats {
+            theme_id,
+            content: content.to_string(),
+            yes_votes: yes,
+            no_votes: no,
+            skip_votes: 0,
+            maybe_votes: 0,
+            total_votes: yes + no,
+            net_score: yes - no,
+            seen_count: yes + no,
+            participation_rate: 1.0,
+        }
+    }
+
+    /// The theme cell of each row, in order.
+    fn themes(html: &str) -> Vec<&str> {
+        html.split("<tr>")
+            .skip(2)
+            .filter_map(|row| {
+                let (_, rest) = row.split_once("</td>\n                    <td>")?;
+                Some(rest.split_once("</td>")?.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rows_follow_the_order_given() {
+        let html = render(
+            &[stats(2, "Bees", 5, 1), stats(1, "Wasps", 3, 0)],
+            "2026-10-16T12:00:00Z".parse().unwrap(),
+        );
+        assert_eq!(themes(&html), ["Bees", "Wasps"]);
+        assert!(html.contains(r#"<td data-value="1">1</td>"#));
+        assert!(html.contains(r#"<td data-value="2">2</td>"#));
+        assert!(html.contains("2 themes, 9 votes. Generated 2026-10-16 12:00 UTC."));
+        assert!(!html.contains("No themes have been added yet."));
+    }
+
+    #[test]
+    fn theme_text_is_escaped() {
+        let html = render(
+            &[stats(1, r#"<script>alert("Tom & Jerry's")</script>"#, 1, 0)],
+            Utc::now(),
+        );
+        assert_eq!(
+            themes(&html),
+            ["&lt;script&gt;alert(&quot;Tom &amp; Jerry&#39;s&quot;)&lt;/script&gt;"]
+        );
+        assert!(!html.contains("<script>alert"));
+    }
+
+    #[test]
+    fn an_empty_report_says_so() {
+        let html = render(&[], Utc::now());
+        assert!(themes(&html).is_empty());
+        assert!(html.contains("No themes have been added yet."));
+    }
+}
@@ -0,0 +1,191 @@
+use clap::Parser;
+use slaughter_vote::database_url;
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use std::env;
+
+/// Users whose votes are inserted per transaction.
+const USERS_PER_BATCH: usize = 100;
+const SEED_THEME_PREFIX: &str = "Seed theme";
+const SEED_USER_PREFIX: &str = "seed-user";
+
+#[derive(Debug, Parser)]
+#[command(about = "Insert synthetic themes and votes for load testing")]
+struct Args {
+    /// Synthetic themes to make sure exist
+    #[arg(long, default_value_t = 100)]
+    themes: i64,
+
+    /// Synthetic voters
+    #[arg(long, default_value_t = 50)]
+    users: usize,
+
+    /// Votes each voter casts, on distinct random themes
+    #[arg(long, default_value_t = 20)]
+    votes_per_user: i64,
+
+    /// Wipe all themes and votes first. Only allowed against a local database,
+    /// or with SEED_ALLOW_RESET=true
+    #[arg(long)]
+    reset: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let database_url = database_url::from_env()?;
+    if args.reset && !reset_allowed(&database_url) {
+        anyhow::bail!(
+            "Refusing to --reset a non-local database; set SEED_ALLOW_RESET=true if you really mean it"
+        );
+    }
+
+    let db = PgPoolOptions::new()
+        .max_connections(5)
+        .acquire_timeout(database_url::acquire_timeout_from_env()?)
+        .connect(&database_url)
+        .await?;
+
+    let votes = seed(&db, &args).await?;
+
+    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("✓ Inserted {} votes from {} users", votes, args.users);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    Ok(())
+}
+
+/// Makes sure the synthetic themes exist and has each synthetic user vote, returning how
+/// many votes were inserted.
+async fn seed(db: &PgPool, args: &Args) -> anyhow::Result<u64> {
+    if args.reset {
+        sqlx::query("TRUNCATE themes, votes RESTART IDENTITY CASCADE")
+            .execute(db)
+            .await?;
+        println!("✓ Cleared all themes and votes");
+    }
+
+    let added = sqlx::query(&format!(
+        "INSERT INTO themes (content)
+         SELECT '{SEED_THEME_PREFIX} ' || n FROM generate_series(1, $1) n
+         WHERE NOT EXISTS (
+             SELECT 1 FROM themes WHERE content = '{SEED_THEME_PREFIX} ' || n
+         )"
+    ))
+    .bind(args.themes)
+    .execute(db)
+    .await?
+    .rows_affected();
+    println!("✓ {} themes added ({} requested)", added, args.themes);
+
+    let mut votes = 0;
+    for first in (0..args.users).step_by(USERS_PER_BATCH) {
+        let mut tx = db.begin().await?;
+        for user in first..(first + USERS_PER_BATCH).min(args.users) {
+            // Re-running tops users up rather than duplicating their votes
+            votes += sqlx::query(&format!(
+                "INSERT INTO votes (user_id, theme_id, vote_type)
+                 SELECT $1, id, (ARRAY['yes', 'no', 'skip', 'maybe'])[1 + floor(random() * 4)::INT]
+                 FROM themes
+                 WHERE content LIKE '{SEED_THEME_PREFIX} %' AND deleted_at IS NULL
+                 ORDER BY random()
+                 LIMIT $2
+                 ON CONFLICT (user_id, theme_id) DO NOTHING"
+            ))
+            .bind(format!("{SEED_USER_PREFIX}-{}", user + 1))
+            .bind(args.votes_per_user)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        println!(
+            "… {}/{} users seeded",
+            (first + USERS_PER_BATCH).min(args.users),
+            args.users
+        );
+    }
+    Ok(votes)
+}
+
+/// Resetting wipes real data, so it's limited to databases on this machine unless overridden.
+fn reset_allowed(database_url: &str) -> bool {
+    if env::var("SEED_ALLOW_RESET").is_ok_and(|value| value.trim() == "true") {
+        return true;
+    }
+    let rest = database_url
+        .split_once("://")
+        .map_or(database_url, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host_port)| host_port);
+    let host = match host_port.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(extra: &[&str]) -> Args {
+        Args::parse_from(["seed"].iter().chain(extra))
+    }
+
+    async fn count(db: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn seeding_makes_the_requested_themes_and_votes(db: PgPool) {
+        let args = args(&["--themes", "30", "--users", "150", "--votes-per-user", "4"]);
+        assert_eq!(seed(&db, &args).await.unwrap(), 600);
+        assert_eq!(count(&db, "themes").await, 30);
+        assert_eq!(count(&db, "votes").await, 600);
+
+        let per_user: Vec<i64> = sqlx::query_scalar("SELECT COUNT(*) FROM votes GROUP BY user_id")
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(per_user.len(), 150);
+        assert!(per_user.iter().all(|&votes| votes == 4));
+    }
+
+    #[sqlx::test]
+    async fn seeding_again_tops_up_without_duplicates(db: PgPool) {
+        sqlx::query("INSERT INTO themes (content) VALUES ('Bees')")
+            .execute(&db)
+            .await
+            .unwrap();
+        seed(
+            &db,
+            &args(&["--themes", "3", "--users", "2", "--votes-per-user", "2"]),
+        )
+        .await
+        .unwrap();
+
+        // Existing themes are kept, and nobody votes on more themes than there are
+        let votes = seed(
+            &db,
+            &args(&["--themes", "5", "--users", "2", "--votes-per-user", "9"]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(votes, 6);
+        assert_eq!(count(&db, "themes").await, 6);
+        assert_eq!(count(&db, "votes").await, 10);
+
+        seed(&db, &args(&["--themes", "1", "--users", "0", "--reset"]))
+            .await
+            .unwrap();
+        assert_eq!(count(&db, "themes").await, 1);
+        assert_eq!(count(&db, "votes").await, 0);
+    }
+}
@@ -0,0 +1,47 @@
+/// Longest tag accepted, in characters.
+pub const MAX_LENGTH: usize = 32;
+
+/// Lowercases a tag and checks it is a single short word.
+pub fn normalize(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() {
+        Err("Tag is empty".to_string())
+    } else if tag.chars().count() > MAX_LENGTH {
+        Err(format!(
+            "Tag is too long (maximum is {MAX_LENGTH} characters)"
+        ))
+    } else if tag.chars().any(|c| c.is_whitespace() || c == ',') {
+        Err(format!("Tag \"{tag}\" can't contain spaces or commas"))
+    } else {
+        Ok(tag)
+    }
+}
+
+/// Parses a comma-separated tag list, dropping blanks and duplicates.
+pub fn parse_list(list: &str) -> Result<Vec<String>, String> {
+    let mut tags = Vec::new();
+    for tag in list.split(',').filter(|tag| !tag.trim().is_empty()) {
+        let tag = normalize(tag)?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// Splits trailing `#tag` tokens off a line, e.g. `"Lost at sea #horror #co-op"`.
+pub fn split_trailing(line: &str) -> (&str, Vec<&str>) {
+    let mut content = line.trim_end();
+    let mut tags = Vec::new();
+    while let Some((rest, last)) = content.rsplit_once(char::is_whitespace) {
+        match last.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() => {
+                tags.push(tag);
+                content = rest.trim_end();
+            }
+            _ => break,
+        }
+    }
+    tags.reverse();
+    (content, tags)
+}
@@ -0,0 +1,146 @@
+use std::env;
+use unicode_normalization::UnicodeNormalization;
+
+/// Shortest theme accepted unless `THEME_MIN_LENGTH` says otherwise, in characters.
+pub const MIN_LENGTH: usize = 2;
+/// Longest theme accepted unless `THEME_MAX_LENGTH` says otherwise, in characters.
+pub const MAX_LENGTH: usize = 100;
+
+/// The accepted range of theme lengths, in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthLimits {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Default for LengthLimits {
+    fn default() -> Self {
+        Self {
+            min: MIN_LENGTH,
+            max: MAX_LENGTH,
+        }
+    }
+}
+
+impl LengthLimits {
+    /// The limits set by `THEME_MIN_LENGTH` and `THEME_MAX_LENGTH`, defaulting to
+    /// [`MIN_LENGTH`] and [`MAX_LENGTH`].
+    pub fn from_env() -> anyhow::Result<Self> {
+        let limit = |key: &str, default: usize| match env::var(key) {
+            Ok(value) if !value.trim().is_empty() => value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| anyhow::anyhow!("{key} must be a number of characters")),
+            _ => Ok(default),
+        };
+        let limits = Self {
+            min: limit("THEME_MIN_LENGTH", MIN_LENGTH)?,
+            max: limit("THEME_MAX_LENGTH", MAX_LENGTH)?,
+        };
+        anyhow::ensure!(
+            limits.min <= limits.max,
+            "THEME_MIN_LENGTH ({}) is above THEME_MAX_LENGTH ({})",
+            limits.min,
+            limits.max
+        );
+        Ok(limits)
+    }
+
+    /// Checks that already-trimmed theme content is within the accepted length range.
+    pub fn validate(&self, content: &str) -> Result<(), String> {
+        let len = content.chars().count();
+        if len < self.min {
+            Err(format!(
+                "Theme is too short ({len} characters, minimum is {})",
+                self.min
+            ))
+        } else if len > self.max {
+            Err(format!(
+                "Theme is too long ({len} characters, maximum is {})",
+                self.max
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Longest description accepted, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 500;
+
+/// Cleans up an optional theme description like the theme itself. Blank means none.
+pub fn description(raw: &str) -> Result<Option<String>, String> {
+    let description = normalize(raw);
+    let len = description.chars().count();
+    if len == 0 {
+        Ok(None)
+    } else if len > MAX_DESCRIPTION_LENGTH {
+        Err(format!(
+            "Description is too long ({len} characters, maximum is {MAX_DESCRIPTION_LENGTH})"
+        ))
+    } else {
+        Ok(Some(description))
+    }
+}
+
+/// Invisible characters that would otherwise make two identical-looking themes differ.
+const INVISIBLE: &[char] = &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+/// Cleans up raw theme text before it's validated or compared: Unicode is normalized (NFC),
+/// control and zero-width characters are dropped, and runs of whitespace become one space.
+pub fn normalize(raw: &str) -> String {
+    let cleaned: String = raw
+        .nfc()
+        .filter(|c| !INVISIBLE.contains(c))
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .filter(|c| !c.is_control())
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_at_the_limits_are_accepted() {
+        let limits = LengthLimits { min: 3, max: 5 };
+        assert_eq!(limits.validate("abc"), Ok(()));
+        assert_eq!(limits.validate("abcde"), Ok(()));
+        // Counted in characters, not bytes
+        assert_eq!(limits.validate("ééééé"), Ok(()));
+    }
+
+    #[test]
+    fn lengths_past_the_limits_are_rejected() {
+        let limits = LengthLimits { min: 3, max: 5 };
+        let short = limits.validate("ab").unwrap_err();
+        assert!(
+            short.contains("too short") && short.contains("minimum is 3"),
+            "{short}"
+        );
+        let long = limits.validate("abcdef").unwrap_err();
+        assert!(
+            long.contains("too long") && long.contains("maximum is 5"),
+            "{long}"
+        );
+    }
+
+    #[test]
+    fn default_limits_match_the_constants() {
+        let limits = LengthLimits::default();
+        assert!(limits.validate(&"a".repeat(MIN_LENGTH - 1)).is_err());
+        assert!(limits.validate(&"a".repeat(MIN_LENGTH)).is_ok());
+        assert!(limits.validate(&"a".repeat(MAX_LENGTH)).is_ok());
+        assert!(limits.validate(&"a".repeat(MAX_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn normalize_drops_zero_width_spaces_and_collapses_tabs() {
+        assert_eq!(normalize("Bees\u{200B}\tand\t\tknees"), "Bees and knees");
+        assert_eq!(normalize(" \tBees\u{200B} "), "Bees");
+        // Control characters go, and composed and decomposed forms come out the same
+        assert_eq!(normalize("Bee\u{7}s"), "Bees");
+        assert_eq!(normalize("Cafe\u{301}"), normalize("Caf\u{e9}"));
+    }
+}